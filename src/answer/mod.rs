@@ -0,0 +1,117 @@
+use crate::parse::ParseError;
+use std::error::Error;
+use std::fmt;
+
+/// The answer to a puzzle part, in the form it is entered on the website
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    Int(i64),
+    Str(String),
+    /// An answer along with how it was found. Only the answer itself is entered on the
+    /// website and recorded in the ledger.
+    Explained(Box<Answer>, String),
+}
+
+impl Answer {
+    pub fn explained(self, explanation: impl Into<String>) -> Answer {
+        Answer::Explained(Box::new(self), explanation.into())
+    }
+
+    pub fn explanation(&self) -> Option<&str> {
+        match self {
+            Answer::Explained(_, explanation) => Some(explanation),
+            _ => None,
+        }
+    }
+}
+
+/// Why a puzzle part has no answer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolveError {
+    /// The input could not be parsed
+    Parse(ParseError),
+    /// The input parsed, but the solver could not find an answer in it
+    Unsolved { day: u8, reason: String },
+}
+
+/// What a puzzle part gives back: its answer, or why there is none
+pub type Solution = Result<Answer, SolveError>;
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Answer::Int(n) => write!(f, "{n}"),
+            Answer::Str(s) => write!(f, "{s}"),
+            Answer::Explained(answer, _) => write!(f, "{answer}"),
+        }
+    }
+}
+
+impl SolveError {
+    pub fn unsolved(day: u8, reason: impl Into<String>) -> SolveError {
+        SolveError::Unsolved {
+            day,
+            reason: reason.into(),
+        }
+    }
+
+    /// The parse error, if that is why there is no answer
+    pub fn into_parse_error(self) -> Option<ParseError> {
+        match self {
+            SolveError::Parse(error) => Some(error),
+            SolveError::Unsolved { .. } => None,
+        }
+    }
+}
+
+impl From<ParseError> for SolveError {
+    fn from(error: ParseError) -> SolveError {
+        SolveError::Parse(error)
+    }
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SolveError::Parse(error) => write!(f, "{error}"),
+            SolveError::Unsolved { day, reason } => {
+                write!(f, "Day {day:02} input has no answer: {reason}")
+            }
+        }
+    }
+}
+
+impl Error for SolveError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SolveError::Parse(error) => Some(error),
+            SolveError::Unsolved { .. } => None,
+        }
+    }
+}
+
+macro_rules! impl_from_int {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(n: $t) -> Answer {
+                    Answer::Int(i64::try_from(n).expect("Answer does not fit in an i64."))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_int!(i32, i64, i128, u8, u32, u64, u128, usize);
+
+impl From<String> for Answer {
+    fn from(s: String) -> Answer {
+        Answer::Str(s)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(s: &str) -> Answer {
+        Answer::Str(s.to_string())
+    }
+}
@@ -0,0 +1,265 @@
+use crate::answer::SolveError;
+use crate::runner::Solver;
+use serde::Serialize;
+use std::cell::Cell;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+/// Environment variable naming the benchmark report file
+pub const REPORT_VAR: &str = "AOC_BENCH_REPORT";
+
+/// Report file used when neither `--report` nor the environment variable is given
+pub const DEFAULT_REPORT: &str = "bench.json";
+
+thread_local! {
+    static PARSED_AT: Cell<Option<Instant>> = const { Cell::new(None) };
+}
+
+/// Mark the point where a solution has finished parsing its input, so that parsing and
+/// solving are timed separately. Only the first mark of each run counts.
+pub fn parsed() {
+    PARSED_AT.with(|parsed_at| {
+        if parsed_at.get().is_none() {
+            parsed_at.set(Some(Instant::now()));
+        }
+    });
+}
+
+/// Fastest, median and slowest of a set of timings, in nanoseconds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Stats {
+    pub min_ns: u64,
+    pub median_ns: u64,
+    pub max_ns: u64,
+}
+
+impl Stats {
+    /// Summary of at least one timing
+    pub fn from_durations(durations: &[Duration]) -> Stats {
+        let mut nanos: Vec<u64> = durations.iter().map(|d| d.as_nanos() as u64).collect();
+        nanos.sort_unstable();
+        Stats {
+            min_ns: nanos[0],
+            median_ns: nanos[nanos.len() / 2],
+            max_ns: nanos[nanos.len() - 1],
+        }
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let [min, median, max] =
+            [self.min_ns, self.median_ns, self.max_ns].map(Duration::from_nanos);
+        write!(f, "{min:.2?} / {median:.2?} / {max:.2?}")
+    }
+}
+
+/// Timings of one puzzle part over several runs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Measurement {
+    pub day: u8,
+    pub part: u8,
+    pub runs: usize,
+    pub answer: String,
+    /// Time spent parsing, if the solution marks where parsing ends
+    pub parse: Option<Stats>,
+    /// Time spent after parsing, or in total if parsing is not marked
+    pub solve: Stats,
+    /// Time spent in total, parsing and solving together
+    pub total: Stats,
+}
+
+impl fmt::Display for Measurement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Day {:02} part {}: {} (",
+            self.day, self.part, self.answer
+        )?;
+        if let Some(parse) = self.parse {
+            write!(f, "parse {parse}, solve {}, ", self.solve)?;
+        }
+        write!(
+            f,
+            "total {}, min / median / max of {})",
+            self.total, self.runs
+        )
+    }
+}
+
+/// Run a solver `runs` times on the same input and time each run
+pub fn measure(
+    day: u8,
+    part: u8,
+    solver: Solver,
+    input_text: &str,
+    runs: usize,
+) -> Result<Measurement, SolveError> {
+    let mut parse_times = Vec::new();
+    let mut solve_times = Vec::new();
+    let mut total_times = Vec::new();
+    let mut answer = String::new();
+    for _ in 0..runs.max(1) {
+        PARSED_AT.with(|parsed_at| parsed_at.set(None));
+        let start = Instant::now();
+        answer = solver(input_text)?.to_string();
+        let end = Instant::now();
+        total_times.push(end - start);
+        match PARSED_AT.with(|parsed_at| parsed_at.get()) {
+            Some(parsed_at) => {
+                parse_times.push(parsed_at - start);
+                solve_times.push(end - parsed_at);
+            }
+            None => solve_times.push(end - start),
+        }
+    }
+    Ok(Measurement {
+        day,
+        part,
+        runs: solve_times.len(),
+        answer,
+        parse: (parse_times.len() == solve_times.len())
+            .then(|| Stats::from_durations(&parse_times)),
+        solve: Stats::from_durations(&solve_times),
+        total: Stats::from_durations(&total_times),
+    })
+}
+
+/// Measurements of every part that was benchmarked, written out as JSON so that
+/// reports from different commits can be compared
+#[derive(Debug, Default, Serialize)]
+pub struct Report {
+    /// Commit of the checkout the solutions were built from, if it can be found
+    commit: Option<String>,
+    measurements: Vec<Measurement>,
+}
+
+impl Report {
+    /// Empty report for the current commit
+    pub fn new() -> Report {
+        Report {
+            commit: current_commit(),
+            measurements: Vec::new(),
+        }
+    }
+
+    pub fn commit(&self) -> Option<&str> {
+        self.commit.as_deref()
+    }
+
+    pub fn measurements(&self) -> &[Measurement] {
+        &self.measurements
+    }
+
+    pub fn add(&mut self, measurement: Measurement) {
+        self.measurements.push(measurement);
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let text = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Could not encode the benchmark report: {e}"))?;
+        fs::write(path, text + "\n").map_err(|e| {
+            format!(
+                "Could not write the benchmark report {}: {e}",
+                path.display()
+            )
+        })
+    }
+}
+
+/// `git rev-parse HEAD` in the crate's checkout, or `None` without git or a checkout
+fn current_commit() -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .output()
+        .ok()?;
+    let commit = String::from_utf8(output.stdout).ok()?;
+    output.status.success().then(|| commit.trim().to_string())
+}
+
+/// Report file from `--report`, then `$AOC_BENCH_REPORT`, then the default
+pub fn report_path(report: Option<PathBuf>) -> PathBuf {
+    report
+        .or_else(|| env::var_os(REPORT_VAR).map(PathBuf::from))
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_REPORT))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::answer::Solution;
+    use std::thread;
+
+    fn marked(input: &str) -> Solution {
+        let n: u64 = input.parse().unwrap();
+        parsed();
+        Ok((n * 2).into())
+    }
+
+    /// Takes at least a millisecond to parse and another to solve
+    fn slow(input: &str) -> Solution {
+        thread::sleep(Duration::from_millis(1));
+        parsed();
+        thread::sleep(Duration::from_millis(1));
+        Ok(input.len().into())
+    }
+
+    fn unmarked(input: &str) -> Solution {
+        Ok(input.len().into())
+    }
+
+    #[test]
+    fn stats() {
+        let durations = [5, 1, 3, 2].map(Duration::from_nanos);
+        let stats = Stats::from_durations(&durations);
+        assert_eq!((stats.min_ns, stats.median_ns, stats.max_ns), (1, 3, 5));
+    }
+
+    #[test]
+    fn measurements() {
+        let measurement = measure(1, 2, marked, "21", 3).unwrap();
+        assert_eq!((measurement.runs, measurement.answer.as_str()), (3, "42"));
+        assert!(measurement.parse.is_some());
+
+        let measurement = measure(1, 1, unmarked, "21", 0).unwrap();
+        assert_eq!(measurement.runs, 1);
+        assert_eq!(measurement.parse, None);
+        assert_eq!(measurement.solve, measurement.total);
+    }
+
+    #[test]
+    fn report() {
+        let mut report = Report {
+            commit: Some("0123abc".to_string()),
+            measurements: Vec::new(),
+        };
+        report.add(measure(1, 2, marked, "21", 1).unwrap());
+        let json: serde_json::Value = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["commit"], "0123abc");
+        assert_eq!(json["measurements"][0]["answer"], "42");
+
+        // Tests run from the checkout, which may not be a git repository
+        if let Some(commit) = Report::new().commit() {
+            assert_eq!(commit.len(), 40);
+            assert!(commit.chars().all(|c| c.is_ascii_hexdigit()));
+        }
+    }
+
+    #[test]
+    fn parse_and_solve_add_up() {
+        let measurement = measure(1, 1, slow, "21", 1).unwrap();
+        let parse = measurement.parse.unwrap();
+        let millisecond = Duration::from_millis(1).as_nanos() as u64;
+        assert!(parse.min_ns >= millisecond);
+        assert!(measurement.solve.min_ns >= millisecond);
+        assert_eq!(
+            parse.min_ns + measurement.solve.min_ns,
+            measurement.total.min_ns
+        );
+    }
+}
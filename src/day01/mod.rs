@@ -1,30 +1,54 @@
+use crate::answer::Solution;
+use crate::bench;
+use crate::parse::ParseError;
 use regex::{Captures, Regex};
-use std::{fs, ops::Deref};
 
-pub fn solution1() {
-    let contents = fs::read_to_string("src/day1/input.txt").expect("Unable to read file.");
+const DAY: u8 = 1;
+
+/// The first and last digit of a line, as a two digit number
+fn calibration_value(line: &str, digits: &[&str]) -> Result<i32, ParseError> {
+    match (digits.first(), digits.last()) {
+        (Some(first), Some(last)) => {
+            let first: i32 = first.parse().unwrap();
+            let last: i32 = last.parse().unwrap();
+            Ok(first * 10 + last)
+        }
+        _ => Err(ParseError::new(
+            DAY,
+            1,
+            line,
+            "expected a line with a digit",
+        )),
+    }
+}
+
+pub fn solution1(input: &str) -> Solution {
     let re = Regex::new(r"(\d)").unwrap();
-    let lines: Vec<i32> = contents
-        .split('\n')
-        .map(|s| {
+    let lines: Vec<&str> = input.split('\n').collect();
+    bench::parsed();
+
+    let total: i32 = lines
+        .into_iter()
+        .enumerate()
+        .map(|(i, s)| {
             let digits: Vec<&str> = re.find_iter(s).map(|m| m.as_str()).collect();
-            let first: i32 = digits.first().unwrap().deref().parse().unwrap();
-            let last: i32 = digits.last().unwrap().deref().parse().unwrap();
-            first * 10 + last
+            calibration_value(s, &digits).map_err(|e| e.on_line(i + 1))
         })
-        .collect();
-    println!("{:?}", lines);
+        .sum::<Result<i32, ParseError>>()?;
+    Ok(total.into())
 }
 
-pub fn solution2() {
-    let contents = fs::read_to_string("src/day1/input.txt").expect("Unable to read file.");
+pub fn solution2(input: &str) -> Solution {
     let digit_regex = Regex::new(r"(\d)").unwrap();
     let text_regex = Regex::new(r"(one|two|three|four|five|six|seven|eight|nine)").unwrap();
     let reverse_regex = Regex::new(r"(enin|thgie|neves|xis|evif|ruof|eerht|owt|eno)").unwrap();
-    let total: i32 = contents
-        .split('\n')
-        .map(|s| {
-            println!("{}", s);
+    let lines: Vec<&str> = input.split('\n').collect();
+    bench::parsed();
+
+    let total: i32 = lines
+        .into_iter()
+        .enumerate()
+        .map(|(i, s)| {
             let text = text_regex.replace(s, |c: &Captures| match &c[0] {
                 "one" => "1one",
                 "two" => "2two",
@@ -59,12 +83,48 @@ pub fn solution2() {
                 .find_iter(text.as_str())
                 .map(|m| m.as_str())
                 .collect();
-            let first: i32 = digits.first().unwrap().deref().parse().unwrap();
-            let last: i32 = digits.last().unwrap().deref().parse().unwrap();
-            let sum = first * 10 + last;
-            println!("{}", sum);
-            sum
+            calibration_value(s, &digits).map_err(|e| e.on_line(i + 1))
         })
-        .sum();
-    println!("Total: {}", total);
+        .sum::<Result<i32, ParseError>>()?;
+    Ok(total.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::answer::Answer;
+
+    const EXAMPLE_1: &str = "\
+1abc2
+pqr3stu8vwx
+a1b2c3d4e5f
+treb7uchet";
+
+    const EXAMPLE_2: &str = "\
+two1nine
+eightwothree
+abcone2threexyz
+xtwone3four
+4nineeightseven2
+zoneight234
+7pqrstsixteen";
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(solution1(EXAMPLE_1), Ok(Answer::Int(142)));
+    }
+
+    #[test]
+    fn example_part2() {
+        assert_eq!(solution2(EXAMPLE_2), Ok(Answer::Int(281)));
+    }
+
+    #[test]
+    fn malformed_input() {
+        let error = solution1("1abc2\nabc").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!((error.line, error.text.as_str()), (2, "abc"));
+        let error = solution2("two1nine\nabc").unwrap_err();
+        assert_eq!(error.into_parse_error().unwrap().line, 2);
+    }
 }
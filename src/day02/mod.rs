@@ -1,104 +1,172 @@
-use regex::Regex;
-use std::fs;
-
-pub fn solution1() {
-    let contents = fs::read_to_string("src/day2/input.txt").expect("Unable to read file.");
-    let possible_games_sum: i32 = contents
-        .split('\n')
-        .map(|s| {
-            // Extract game id
-            let re = Regex::new(r"(\d*):").unwrap();
-            let game_id = &re.captures(s).unwrap()[1];
-            let game_id: i32 = game_id.parse().unwrap();
-
-            // Extract subsets
-            let re = Regex::new(r":(.*)").unwrap();
-            let game_data = &re.captures(s).unwrap()[1];
-            let impossible_subset_detected = game_data
-                .split(';')
-                // Determine if a given subset is possible or not
-                .any(|ss| {
-                    // Extract number of each cube in subset
-                    let red_regex = Regex::new(r"(\d*) red").unwrap();
-                    let green_regex = Regex::new(r"(\d*) green").unwrap();
-                    let blue_regex = Regex::new(r"(\d*) blue").unwrap();
-
-                    // Number of each cube (defaults)
-                    let mut red_num: i32 = 0;
-                    let mut green_num: i32 = 0;
-                    let mut blue_num: i32 = 0;
-
-                    // Add observed cubes
-                    if let Some(red_capture) = red_regex.captures(ss) {
-                        red_num += red_capture[1].parse::<i32>().unwrap();
-                    }
-
-                    if let Some(green_capture) = green_regex.captures(ss) {
-                        green_num += green_capture[1].parse::<i32>().unwrap();
-                    }
-
-                    if let Some(blue_capture) = blue_regex.captures(ss) {
-                        blue_num += blue_capture[1].parse::<i32>().unwrap();
-                    }
-
-                    // Determine if any cube counts are impossible
-                    (red_num > 12) || (green_num > 13) || (blue_num > 14)
-                });
-
-            // Return game id if impossible subsets are detected, 0 otherwise
-            if impossible_subset_detected {
-                0
-            } else {
-                game_id
+use crate::answer::Solution;
+use crate::bench;
+use crate::parse::{self, ParseError};
+use std::str::FromStr;
+
+const DAY: u8 = 2;
+
+/// Number of cubes of each color, either shown in one handful or in the bag
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct Cubes {
+    red: i32,
+    green: i32,
+    blue: i32,
+}
+
+/// The cubes in the bag for part 1
+const BAG: Cubes = Cubes {
+    red: 12,
+    green: 13,
+    blue: 14,
+};
+
+impl FromStr for Cubes {
+    type Err = ParseError;
+
+    /// Parse a handful such as `3 blue, 4 red`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut cubes = Cubes::default();
+        for (column, field) in parse::fields(s, ',') {
+            let text = field.trim_start();
+            let column = column + field.len() - text.len();
+            let text = text.trim_end();
+            let (count, color) = text.split_once(' ').ok_or_else(|| {
+                ParseError::new(DAY, column, text, "expected a count and a color")
+            })?;
+            let count: i32 = parse::number(DAY, column, count)?;
+            let color_column = column + text.len() - color.len();
+            match color {
+                "red" => cubes.red += count,
+                "green" => cubes.green += count,
+                "blue" => cubes.blue += count,
+                _ => {
+                    return Err(ParseError::new(
+                        DAY,
+                        color_column,
+                        color,
+                        "expected red, green or blue",
+                    ))
+                }
             }
-        })
-        .sum();
+        }
+        Ok(cubes)
+    }
+}
+
+impl Cubes {
+    fn fits_in(&self, bag: &Cubes) -> bool {
+        self.red <= bag.red && self.green <= bag.green && self.blue <= bag.blue
+    }
+
+    /// The fewest cubes of each color that cover both
+    fn max(self, other: Cubes) -> Cubes {
+        Cubes {
+            red: self.red.max(other.red),
+            green: self.green.max(other.green),
+            blue: self.blue.max(other.blue),
+        }
+    }
+
+    fn power(&self) -> i32 {
+        self.red * self.green * self.blue
+    }
+}
 
-    println!("Sum of possible game IDs: {}", possible_games_sum);
+#[derive(Debug)]
+struct Game {
+    id: i32,
+    handfuls: Vec<Cubes>,
 }
 
-fn get_cube_count(s: &str, regex: &str) -> i32 {
-    let re = Regex::new(regex).unwrap();
-    let mut count: i32 = 0;
-    if let Some(capture) = re.captures(s) {
-        count += capture[1].parse::<i32>().unwrap();
+impl FromStr for Game {
+    type Err = ParseError;
+
+    /// Parse a line such as `Game 1: 3 blue, 4 red; 1 red, 2 green`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rest = s
+            .strip_prefix("Game ")
+            .ok_or_else(|| ParseError::new(DAY, 1, s, "expected 'Game'"))?;
+        let (id, handfuls) = rest
+            .split_once(':')
+            .ok_or_else(|| ParseError::new(DAY, 6, rest, "expected a game id and ':'"))?;
+        let offset = "Game ".len() + id.len() + 1;
+        let handfuls = parse::fields(handfuls, ';')
+            .map(|(column, handful)| {
+                Cubes::from_str(handful).map_err(|e| e.shifted(offset + column - 1))
+            })
+            .collect::<Result<Vec<Cubes>, ParseError>>()?;
+        Ok(Game {
+            id: parse::number(DAY, 6, id)?,
+            handfuls,
+        })
     }
-    count
 }
 
-pub fn solution2() {
-    let contents = fs::read_to_string("src/day2/input.txt").expect("Unable to read file.");
-    let power_games_sum: i32 = contents
-        .split('\n')
-        .map(|s| {
-            // Extract subsets
-            let re = Regex::new(r":(.*)").unwrap();
-            let game_data = &re.captures(s).unwrap()[1];
-
-            // Extract maximum number of blue cubes
-            let maximum_blue = game_data
-                .split(';')
-                .map(|ss| get_cube_count(ss, r"(\d*) blue"))
-                .max()
-                .unwrap();
-
-            // Extract maximum number of blue cubes
-            let maximum_red = game_data
-                .split(';')
-                .map(|ss| get_cube_count(ss, r"(\d*) red"))
-                .max()
-                .unwrap();
-
-            // Extract maximum number of green cubes
-            let maximum_green = game_data
-                .split(';')
-                .map(|ss| get_cube_count(ss, r"(\d*) green"))
-                .max()
-                .unwrap();
-
-            maximum_blue * maximum_red * maximum_green
+pub fn solution1(input: &str) -> Solution {
+    let games: Vec<Game> = parse::lines(input, Game::from_str)?;
+    bench::parsed();
+
+    // Sum the ids of the games in which every handful could have come from the bag
+    let possible_games_sum: i32 = games
+        .iter()
+        .filter(|game| game.handfuls.iter().all(|cubes| cubes.fits_in(&BAG)))
+        .map(|game| game.id)
+        .sum();
+
+    Ok(possible_games_sum.into())
+}
+
+pub fn solution2(input: &str) -> Solution {
+    let games: Vec<Game> = parse::lines(input, Game::from_str)?;
+    bench::parsed();
+
+    // Sum the powers of the smallest bags each game could have been played with
+    let power_games_sum: i32 = games
+        .iter()
+        .map(|game| {
+            let fewest = game
+                .handfuls
+                .iter()
+                .fold(Cubes::default(), |a, b| a.max(*b));
+            fewest.power()
         })
         .sum();
 
-    println!("Power of game IDs: {}", power_games_sum);
+    Ok(power_games_sum.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::answer::Answer;
+
+    const EXAMPLE: &str = "\
+Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(solution1(EXAMPLE), Ok(Answer::Int(8)));
+    }
+
+    #[test]
+    fn example_part2() {
+        assert_eq!(solution2(EXAMPLE), Ok(Answer::Int(2286)));
+    }
+
+    #[test]
+    fn malformed_input() {
+        let error = solution1("Game 1: 3 blue\nGame x: 1 red").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!((error.line, error.column, error.text.as_str()), (2, 6, "x"));
+        let error = solution1("Game 12: 3 blue; 4 purple").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!((error.column, error.text.as_str()), (20, "purple"));
+        let error = solution2("Game 1: 3 blue, red").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!((error.column, error.text.as_str()), (17, "red"));
+    }
 }
@@ -1,296 +1,118 @@
+use crate::answer::Solution;
+use crate::bench;
+use crate::grid::Grid;
+use crate::parse::{self, ParseError};
 use regex::Regex;
-use std::{
-    collections::{HashMap, HashSet},
-    fs,
-};
+use std::collections::{HashMap, HashSet};
 
-struct Grid {
-    i_limit: usize,
-    j_limit: usize,
-}
+const DAY: u8 = 3;
 
-impl Grid {
-    fn valid_offset(&self, i: usize, j: usize, i_offset: i32, j_offset: i32) -> bool {
-        let i: i32 = i as i32 + i_offset;
-        let j: i32 = j as i32 + j_offset;
-        (i >= 0) && (i < self.i_limit as i32) && (j >= 0) && (j < self.j_limit as i32)
-    }
+/// Read the schematic. X is used to mark part digits, so it cannot appear in the input.
+fn parse_schematic(input: &str) -> Result<Grid<char>, ParseError> {
+    Grid::try_parse(input, DAY, "expected a digit, '.' or a symbol", |c| {
+        (!c.is_whitespace() && c != 'X').then_some(c)
+    })
 }
 
-pub fn solution1() {
-    // Read data
-    let contents: String = fs::read_to_string("src/day3/input.txt").expect("Unable to read file.");
-    let mut matrix: Vec<Vec<char>> = contents
-        .split('\n')
-        .map(|s| {
-            let vec: Vec<char> = s.chars().collect();
-            vec
-        })
-        .collect();
-
-    // Get row & column count
-    let nrows = contents.split('\n').count();
-    let ncols = contents
-        .split('\n')
-        .map(|s| s.chars().count())
-        .max()
-        .unwrap()
-        - 1;
-    let grid = Grid {
-        i_limit: nrows,
-        j_limit: ncols,
-    };
+/// Replace every digit that belongs to a part number with an X
+fn mark_part_digits(grid: &Grid<char>) -> Grid<char> {
+    let mut marked = grid.clone();
 
     // If a digit is next to a symbol or an X, replace it with an X
     let digits: HashSet<char> = "0123456789".chars().collect();
     let nonsymbols: HashSet<char> = "0123456789.".chars().collect();
     for _ in 0..5 {
-        for i in 0..nrows {
-            for j in 0..ncols {
-                if digits.contains(&matrix[i][j]) {
-                    // Top left
-                    if grid.valid_offset(i, j, -1, -1)
-                        && !nonsymbols.contains(&matrix[i - 1][j - 1])
-                    {
-                        matrix[i][j] = 'X';
-                    }
-
-                    // Top middle
-                    if grid.valid_offset(i, j, -1, 0) && !nonsymbols.contains(&matrix[i - 1][j]) {
-                        matrix[i][j] = 'X';
-                    }
-
-                    // Top right
-                    if grid.valid_offset(i, j, -1, 1) && !nonsymbols.contains(&matrix[i - 1][j + 1])
-                    {
-                        matrix[i][j] = 'X';
-                    }
-
-                    // Center left
-                    if grid.valid_offset(i, j, 0, -1) && !nonsymbols.contains(&matrix[i][j - 1]) {
-                        matrix[i][j] = 'X';
-                    }
-
-                    // Center right
-                    if grid.valid_offset(i, j, 0, 1) && !nonsymbols.contains(&matrix[i][j + 1]) {
-                        matrix[i][j] = 'X';
-                    }
-
-                    // Bottom left
-                    if grid.valid_offset(i, j, 1, -1) && !nonsymbols.contains(&matrix[i + 1][j - 1])
-                    {
-                        matrix[i][j] = 'X';
-                    }
-
-                    // Bottom middle
-                    if grid.valid_offset(i, j, 1, 0) && !nonsymbols.contains(&matrix[i + 1][j]) {
-                        matrix[i][j] = 'X';
-                    }
-
-                    // Bottom right
-                    if grid.valid_offset(i, j, 1, 1) && !nonsymbols.contains(&matrix[i + 1][j + 1])
-                    {
-                        matrix[i][j] = 'X';
-                    }
-                }
+        for position in marked.positions() {
+            if digits.contains(&marked[position])
+                && marked
+                    .neighbors8(position)
+                    .any(|neighbor| !nonsymbols.contains(&marked[neighbor]))
+            {
+                marked[position] = 'X';
             }
         }
     }
+    marked
+}
 
-    // Get original data
-    let mut original_matrix: Vec<Vec<char>> = contents
-        .split('\n')
-        .map(|s| {
-            let vec: Vec<char> = s.chars().collect();
-            vec
-        })
-        .collect();
+pub fn solution1(input: &str) -> Solution {
+    // Read data
+    let mut grid = parse_schematic(input)?;
+    bench::parsed();
+    let marked = mark_part_digits(&grid);
 
     // Mask the original data where it has not been marked with X
-    for i in 0..nrows {
-        for j in 0..ncols {
-            if matrix[i][j] != 'X' {
-                original_matrix[i][j] = '.';
-            }
+    for position in grid.positions() {
+        if marked[position] != 'X' {
+            grid[position] = '.';
         }
     }
 
     // Sum numbers
     let re = Regex::new(r"([0-9]+)").unwrap();
     let mut total = 0;
-    for r in original_matrix.iter().take(nrows) {
-        let row: String = r.clone().into_iter().collect();
-        for (_, [s]) in re.captures_iter(row.as_str()).map(|c| c.extract()) {
-            let num: i32 = s.parse().unwrap();
+    for (i, r) in grid.rows().enumerate() {
+        let row: String = r.iter().collect();
+        for m in re.find_iter(row.as_str()) {
+            let num: i32 =
+                parse::number(DAY, m.start() + 1, m.as_str()).map_err(|e| e.on_line(i + 1))?;
             total += num;
         }
     }
 
-    println!("Sum of parts: {}", total);
+    Ok(total.into())
 }
 
-pub fn solution2() {
+pub fn solution2(input: &str) -> Solution {
     // Read data
-    let contents: String = fs::read_to_string("src/day3/input.txt").expect("Unable to read file.");
-    let mut matrix: Vec<Vec<char>> = contents
-        .split('\n')
-        .map(|s| {
-            let vec: Vec<char> = s.chars().collect();
-            vec
-        })
-        .collect();
-
-    // Get row & column count
-    let nrows = contents.split('\n').count();
-    let ncols = contents
-        .split('\n')
-        .map(|s| s.chars().count())
-        .max()
-        .unwrap()
-        - 1;
-    let grid = Grid {
-        i_limit: nrows,
-        j_limit: ncols,
-    };
-
-    // If a digit is next to a symbol or an X, replace it with an X
-    let digits: HashSet<char> = "0123456789".chars().collect();
-    let nonsymbols: HashSet<char> = "0123456789.".chars().collect();
-    for _ in 0..5 {
-        for i in 0..nrows {
-            for j in 0..ncols {
-                if digits.contains(&matrix[i][j]) {
-                    // Top left
-                    if grid.valid_offset(i, j, -1, -1)
-                        && !nonsymbols.contains(&matrix[i - 1][j - 1])
-                    {
-                        matrix[i][j] = 'X';
-                    }
-
-                    // Top middle
-                    if grid.valid_offset(i, j, -1, 0) && !nonsymbols.contains(&matrix[i - 1][j]) {
-                        matrix[i][j] = 'X';
-                    }
-
-                    // Top right
-                    if grid.valid_offset(i, j, -1, 1) && !nonsymbols.contains(&matrix[i - 1][j + 1])
-                    {
-                        matrix[i][j] = 'X';
-                    }
-
-                    // Center left
-                    if grid.valid_offset(i, j, 0, -1) && !nonsymbols.contains(&matrix[i][j - 1]) {
-                        matrix[i][j] = 'X';
-                    }
-
-                    // Center right
-                    if grid.valid_offset(i, j, 0, 1) && !nonsymbols.contains(&matrix[i][j + 1]) {
-                        matrix[i][j] = 'X';
-                    }
-
-                    // Bottom left
-                    if grid.valid_offset(i, j, 1, -1) && !nonsymbols.contains(&matrix[i + 1][j - 1])
-                    {
-                        matrix[i][j] = 'X';
-                    }
-
-                    // Bottom middle
-                    if grid.valid_offset(i, j, 1, 0) && !nonsymbols.contains(&matrix[i + 1][j]) {
-                        matrix[i][j] = 'X';
-                    }
-
-                    // Bottom right
-                    if grid.valid_offset(i, j, 1, 1) && !nonsymbols.contains(&matrix[i + 1][j + 1])
-                    {
-                        matrix[i][j] = 'X';
-                    }
-                }
-            }
-        }
-    }
-
-    // Get original data
-    let mut masked_matrix: Vec<Vec<char>> = contents
-        .split('\n')
-        .map(|s| {
-            let vec: Vec<char> = s.chars().collect();
-            vec
-        })
-        .collect();
+    let mut grid = parse_schematic(input)?;
+    bench::parsed();
+    let marked = mark_part_digits(&grid);
 
     // Mask the original data where it has not been marked with X
-    for i in 0..nrows {
-        for j in 0..ncols {
-            if (matrix[i][j] != 'X') && (masked_matrix[i][j] != '*') {
-                masked_matrix[i][j] = '.';
-            }
+    for position in grid.positions() {
+        if (marked[position] != 'X') && (grid[position] != '*') {
+            grid[position] = '.';
         }
     }
 
     // Get list of number locations
+    let digits: HashSet<char> = "0123456789".chars().collect();
     let mut numbers: Vec<Number> = Vec::new();
-    for (i, row) in masked_matrix.iter().enumerate().take(nrows) {
-        for j in 0..ncols {
-            // If current character is a digit
-            if digits.contains(&row[j]) {
-                // Check if character to left is out of bounds
-                let in_bounds = grid.valid_offset(i, j, 0, -1);
-
-                // Check if character to the left is not a digit
-                let not_a_digit = if !in_bounds {
-                    true
-                } else {
-                    !digits.contains(&row[j - 1])
-                };
-
-                // If the character to the left is not a digit
-                if not_a_digit {
-                    let mut offset: usize = 0;
-                    let mut values: Vec<char> = Vec::new();
-                    loop {
-                        if !grid.valid_offset(i, j, 0, offset as i32) {
-                            break;
-                        }
-                        if digits.contains(&masked_matrix[i][j + offset]) {
-                            values.push(masked_matrix[i][j + offset]);
-                            offset += 1;
-                        } else {
-                            break;
-                        }
-                    }
-                    let value = values
-                        .into_iter()
-                        .collect::<String>()
-                        .parse::<i32>()
-                        .unwrap();
-                    numbers.push(Number {
-                        i,
-                        j,
-                        value,
-                        length: offset,
-                        i_limit: nrows,
-                        j_limit: ncols,
-                    });
-                }
+    for (i, j) in grid.positions() {
+        // If current character is a digit
+        if digits.contains(&grid[(i, j)]) {
+            // Check if the character to the left is out of bounds or not a digit
+            let not_a_digit = match grid.offset((i, j), 0, -1) {
+                Some(left) => !digits.contains(&grid[left]),
+                None => true,
+            };
+
+            // If the character to the left is not a digit
+            if not_a_digit {
+                let values: String = grid.row(i)[j..]
+                    .iter()
+                    .take_while(|c| digits.contains(c))
+                    .collect();
+                numbers.push(Number {
+                    i,
+                    j,
+                    value: parse::number(DAY, j + 1, &values).map_err(|e| e.on_line(i + 1))?,
+                    length: values.len(),
+                });
             }
         }
     }
 
-    // Print the map
-    for line in &masked_matrix {
-        let s: String = line.clone().into_iter().collect();
-        println!("{}", s);
-    }
-
     // Create list of asterisks with adjacent numbers
     let mut asterisks: HashMap<(usize, usize), HashSet<Number>> = HashMap::new();
     for number in numbers {
-        for (i, j) in number.adjacent_points() {
-            if masked_matrix[i][j] == '*' {
-                if let Some(points) = asterisks.get_mut(&(i, j)) {
+        for position in number.adjacent_points(&grid) {
+            if grid[position] == '*' {
+                if let Some(points) = asterisks.get_mut(&position) {
                     points.insert(number);
                 } else {
-                    asterisks.insert((i, j), HashSet::from([number]));
+                    asterisks.insert(position, HashSet::from([number]));
                 }
             }
         }
@@ -306,7 +128,7 @@ pub fn solution2() {
         })
         .sum();
 
-    println!("Total gear ratio: {}", total_gear_ratio);
+    Ok(total_gear_ratio.into())
 }
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
@@ -315,39 +137,16 @@ struct Number {
     j: usize,
     value: i32,
     length: usize,
-    i_limit: usize,
-    j_limit: usize,
 }
 
 impl Number {
-    pub fn adjacent_points(&self) -> HashSet<(usize, usize)> {
-        let mut points: HashSet<(usize, usize)> = HashSet::new();
+    pub fn adjacent_points(&self, grid: &Grid<char>) -> HashSet<(usize, usize)> {
         let number_points = self.points();
-        for x in 0..self.length {
-            let i = self.i as i32;
-            let j = self.j as i32;
-            let offset = x as i32;
-
-            let candidates = [
-                (i - 1, j - 1 + offset),
-                (i - 1, j + offset),
-                (i - 1, j + 1 + offset),
-                (i, j - 1 + offset),
-                (i, j + 1 + offset),
-                (i + 1, j - 1 + offset),
-                (i + 1, j + offset),
-                (i + 1, j + 1 + offset),
-            ];
-
-            for (ci, cj) in candidates {
-                if self.valid_point(ci, cj) && !number_points.contains(&(ci as usize, cj as usize))
-                {
-                    points.insert((ci as usize, cj as usize));
-                }
-            }
-        }
-
-        points
+        number_points
+            .iter()
+            .flat_map(|point| grid.neighbors8(*point))
+            .filter(|point| !number_points.contains(point))
+            .collect()
     }
 
     fn points(&self) -> HashSet<(usize, usize)> {
@@ -357,8 +156,48 @@ impl Number {
         }
         pts
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::answer::Answer;
+
+    const EXAMPLE: &str = "\
+467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..";
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(solution1(EXAMPLE), Ok(Answer::Int(4361)));
+    }
+
+    #[test]
+    fn example_part2() {
+        assert_eq!(solution2(EXAMPLE), Ok(Answer::Int(467835)));
+    }
 
-    fn valid_point(&self, i: i32, j: i32) -> bool {
-        (i >= 0) && (i < self.i_limit as i32) && (j >= 0) && (j < self.j_limit as i32)
+    #[test]
+    fn malformed_input() {
+        let error = solution1("467..\n...*").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!(
+            (error.line, error.reason.as_str()),
+            (2, "expected a row of 5 tiles")
+        );
+        let error = solution2("467..\n..X*.").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!((error.line, error.column, error.text.as_str()), (2, 3, "X"));
+        let error = solution1("*99999999999").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!((error.line, error.column), (1, 2));
     }
 }
@@ -1,5 +1,10 @@
-use regex::Regex;
-use std::{collections::HashSet, fs};
+use crate::answer::Solution;
+use crate::bench;
+use crate::parse::{self, ParseError};
+use std::collections::HashSet;
+use std::str::FromStr;
+
+const DAY: u8 = 4;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct Card {
@@ -8,54 +13,52 @@ struct Card {
     id: u8,
 }
 
-impl Card {
-    pub fn extract_from_line(line: &str) -> Card {
-        // Extract id
-        let id_regex = Regex::new(r"Card\s+(\d+):").unwrap();
-        let captures = id_regex.captures(line).unwrap();
-        let id: u8 = captures[1].parse().unwrap();
-
-        // Replace spaces with commas
-        let space_regex = Regex::new(r"( +)").unwrap();
-        let line = space_regex.replace_all(line, ",").to_string();
-
-        // Cleanly format
-        let comma_regex = Regex::new(r":,").unwrap();
-        let line = comma_regex.replace_all(line.as_str(), ":").to_string();
-        let pipe_regex = Regex::new(r",\|,").unwrap();
-        let line = pipe_regex.replace_all(line.as_str(), "|").to_string();
-        let end_regex = Regex::new(r",$").unwrap();
-        let line = end_regex.replace_all(line.as_str(), "").to_string();
-
-        // Extract winning numbers
-        let win_regex = Regex::new(r":([\d|,]+)\|").unwrap();
-        let winning_numbers = win_regex.captures(line.as_str()).unwrap();
-        let winning_numbers: Vec<u8> = winning_numbers[1]
-            .split(',')
-            .map(|s| {
-                let num: u8 = s.parse().unwrap();
-                num
-            })
-            .collect();
-
-        // Extract card numbers
-        let card_regex = Regex::new(r"\|([\d|,]+)").unwrap();
-        let card_numbers = card_regex.captures(line.as_str()).unwrap();
-        let card_numbers: Vec<u8> = card_numbers[1]
-            .split(',')
-            .map(|s| {
-                let num: u8 = s.parse().unwrap();
-                num
-            })
-            .collect();
-
-        Card {
+impl FromStr for Card {
+    type Err = ParseError;
+
+    /// Parse a line such as `Card 1: 41 48 83 | 83 86  6`
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let rest = line
+            .strip_prefix("Card")
+            .ok_or_else(|| ParseError::new(DAY, 1, line, "expected 'Card'"))?;
+        let (id, numbers) = rest
+            .split_once(':')
+            .ok_or_else(|| ParseError::new(DAY, 5, rest, "expected a card number and ':'"))?;
+        let id_column = 5 + id.len() - id.trim_start().len();
+        let id: u8 = parse::number(DAY, id_column, id.trim())?;
+        if id == 0 {
+            return Err(ParseError::new(
+                DAY,
+                id_column,
+                "0",
+                "expected cards to count from 1",
+            ));
+        }
+
+        let offset = "Card".len() + rest.len() - numbers.len();
+        let (winning, card) = numbers
+            .split_once('|')
+            .ok_or_else(|| ParseError::new(DAY, offset + 1, numbers, "expected a '|'"))?;
+        let winning_numbers = parse_numbers(winning, offset)?;
+        let card_numbers = parse_numbers(card, offset + winning.len() + 1)?;
+
+        Ok(Card {
             winning_numbers,
             card_numbers,
             id,
-        }
+        })
     }
+}
 
+/// Parse the space separated numbers of a list that starts after column `offset`
+fn parse_numbers(list: &str, offset: usize) -> Result<Vec<u8>, ParseError> {
+    parse::fields(list, ' ')
+        .filter(|(_, field)| !field.is_empty())
+        .map(|(column, field)| parse::number(DAY, column + offset, field))
+        .collect()
+}
+
+impl Card {
     pub fn winning_card_numbers(&self) -> Vec<u8> {
         let card_hashset: HashSet<u8> = HashSet::from_iter(self.card_numbers.clone());
         let win_hashset: HashSet<u8> = HashSet::from_iter(self.winning_numbers.clone());
@@ -94,25 +97,21 @@ impl Card {
     }
 }
 
-fn read_input() -> String {
-    fs::read_to_string("src/day4/input.txt").expect("Unable to read file.")
-}
-
-pub fn solution1() {
-    let contents = read_input();
-    let cards: Vec<Card> = contents.split('\n').map(Card::extract_from_line).collect();
+pub fn solution1(input: &str) -> Solution {
+    let cards: Vec<Card> = parse::lines(input, Card::from_str)?;
+    bench::parsed();
 
     let mut total: u32 = 0;
     for card in cards {
         total += card.score_points();
     }
 
-    println!("Total: {}", total);
+    Ok(total.into())
 }
 
-pub fn solution2() {
-    let contents = read_input();
-    let cards: Vec<Card> = contents.split('\n').map(Card::extract_from_line).collect();
+pub fn solution2(input: &str) -> Solution {
+    let cards: Vec<Card> = parse::lines(input, Card::from_str)?;
+    bench::parsed();
     let cards_limit = cards.len();
 
     let mut total_cards: u32 = 0;
@@ -134,5 +133,48 @@ pub fn solution2() {
         }
     }
 
-    println!("Total cards: {}", total_cards);
+    Ok(total_cards.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::answer::Answer;
+
+    const EXAMPLE: &str = "\
+Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(solution1(EXAMPLE), Ok(Answer::Int(13)));
+    }
+
+    #[test]
+    fn example_part2() {
+        assert_eq!(solution2(EXAMPLE), Ok(Answer::Int(30)));
+    }
+
+    #[test]
+    fn malformed_input() {
+        let error = solution1("Card 1: 41 48 | 83 86\nCard 2: 13 x2 | 61").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!(
+            (error.line, error.column, error.text.as_str()),
+            (2, 12, "x2")
+        );
+        let error = solution2("Card  3: 1 21 | 69 8a").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!((error.column, error.text.as_str()), (20, "8a"));
+        let error = solution1("Card 1: 41 48 83").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!(error.reason, "expected a '|'");
+        let error = solution2("Card 0: 1 | 1").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!((error.column, error.text.as_str()), (6, "0"));
+    }
 }
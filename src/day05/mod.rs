@@ -1,146 +1,497 @@
-use regex::Regex;
-use std::fs;
+use crate::answer::{Solution, SolveError};
+use crate::bench;
+use crate::parse::{self, ParseError};
+use std::collections::HashMap;
+use std::ops::Range;
 
-#[derive(Debug)]
+const DAY: u8 = 5;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 struct MapEntry {
-    length: u32,
-    destination: u32,
-    source: u32,
+    length: u64,
+    destination: u64,
+    source: u64,
 }
 
 impl MapEntry {
-    pub fn from_str(s: &str) -> MapEntry {
-        let values_regex = Regex::new(r"(\d+) (\d+) (\d+)").unwrap();
-        let captures = values_regex.captures(s).unwrap();
-        let length: u32 = captures[3].parse().unwrap();
-        let destination: u32 = captures[1].parse().unwrap();
-        let source: u32 = captures[2].parse().unwrap();
-
-        MapEntry {
-            length,
-            destination,
-            source,
+    pub fn from_str(s: &str) -> Result<MapEntry, ParseError> {
+        let values: Vec<u64> = parse::fields(s, ' ')
+            .map(|(column, field)| parse::number(DAY, column, field))
+            .collect::<Result<_, _>>()?;
+        match values[..] {
+            [destination, source, length] => Ok(MapEntry {
+                length,
+                destination,
+                source,
+            }),
+            _ => Err(ParseError::new(
+                DAY,
+                1,
+                s,
+                "expected a destination, a source and a length",
+            )),
         }
     }
 
-    pub fn map(&self, value: u32) -> Option<u32> {
-        let source_range = self.source..(self.source + self.length);
-        if source_range.contains(&value) {
+    fn source_range(&self) -> Range<u64> {
+        self.source..(self.source + self.length)
+    }
+
+    fn destination_range(&self) -> Range<u64> {
+        self.destination..(self.destination + self.length)
+    }
+
+    pub fn map(&self, value: u64) -> Option<u64> {
+        if self.source_range().contains(&value) {
             let offset = value - self.source;
             Some(self.destination + offset)
         } else {
             None
         }
     }
+
+    /// Split a range at the edges of the entry's source range. Returns the part inside it,
+    /// mapped to the destination, and the parts before and after it, left as they are.
+    pub fn map_range(&self, range: &Range<u64>) -> (Option<Range<u64>>, Vec<Range<u64>>) {
+        let source_range = self.source_range();
+        let mut unmapped = Vec::new();
+        if range.start < source_range.start {
+            unmapped.push(range.start..range.end.min(source_range.start));
+        }
+        if range.end > source_range.end {
+            unmapped.push(range.start.max(source_range.end)..range.end);
+        }
+
+        let start = range.start.max(source_range.start);
+        let end = range.end.min(source_range.end);
+        if start < end {
+            let mapped =
+                (start - self.source + self.destination)..(end - self.source + self.destination);
+            (Some(mapped), unmapped)
+        } else {
+            (None, vec![range.clone()])
+        }
+    }
 }
 
-#[derive(Debug)]
-struct Map {
+/// Map from one category to the next. Values that no entry covers map to themselves.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Map {
+    source: String,
+    destination: String,
     entries: Vec<MapEntry>,
 }
 
 impl Map {
-    pub fn from_file(file: &str) -> Map {
-        let contents = fs::read_to_string(file).expect("Unable to read file.");
+    pub fn lookup(&self, value: u64) -> u64 {
+        for entry in &self.entries {
+            if let Some(destination) = entry.map(value) {
+                return destination;
+            }
+        }
+        value
+    }
 
-        let entries: Vec<MapEntry> = contents.split('\n').map(MapEntry::from_str).collect();
+    /// Map a set of half-open ranges at once, splitting them where entries start and end
+    pub fn lookup_ranges(&self, ranges: Vec<Range<u64>>) -> Vec<Range<u64>> {
+        let mut mapped = Vec::new();
+        let mut unmapped = ranges;
+        for entry in &self.entries {
+            let mut remaining = Vec::new();
+            for range in unmapped.iter() {
+                let (inside, outside) = entry.map_range(range);
+                mapped.extend(inside);
+                remaining.extend(outside);
+            }
+            unmapped = remaining;
+        }
+        // Values that no entry covers map to themselves
+        mapped.extend(unmapped);
+        mapped
+    }
 
-        Map { entries }
+    /// Every value that maps to the given one. There can be more than one if an entry
+    /// maps onto a value that is also left as it is, or none if an entry maps it away.
+    pub fn reverse_lookup(&self, value: u64) -> Vec<u64> {
+        let mut sources: Vec<u64> = self
+            .entries
+            .iter()
+            .filter(|e| e.destination_range().contains(&value))
+            .map(|e| value - e.destination + e.source)
+            .collect();
+        if self.entries.iter().all(|e| e.map(value).is_none()) {
+            sources.push(value);
+        }
+        sources.sort();
+        sources
     }
 
-    pub fn lookup(&self, value: u32) -> u32 {
+    /// Single map that does the same as this map followed by `next`
+    pub fn compose(&self, next: &Map) -> Map {
+        let mut entries = Vec::new();
+
+        // Split what each entry maps to at the entries of the next map
         for entry in &self.entries {
-            if let Some(destination) = entry.map(value) {
-                return destination;
+            let mut unmapped = vec![entry.destination_range()];
+            for next_entry in &next.entries {
+                let mut remaining = Vec::new();
+                for range in unmapped.iter() {
+                    let (inside, outside) = next_entry.map_range(range);
+                    if let Some(inside) = inside {
+                        // Where the overlap starts, in the destination of this map
+                        let start = inside.start - next_entry.destination + next_entry.source;
+                        entries.push(MapEntry {
+                            length: inside.end - inside.start,
+                            destination: inside.start,
+                            source: start - entry.destination + entry.source,
+                        });
+                    }
+                    remaining.extend(outside);
+                }
+                unmapped = remaining;
+            }
+            for range in unmapped {
+                entries.push(MapEntry {
+                    length: range.end - range.start,
+                    destination: range.start,
+                    source: range.start - entry.destination + entry.source,
+                });
             }
         }
-        value
+
+        // Values this map leaves as they are go straight to the next map
+        for next_entry in &next.entries {
+            let mut uncovered = vec![next_entry.source_range()];
+            for entry in &self.entries {
+                uncovered = uncovered
+                    .iter()
+                    .flat_map(|range| entry.map_range(range).1)
+                    .collect();
+            }
+            for range in uncovered {
+                entries.push(MapEntry {
+                    length: range.end - range.start,
+                    destination: next_entry.map(range.start).unwrap(),
+                    source: range.start,
+                });
+            }
+        }
+
+        entries.sort_by_key(|e| e.source);
+        Map {
+            source: self.source.clone(),
+            destination: next.destination.clone(),
+            entries,
+        }
     }
 }
 
-fn read_seeds() -> Vec<u32> {
-    let contents = fs::read_to_string("src/day5/seeds.txt").expect("Unable to read seeds.");
-    let seeds: Vec<u32> = contents
-        .split(' ')
-        .map(|s| {
-            let value: u32 = s.parse().unwrap();
-            value
-        })
-        .collect();
-    seeds
+/// The seeds to plant, and the maps from each category to the next
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Almanac {
+    seeds: Vec<u64>,
+    /// Maps keyed by the category they map from
+    maps: HashMap<String, Map>,
 }
 
-pub fn solution1() {
-    // Read in maps
-    let fertilizer_to_water_map = Map::from_file("src/day5/fertilizer_to_water_map.txt");
-    let humidity_to_location_map = Map::from_file("src/day5/humidity_to_location_map.txt");
-    let light_to_temperature_map = Map::from_file("src/day5/light_to_temperature_map.txt");
-    let seed_to_soil_map = Map::from_file("src/day5/seed_to_soil_map.txt");
-    let soil_to_fertilizer_map = Map::from_file("src/day5/soil_to_fertilizer_map.txt");
-    let temperature_to_humidity_map = Map::from_file("src/day5/temperature_to_humidity_map.txt");
-    let water_to_light_map = Map::from_file("src/day5/water_to_light_map.txt");
-
-    // Read in seeds
-    let seeds = read_seeds();
-
-    // Map seeds
-    let mut locations: Vec<u32> = Vec::new();
-    for seed in seeds {
-        let soil = seed_to_soil_map.lookup(seed);
-        let fertilizer = soil_to_fertilizer_map.lookup(soil);
-        let water = fertilizer_to_water_map.lookup(fertilizer);
-        let light = water_to_light_map.lookup(water);
-        let temperature = light_to_temperature_map.lookup(light);
-        let humidity = temperature_to_humidity_map.lookup(temperature);
-        let location = humidity_to_location_map.lookup(humidity);
-        locations.push(location);
+impl Almanac {
+    /// Parse the puzzle input: a line of seeds, then one section per map, each starting
+    /// with an "x-to-y map:" header
+    pub fn from_string(s: &str) -> Result<Almanac, ParseError> {
+        let mut lines = s.lines().enumerate();
+
+        // Seeds come first
+        let (_, seeds_line) = lines.next().unwrap_or((0, ""));
+        let seeds_text = seeds_line
+            .strip_prefix("seeds:")
+            .ok_or_else(|| ParseError::new(DAY, 1, seeds_line, "expected 'seeds:'").on_line(1))?;
+        let seeds: Vec<u64> = parse::fields(seeds_text, ' ')
+            .filter(|(_, field)| !field.is_empty())
+            .map(|(column, field)| parse::number(DAY, column + "seeds:".len(), field))
+            .collect::<Result<_, _>>()
+            .map_err(|e| e.on_line(1))?;
+
+        // Then the maps, in any order
+        let mut maps: HashMap<String, Map> = HashMap::new();
+        let mut current: Option<Map> = None;
+        for (i, line) in lines {
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(header) = line.strip_suffix(" map:") {
+                let (source, destination) = header.split_once("-to-").ok_or_else(|| {
+                    ParseError::new(DAY, 1, header, "expected 'x-to-y map:'").on_line(i + 1)
+                })?;
+                if let Some(map) = current.take() {
+                    maps.insert(map.source.clone(), map);
+                }
+                current = Some(Map {
+                    source: source.to_string(),
+                    destination: destination.to_string(),
+                    entries: Vec::new(),
+                });
+            } else {
+                let entry = MapEntry::from_str(line).map_err(|e| e.on_line(i + 1))?;
+                let map = current.as_mut().ok_or_else(|| {
+                    ParseError::new(DAY, 1, line, "expected a map header").on_line(i + 1)
+                })?;
+                map.entries.push(entry);
+            }
+        }
+        if let Some(map) = current {
+            maps.insert(map.source.clone(), map);
+        }
+
+        Ok(Almanac { seeds, maps })
     }
 
+    /// The maps to follow from one category to another, found through the headers
+    pub fn chain(&self, from: &str, to: &str) -> Option<Vec<&Map>> {
+        let mut chain = Vec::new();
+        let mut category = from;
+        while category != to {
+            // A chain can't be longer than the number of maps, unless it loops
+            if chain.len() == self.maps.len() {
+                return None;
+            }
+            let map = self.maps.get(category)?;
+            chain.push(map);
+            category = &map.destination;
+        }
+        Some(chain)
+    }
+
+    /// Single map from one category to another
+    pub fn pipeline(&self, from: &str, to: &str) -> Option<Map> {
+        let identity = Map {
+            source: from.to_string(),
+            destination: from.to_string(),
+            entries: Vec::new(),
+        };
+        let chain = self.chain(from, to)?;
+        Some(chain.iter().fold(identity, |map, next| map.compose(next)))
+    }
+
+    /// Every seed that ends up at the given location, if the maps lead from one to the other
+    pub fn seeds_for_location(&self, location: u64) -> Option<Vec<u64>> {
+        let chain = self.chain("seed", "location")?;
+        let mut values = vec![location];
+        for map in chain.iter().rev() {
+            values = values.iter().flat_map(|v| map.reverse_lookup(*v)).collect();
+        }
+        values.sort();
+        values.dedup();
+        Some(values)
+    }
+}
+
+/// Single map from seeds to locations, or an error after the last line of the input if
+/// the maps don't lead from one to the other
+fn seed_to_location(almanac: &Almanac, input: &str) -> Result<Map, ParseError> {
+    almanac.pipeline("seed", "location").ok_or_else(|| {
+        let reason = "expected maps leading from seed to location";
+        ParseError::new(DAY, 1, "", reason).on_line(input.lines().count() + 1)
+    })
+}
+
+/// Seeds read in pairs of range start and length, with errors pointing at the seeds line
+fn seed_ranges(almanac: &Almanac, input: &str) -> Result<Vec<Range<u64>>, ParseError> {
+    // The seeds line has already been parsed, so its fields line up with the seeds
+    let seeds_line = input.lines().next().unwrap_or_default();
+    let fields: Vec<(usize, &str)> = parse::fields(seeds_line, ' ')
+        .filter(|(_, field)| !field.is_empty())
+        .skip(1)
+        .collect();
+    let error = |i: usize, reason: &str| {
+        let (column, text) = fields[i];
+        ParseError::new(DAY, column, text, reason).on_line(1)
+    };
+    if almanac.seeds.len() % 2 == 1 {
+        return Err(error(
+            fields.len() - 1,
+            "expected a range length after each start",
+        ));
+    }
+    (0..almanac.seeds.len())
+        .step_by(2)
+        .map(|i| {
+            let (start, length) = (almanac.seeds[i], almanac.seeds[i + 1]);
+            let end = start
+                .checked_add(length)
+                .ok_or_else(|| error(i + 1, "expected a range that ends below 2^64"))?;
+            Ok(start..end)
+        })
+        .collect()
+}
+
+pub fn solution1(input: &str) -> Solution {
+    let almanac = Almanac::from_string(input)?;
+    bench::parsed();
+    let map = seed_to_location(&almanac, input)?;
+
     // Lowest location
-    let lowest_location = locations.into_iter().min().unwrap();
-    println!("Lowest location: {}", lowest_location);
+    let lowest_location = almanac
+        .seeds
+        .iter()
+        .map(|s| map.lookup(*s))
+        .min()
+        .ok_or_else(|| SolveError::unsolved(DAY, "there are no seeds"))?;
+    Ok(lowest_location.into())
 }
 
-pub fn solution2() {
-    // Read in maps
-    let fertilizer_to_water_map = Map::from_file("src/day5/fertilizer_to_water_map.txt");
-    let humidity_to_location_map = Map::from_file("src/day5/humidity_to_location_map.txt");
-    let light_to_temperature_map = Map::from_file("src/day5/light_to_temperature_map.txt");
-    let seed_to_soil_map = Map::from_file("src/day5/seed_to_soil_map.txt");
-    let soil_to_fertilizer_map = Map::from_file("src/day5/soil_to_fertilizer_map.txt");
-    let temperature_to_humidity_map = Map::from_file("src/day5/temperature_to_humidity_map.txt");
-    let water_to_light_map = Map::from_file("src/day5/water_to_light_map.txt");
-
-    // Read in seeds
-    let seed_ranges = read_seeds();
-    let mut seed_starts: Vec<u32> = Vec::new();
-    let mut range_lengths: Vec<u32> = Vec::new();
-    for (index, value) in seed_ranges.iter().enumerate() {
-        if index % 2 == 0 {
-            seed_starts.push(*value);
-        } else {
-            range_lengths.push(*value);
-        }
-    }
-    let seed_ranges = seed_starts.iter().zip(range_lengths.iter());
-
-    // Map seeds
-    println!("Ah, here we go...");
-    let mut lowest_location = u32::MAX;
-    for (seed_start, seed_range) in seed_ranges {
-        println!("Seed start: {}, Seed range: {}", seed_start, seed_range);
-        for i in 0..*seed_range {
-            let soil = seed_to_soil_map.lookup(seed_start + i);
-            let fertilizer = soil_to_fertilizer_map.lookup(soil);
-            let water = fertilizer_to_water_map.lookup(fertilizer);
-            let light = water_to_light_map.lookup(water);
-            let temperature = light_to_temperature_map.lookup(light);
-            let humidity = temperature_to_humidity_map.lookup(temperature);
-            let location = humidity_to_location_map.lookup(humidity);
-            if location < lowest_location {
-                lowest_location = location;
-            }
+pub fn solution2(input: &str) -> Solution {
+    let almanac = Almanac::from_string(input)?;
+    bench::parsed();
+    let map = seed_to_location(&almanac, input)?;
+
+    let ranges = seed_ranges(&almanac, input)?;
+
+    // Lowest location is the start of one of the location ranges
+    let lowest_location = map
+        .lookup_ranges(ranges)
+        .iter()
+        .filter(|r| !r.is_empty())
+        .map(|r| r.start)
+        .min()
+        .ok_or_else(|| SolveError::unsolved(DAY, "there are no seeds in the ranges"))?;
+    Ok(lowest_location.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::answer::Answer;
+
+    const EXAMPLE: &str = "\
+seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15
+
+fertilizer-to-water map:
+49 53 8
+0 11 42
+42 0 7
+57 7 4
+
+water-to-light map:
+88 18 7
+18 25 70
+
+light-to-temperature map:
+45 77 23
+81 45 19
+68 64 13
+
+temperature-to-humidity map:
+0 69 1
+1 0 69
+
+humidity-to-location map:
+60 56 37
+56 93 4";
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(solution1(EXAMPLE), Ok(Answer::Int(35)));
+    }
+
+    #[test]
+    fn example_part2() {
+        assert_eq!(solution2(EXAMPLE), Ok(Answer::Int(46)));
+    }
+
+    fn parse_map(section: &str) -> Map {
+        let almanac = Almanac::from_string(&format!("seeds: 1\n\n{section}")).unwrap();
+        almanac.maps.into_values().next().unwrap()
+    }
+
+    #[test]
+    fn lookup_ranges() {
+        let map = parse_map("seed-to-soil map:\n50 98 2\n52 50 48");
+        let mut ranges = map.lookup_ranges(vec![40..60, 95..110]);
+        ranges.sort_by_key(|r| r.start);
+        assert_eq!(ranges, vec![40..50, 50..52, 52..62, 97..100, 100..110]);
+        // Values near the top of the range don't overflow
+        let map = parse_map("a-to-b map:\n4294967296 4294967295 2");
+        assert_eq!(map.lookup(4294967296), 4294967297);
+    }
+
+    #[test]
+    fn composition() {
+        let almanac = Almanac::from_string(EXAMPLE).unwrap();
+        let chain = almanac.chain("seed", "location").unwrap();
+        assert_eq!(chain.len(), 7);
+        assert_eq!(chain[3].source, "water");
+        assert!(almanac.chain("location", "seed").is_none());
+
+        let composed = almanac.pipeline("seed", "location").unwrap();
+        for seed in 0..120 {
+            let location = chain.iter().fold(seed, |value, map| map.lookup(value));
+            assert_eq!(composed.lookup(seed), location);
         }
     }
-    println!("Lowest location: {}", lowest_location);
+
+    #[test]
+    fn reverse_lookup() {
+        let almanac = Almanac::from_string(EXAMPLE).unwrap();
+        assert_eq!(almanac.seeds_for_location(35), Some(vec![13]));
+        assert_eq!(almanac.seeds_for_location(46), Some(vec![82]));
+    }
+
+    #[test]
+    fn malformed_input() {
+        let error = Almanac::from_string("seeds: 79 x").unwrap_err();
+        assert_eq!(
+            (error.line, error.column, error.text.as_str()),
+            (1, 11, "x")
+        );
+        let error = Almanac::from_string("seeds: 79\n\n50 98 2").unwrap_err();
+        assert_eq!(
+            (error.line, error.reason.as_str()),
+            (3, "expected a map header")
+        );
+        let error = Almanac::from_string("seeds: 79\n\na-to-b map:\n50 98").unwrap_err();
+        assert_eq!(error.line, 4);
+
+        // The maps have to lead all the way from seed to location
+        let error = solution1("seeds: 79\n\nseed-to-soil map:\n50 98 2").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!(
+            (error.line, error.reason.as_str()),
+            (5, "expected maps leading from seed to location")
+        );
+        assert_eq!(
+            solution1("seeds:\n\nseed-to-location map:\n50 98 2"),
+            Err(SolveError::unsolved(DAY, "there are no seeds"))
+        );
+
+        // Part 2 reads the seeds in pairs
+        let maps = "\n\nseed-to-location map:\n50 98 2";
+        let error = solution2(&format!("seeds: 79 14  55{maps}")).unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!(
+            (error.line, error.column, error.text.as_str()),
+            (1, 15, "55")
+        );
+        let error = solution2(&format!("seeds: 79 18446744073709551615{maps}")).unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!(
+            (error.column, error.text.as_str()),
+            (11, "18446744073709551615")
+        );
+        assert_eq!(
+            solution2(&format!("seeds: 79 0{maps}")),
+            Err(SolveError::unsolved(
+                DAY,
+                "there are no seeds in the ranges"
+            ))
+        );
+    }
 }
@@ -1,5 +1,8 @@
-use regex::Regex;
-use std::fs;
+use crate::answer::Solution;
+use crate::bench;
+use crate::parse::{self, ParseError};
+
+const DAY: u8 = 6;
 
 #[derive(Debug)]
 struct Race {
@@ -8,41 +11,65 @@ struct Race {
 }
 
 impl Race {
-    fn calculate_ways_to_win(&self) -> f64 {
+    fn calculate_ways_to_win(&self) -> i64 {
+        let wins = |hold: i64| hold * (self.time - hold) > self.distance;
         let t = self.time as f64;
         let d = self.distance as f64;
-        let lower = ((-t + (t.powi(2) - 4. * d).sqrt()) / -2.).ceil();
-        let upper = ((-t - (t.powi(2) - 4. * d).sqrt()) / -2.).floor();
-        upper - lower + 1.
+        // Estimate the lower root of h * (t - h) = d, then correct it in whole steps. The
+        // square root loses precision on long races, and holding for exactly a root only
+        // ties the record, so neither rounding it up nor down can be trusted.
+        let mut lower = ((t - (t.powi(2) - 4. * d).max(0.).sqrt()) / 2.).floor() as i64;
+        while lower > 0 && wins(lower - 1) {
+            lower -= 1;
+        }
+        while lower <= self.time / 2 && !wins(lower) {
+            lower += 1;
+        }
+        if lower > self.time / 2 {
+            return 0;
+        }
+        // The winning hold times are symmetric around t / 2
+        let upper = self.time - lower;
+        upper - lower + 1
     }
 }
 
-fn read_data(path: &str) -> Vec<Race> {
-    // Read file
-    let contents = fs::read_to_string(path).unwrap();
-    let lines: Vec<&str> = contents.split('\n').collect();
-
-    // Read times
-    let time_regex = Regex::new(r"(\d+)").unwrap();
-    let times: Vec<i64> = time_regex
-        .captures_iter(lines[0])
-        .map(|c| {
-            let time_str = c.get(0).unwrap().as_str();
-            let time: i64 = time_str.parse().unwrap();
-            time
-        })
+/// The numbers after `label` on the given line of the input. With `kerning`, they are
+/// run together into a single number.
+fn read_numbers(
+    input: &str,
+    line: usize,
+    label: &str,
+    kerning: bool,
+) -> Result<Vec<i64>, ParseError> {
+    let text = input.lines().nth(line - 1).unwrap_or_default();
+    let numbers = text.strip_prefix(label).ok_or_else(|| {
+        ParseError::new(DAY, 1, text, format!("expected '{label}'")).on_line(line)
+    })?;
+    let mut fields: Vec<(usize, String)> = parse::fields(numbers, ' ')
+        .filter(|(_, field)| !field.is_empty())
+        .map(|(column, field)| (column + label.len(), field.to_string()))
         .collect();
+    if kerning && !fields.is_empty() {
+        let digits: String = fields.iter().map(|(_, field)| field.as_str()).collect();
+        fields = vec![(fields[0].0, digits)];
+    }
+    fields
+        .iter()
+        .map(|(column, field)| parse::number(DAY, *column, field))
+        .collect::<Result<Vec<i64>, ParseError>>()
+        .map_err(|e| e.on_line(line))
+}
 
-    // Read distances
-    let dist_regex = Regex::new(r"(\d+)").unwrap();
-    let dists: Vec<i64> = dist_regex
-        .captures_iter(lines[1])
-        .map(|c| {
-            let dist_str = c.get(0).unwrap().as_str();
-            let dist: i64 = dist_str.parse().unwrap();
-            dist
-        })
-        .collect();
+/// Read the races, or with `kerning` the single race written with spaces in its numbers
+fn read_data(input: &str, kerning: bool) -> Result<Vec<Race>, ParseError> {
+    let times = read_numbers(input, 1, "Time:", kerning)?;
+    let dists = read_numbers(input, 2, "Distance:", kerning)?;
+    if times.len() != dists.len() {
+        let reason = format!("expected {} distances", times.len());
+        let text = input.lines().nth(1).unwrap_or_default();
+        return Err(ParseError::new(DAY, 1, text, reason).on_line(2));
+    }
 
     // Zip vectors
     let mut races: Vec<Race> = Vec::new();
@@ -52,23 +79,89 @@ fn read_data(path: &str) -> Vec<Race> {
             distance: *d,
         });
     }
-    races
+    Ok(races)
 }
 
-pub fn solution1() {
-    let races = read_data("src/day6/input.txt");
-    let score: f64 = races
+pub fn solution1(input: &str) -> Solution {
+    let races = read_data(input, false)?;
+    bench::parsed();
+    let score: i64 = races
         .iter()
         .map(|race| race.calculate_ways_to_win())
         .product();
-    println!("{:?}", score);
+    Ok(score.into())
 }
 
-pub fn solution2() {
-    let races = read_data("src/day6/input2.txt");
-    let score: f64 = races
+pub fn solution2(input: &str) -> Solution {
+    // Ignore the spaces between numbers; each line holds a single race
+    let races = read_data(input, true)?;
+    bench::parsed();
+    let score: i64 = races
         .iter()
         .map(|race| race.calculate_ways_to_win())
         .product();
-    println!("{:?}", score);
+    Ok(score.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::answer::Answer;
+
+    const EXAMPLE: &str = "\
+Time:      7  15   30
+Distance:  9  40  200";
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(solution1(EXAMPLE), Ok(Answer::Int(288)));
+    }
+
+    #[test]
+    fn example_part2() {
+        assert_eq!(solution2(EXAMPLE), Ok(Answer::Int(71503)));
+    }
+
+    #[test]
+    fn malformed_input() {
+        let error = solution1("Time: 7 15\nDistance: 9 4x").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!(
+            (error.line, error.column, error.text.as_str()),
+            (2, 13, "4x")
+        );
+        let error = solution2("Time: 7 1.5\nDistance: 9 40").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!(
+            (error.line, error.column, error.text.as_str()),
+            (1, 7, "71.5")
+        );
+        let error = solution1("Time: 7 15\nDistance: 9").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!(
+            (error.line, error.reason.as_str()),
+            (2, "expected 2 distances")
+        );
+        let error = solution1("Time: 7").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!(
+            (error.line, error.reason.as_str()),
+            (2, "expected 'Distance:'")
+        );
+    }
+
+    #[test]
+    fn ways_to_win() {
+        // Holding for 10 or 20 ms only ties the record
+        let race = Race {
+            time: 30,
+            distance: 200,
+        };
+        assert_eq!(race.calculate_ways_to_win(), 9);
+        let race = Race {
+            time: 4,
+            distance: 10,
+        };
+        assert_eq!(race.calculate_ways_to_win(), 0);
+    }
 }
@@ -1,7 +1,13 @@
-use aocd::*;
-use regex::Regex;
+use crate::answer::Solution;
+use crate::bench;
+use crate::parse::{self, ParseError};
 use std::{collections::HashMap, str::FromStr};
 
+const DAY: u8 = 7;
+
+/// Card ranks, from weakest to strongest, with jokers being the weakest
+const CARDS: &str = "J23456789TQKA";
+
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
 pub enum HandType {
     HighCard,
@@ -13,13 +19,23 @@ pub enum HandType {
     FiveOfAKind,
 }
 
-#[derive(Debug, PartialEq, Eq)]
-pub struct ParseHandError;
-
 impl FromStr for HandType {
-    type Err = ParseHandError;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Check that the hand is made of five known cards
+        if let Some((i, c)) = s.chars().enumerate().find(|(_, c)| !CARDS.contains(*c)) {
+            return Err(ParseError::new(
+                DAY,
+                i + 1,
+                &c.to_string(),
+                "expected a card",
+            ));
+        }
+        if s.chars().count() != 5 {
+            return Err(ParseError::new(DAY, 1, s, "expected a hand of five cards"));
+        }
+
         // Convert string to count of cards
         let mut hand_map: HashMap<char, usize> =
             s.chars().fold(HashMap::<char, usize>::new(), |mut m, x| {
@@ -28,11 +44,7 @@ impl FromStr for HandType {
             });
 
         // Get 'J' counts
-        let joker_count = if let Some(n) = hand_map.remove(&'J') {
-            n
-        } else {
-            0
-        };
+        let joker_count = hand_map.remove(&'J').unwrap_or_default();
 
         // Exception: Hand is all J's
         if hand_map.is_empty() {
@@ -75,24 +87,23 @@ struct Play {
     bid: u32,
 }
 
-#[derive(Debug, PartialEq, Eq)]
-pub struct ParsePlayError;
-
 impl FromStr for Play {
-    type Err = ParsePlayError;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let hand_regex = Regex::new(r"^(.{5}) ").unwrap();
-        let hand = hand_regex.captures(s).unwrap()[1].to_string();
-        let bid_regex = Regex::new(r" (\d*)$").unwrap();
-        let bid: u32 = bid_regex.captures(s).unwrap()[1]
-            .to_string()
-            .parse()
-            .unwrap();
+        let fields: Vec<(usize, &str)> = parse::fields(s, ' ').collect();
+        let (hand, (bid_column, bid)) = match fields[..] {
+            [(_, hand), bid] => (hand, bid),
+            [_] => {
+                let end = s.chars().count() + 1;
+                return Err(ParseError::new(DAY, end, "", "expected a bid"));
+            }
+            _ => return Err(ParseError::new(DAY, 1, s, "expected a hand and a bid")),
+        };
         Ok(Play {
-            hand_type: HandType::from_str(hand.as_str()).unwrap(),
-            hand,
-            bid,
+            hand_type: HandType::from_str(hand)?,
+            hand: hand.to_string(),
+            bid: parse::number(DAY, bid_column, bid)?,
         })
     }
 }
@@ -131,13 +142,10 @@ impl Play {
     }
 }
 
-#[aocd(2023, 7)]
-pub fn solution2() {
+pub fn solution2(input: &str) -> Solution {
     // Get hands
-    let mut hands: Vec<Play> = input!()
-        .split('\n')
-        .map(|s| Play::from_str(s).unwrap())
-        .collect();
+    let mut hands: Vec<Play> = parse::lines(input, Play::from_str)?;
+    bench::parsed();
 
     // Sort
     hands.sort_by_key(|hand| hand.encode_hand());
@@ -149,7 +157,7 @@ pub fn solution2() {
         .map(|(i, hand)| (i as u32 + 1) * hand.bid)
         .sum();
 
-    submit!(2, total);
+    Ok(total.into())
 }
 
 /* Part 1 Solution
@@ -282,3 +290,43 @@ pub fn solution1() {
     submit!(1, total);
 }
 */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::answer::Answer;
+
+    const EXAMPLE: &str = "\
+32T3K 765
+T55J5 684
+KK677 28
+KTJJT 220
+QQQJA 483";
+
+    #[test]
+    fn example_part2() {
+        assert_eq!(solution2(EXAMPLE), Ok(Answer::Int(5905)));
+    }
+
+    #[test]
+    fn malformed_input() {
+        let error = solution2("32T3K 765\nT55X5 684")
+            .unwrap_err()
+            .into_parse_error()
+            .unwrap();
+        assert_eq!((error.line, error.column, error.text.as_str()), (2, 4, "X"));
+        let error = solution2("32T3K 765\nKK677 28x")
+            .unwrap_err()
+            .into_parse_error()
+            .unwrap();
+        assert_eq!(
+            (error.line, error.column, error.text.as_str()),
+            (2, 7, "28x")
+        );
+        let error = solution2("32T3K 765\nKK677")
+            .unwrap_err()
+            .into_parse_error()
+            .unwrap();
+        assert_eq!((error.line, error.column), (2, 6));
+    }
+}
@@ -1,133 +1,479 @@
-use aocd::*;
-use regex::Regex;
+use crate::answer::{Solution, SolveError};
+use crate::bench;
+use crate::math;
+use crate::parse::ParseError;
+use serde::Serialize;
 use std::collections::HashMap;
 
-#[aocd(2023, 8)]
-pub fn solution1() {
-    let mut lines: Vec<String> = input!().split('\n').map(|s| s.to_string()).collect();
+const DAY: u8 = 8;
 
-    // Get commands
-    let commands = lines[0].clone();
+/// The nodes of the map, with names interned into indices and a table of the left and
+/// right successor of every node
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Network {
+    names: Vec<String>,
+    indices: HashMap<String, usize>,
+    successors: Vec<[usize; 2]>,
+}
 
-    // Remove first two lines
-    lines.remove(0);
-    lines.remove(0);
+/// A node as it appears in the JSON export
+#[derive(Serialize)]
+struct NodeEntry<'a> {
+    name: &'a str,
+    left: &'a str,
+    right: &'a str,
+}
 
-    // Get nodes
-    let nodes: HashMap<String, (String, String)> = lines
-        .iter()
-        .map(|s| {
-            let id_regex = Regex::new(r"(\w{3}) =").unwrap();
-            let left_regex = Regex::new(r"= \((\w{3})").unwrap();
-            let right_regex = Regex::new(r", (\w{3})").unwrap();
+impl Network {
+    /// Parse lines of the form `AAA = (BBB, CCC)`. Every node that is stepped to must
+    /// have a line of its own.
+    pub fn from_string(s: &str) -> Result<Network, ParseError> {
+        let mut network = Network::default();
+        let mut defined = Vec::new();
+        let mut referenced = Vec::new();
+        for (i, line) in s.lines().enumerate() {
+            let (node, successors) = parse_node(line).map_err(|e| e.on_line(i + 1))?;
+            let node = network.intern(node);
+            let successors = successors.map(|(column, name)| (column, network.intern(name)));
+            network.successors[node] = successors.map(|(_, successor)| successor);
+            defined.push(node);
+            referenced.push((i + 1, successors));
+        }
 
-            let id = id_regex.captures(s.as_str()).unwrap()[1].to_string();
-            let left = left_regex.captures(s.as_str()).unwrap()[1].to_string();
-            let right = right_regex.captures(s.as_str()).unwrap()[1].to_string();
+        // Point at the first successor without a line of its own
+        let mut is_defined = vec![false; network.len()];
+        for node in defined {
+            is_defined[node] = true;
+        }
+        for (line, successors) in referenced {
+            if let Some((column, successor)) = successors.into_iter().find(|(_, n)| !is_defined[*n])
+            {
+                let name = network.name(successor);
+                return Err(
+                    ParseError::new(DAY, column, name, "expected a known node").on_line(line)
+                );
+            }
+        }
+        Ok(network)
+    }
 
-            (id, left, right)
-        })
-        .fold(
-            HashMap::<String, (String, String)>::new(),
-            |mut m, (id, left, right)| {
-                m.insert(id, (left, right));
-                m
-            },
-        );
+    /// Index of a node, adding it if it hasn't been seen yet
+    fn intern(&mut self, name: &str) -> usize {
+        if let Some(&index) = self.indices.get(name) {
+            return index;
+        }
+        let index = self.names.len();
+        self.names.push(name.to_string());
+        self.indices.insert(name.to_string(), index);
+        self.successors.push([index, index]);
+        index
+    }
 
-    // Navigate to ZZZ and count steps
-    let mut steps: u64 = 0;
-    let mut current_node: String = String::from("AAA");
-    'outer: loop {
-        for command in commands.chars() {
-            if current_node == "ZZZ" {
-                println!("found!");
-                break 'outer;
-            }
-            if command == 'L' {
-                steps += 1;
-                current_node = nodes.get(&current_node).unwrap().0.clone();
-            } else if command == 'R' {
-                steps += 1;
-                current_node = nodes.get(&current_node).unwrap().1.clone();
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.indices.get(name).copied()
+    }
+
+    pub fn name(&self, node: usize) -> &str {
+        &self.names[node]
+    }
+
+    /// Node reached from `node` by going left (0) or right (1)
+    pub fn next(&self, node: usize, direction: usize) -> usize {
+        self.successors[node][direction]
+    }
+
+    /// Nodes whose names end in the given letter
+    pub fn ending_in(&self, letter: char) -> impl Iterator<Item = usize> + '_ {
+        (0..self.len()).filter(move |&node| self.names[node].ends_with(letter))
+    }
+
+    /// Graphviz rendering of the network. Starting nodes are boxes, and nodes ending in
+    /// Z are double circles.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph network {\n");
+        for (node, name) in self.names.iter().enumerate() {
+            if name.ends_with('A') {
+                dot += &format!("    \"{name}\" [shape=box];\n");
+            } else if name.ends_with('Z') {
+                dot += &format!("    \"{name}\" [shape=doublecircle];\n");
             }
+            let [left, right] = self.successors[node].map(|n| self.name(n));
+            dot += &format!("    \"{name}\" -> \"{left}\" [label=\"L\"];\n");
+            dot += &format!("    \"{name}\" -> \"{right}\" [label=\"R\"];\n");
         }
+        dot + "}\n"
     }
 
-    submit!(1, steps);
+    /// JSON list of the nodes with their left and right successors
+    pub fn to_json(&self) -> String {
+        let nodes: Vec<NodeEntry> = (0..self.len())
+            .map(|node| NodeEntry {
+                name: self.name(node),
+                left: self.name(self.next(node, 0)),
+                right: self.name(self.next(node, 1)),
+            })
+            .collect();
+        serde_json::to_string_pretty(&nodes).unwrap()
+    }
 }
 
-#[aocd(2023, 8)]
-pub fn solution2() {
-    let mut lines: Vec<String> = input!().split('\n').map(|s| s.to_string()).collect();
+/// A node name, with the column it starts at
+type Field<'a> = (usize, &'a str);
 
-    // Get commands
-    let commands: Vec<usize> = lines[0]
-        .chars()
-        .map(|s| if s == 'L' { 0 } else { 1 })
-        .collect();
+/// Name of the node of a line, and the columns and names of its successors
+fn parse_node(line: &str) -> Result<(&str, [Field<'_>; 2]), ParseError> {
+    let (node, successors) = line
+        .split_once(" = ")
+        .ok_or_else(|| ParseError::new(DAY, 1, line, "expected 'AAA = (BBB, CCC)'"))?;
+    let offset = node.len() + " = ".len();
+    let inner = successors
+        .strip_prefix('(')
+        .and_then(|s| s.strip_suffix(')'))
+        .ok_or_else(|| ParseError::new(DAY, offset + 1, successors, "expected '(BBB, CCC)'"))?;
+    let (left, right) = inner
+        .split_once(", ")
+        .ok_or_else(|| ParseError::new(DAY, offset + 2, inner, "expected a left and right node"))?;
+    let left_column = offset + 2;
+    let right_column = left_column + left.len() + ", ".len();
+    Ok((node, [(left_column, left), (right_column, right)]))
+}
 
-    println!("Command length: {}", commands.len());
+/// Instructions as indices into the successor table: 0 for left and 1 for right. There
+/// has to be at least one, since they are repeated forever.
+fn parse_instructions(line: &str) -> Result<Vec<usize>, ParseError> {
+    if line.is_empty() {
+        return Err(ParseError::new(DAY, 1, "", "expected 'L' or 'R'"));
+    }
+    line.chars()
+        .enumerate()
+        .map(|(i, c)| match c {
+            'L' => Ok(0),
+            'R' => Ok(1),
+            _ => Err(ParseError::new(
+                DAY,
+                i + 1,
+                &c.to_string(),
+                "expected 'L' or 'R'",
+            )),
+        })
+        .collect()
+}
 
-    // Remove first two lines
-    lines.remove(0);
-    lines.remove(0);
+/// The instructions and the network, separated by an empty line
+fn parse_map(input: &str) -> Result<(Vec<usize>, Network), ParseError> {
+    let (instructions, nodes) = input.split_once("\n\n").ok_or_else(|| {
+        let line = input.lines().count() + 1;
+        ParseError::new(DAY, 1, "", "expected an empty line and a network").on_line(line)
+    })?;
+    let instructions = parse_instructions(instructions).map_err(|e| e.on_line(1))?;
+    let network = Network::from_string(nodes).map_err(|e| ParseError {
+        line: e.line + 2,
+        ..e
+    })?;
+    Ok((instructions, network))
+}
 
-    // Get nodes
-    let nodes: HashMap<String, [String; 2]> = lines
-        .iter()
-        .map(|s| {
-            let id_regex = Regex::new(r"(\w{3}) =").unwrap();
-            let left_regex = Regex::new(r"= \((\w{3})").unwrap();
-            let right_regex = Regex::new(r", (\w{3})").unwrap();
+pub fn solution1(input: &str) -> Solution {
+    let (instructions, network) = parse_map(input)?;
+    bench::parsed();
 
-            let id = &id_regex.captures(s).unwrap()[1];
-            let left = &left_regex.captures(s).unwrap()[1];
-            let right = &right_regex.captures(s).unwrap()[1];
+    // Navigate to ZZZ and count steps
+    let node_named = |name: &str| {
+        network
+            .index_of(name)
+            .ok_or_else(|| SolveError::unsolved(DAY, format!("there is no node {name}")))
+    };
+    let end = node_named("ZZZ")?;
+    let mut node = node_named("AAA")?;
+    // Past this many steps some node and instruction have come up twice, so the walk
+    // repeats forever without reaching ZZZ
+    let limit = (network.len() * instructions.len()) as u64;
+    let mut steps: u64 = 0;
+    for &direction in instructions.iter().cycle() {
+        if node == end {
+            break;
+        }
+        if steps == limit {
+            return Err(SolveError::unsolved(DAY, "ZZZ can't be reached from AAA"));
+        }
+        node = network.next(node, direction);
+        steps += 1;
+    }
 
-            (id.to_string(), left.to_string(), right.to_string())
-        })
-        .fold(
-            HashMap::<String, [String; 2]>::new(),
-            |mut m, (id, left, right)| {
-                m.insert(id, [left, right]);
-                m
-            },
-        );
+    Ok(steps.into())
+}
+
+pub fn solution2(input: &str) -> Solution {
+    let (instructions, network) = parse_map(input)?;
+    bench::parsed();
+
+    let cycles: Vec<GhostCycle> = network
+        .ending_in('A')
+        .map(|start| GhostCycle::find(&network, &instructions, start))
+        .collect();
+    if cycles.is_empty() {
+        return Err(SolveError::unsolved(DAY, "there are no nodes ending in A"));
+    }
 
-    // Get A nodes
-    let mut current_nodes: Vec<String> =
-        nodes.keys().filter(|s| s.ends_with('A')).cloned().collect();
+    let steps = first_common_z(&cycles).ok_or(SolveError::unsolved(
+        DAY,
+        "the ghosts are never all on a Z node at once",
+    ))?;
+    Ok(steps.into())
+}
 
-    // List of first occurrence of a Z node and cycle duration until next one
-    let mut z_data: Vec<u64> = Vec::new();
+/// Where one ghost stands on a `..Z` node. Its state is its node along with the index of
+/// the next instruction, so once a state repeats the ghost walks the same cycle forever.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GhostCycle {
+    /// Step at which the ghost first enters its cycle
+    pub entry: u64,
+    /// Number of steps around the cycle
+    pub length: u64,
+    /// Steps before the cycle is entered at which the ghost is on a Z node
+    pub lead_in: Vec<u64>,
+    /// Steps within the first time around the cycle at which the ghost is on a Z node
+    pub offsets: Vec<u64>,
+}
 
-    // Navigate the map
-    for node in current_nodes.iter_mut() {
+impl GhostCycle {
+    fn find(network: &Network, instructions: &[usize], start: usize) -> GhostCycle {
+        let mut seen: HashMap<(usize, usize), u64> = HashMap::new();
+        let mut z_steps = Vec::new();
+        let mut node = start;
         let mut steps: u64 = 0;
-        'outer: loop {
-            for command in commands.iter() {
-                steps += 1;
-                *node = nodes.get(node).unwrap()[*command].clone();
-                if node.ends_with('Z') {
-                    z_data.push(steps);
-                    break 'outer;
-                }
+        loop {
+            let instruction = steps as usize % instructions.len();
+            if let Some(&entry) = seen.get(&(node, instruction)) {
+                let (lead_in, offsets) = z_steps.iter().partition(|&&z| z < entry);
+                return GhostCycle {
+                    entry,
+                    length: steps - entry,
+                    lead_in,
+                    offsets,
+                };
+            }
+            seen.insert((node, instruction), steps);
+            if network.name(node).ends_with('Z') {
+                z_steps.push(steps);
             }
+            node = network.next(node, instructions[instruction]);
+            steps += 1;
         }
     }
 
-    // Find the least common multiple
-    let mut fct = 1;
-    let largest = *z_data.iter().max().unwrap();
-    'lcm: loop {
-        let all_divisible = z_data.iter().all(|z| (fct * largest) % z == 0);
-        if all_divisible {
-            break 'lcm;
+    /// Whether the ghost is on a Z node after the given number of steps
+    fn is_on_z(&self, steps: u64) -> bool {
+        if steps < self.entry {
+            self.lead_in.contains(&steps)
         } else {
-            fct += 1;
+            let step_in_cycle = self.entry + (steps - self.entry) % self.length;
+            self.offsets.contains(&step_in_cycle)
         }
     }
+}
+
+/// Fewest steps after which every ghost is on a Z node at once, if that ever happens.
+/// Before all ghosts are in their cycles, the steps at which the first ghost is on a Z
+/// node are checked one by one. After that, each choice of a Z offset per ghost is a
+/// system of congruences, solved with the Chinese remainder theorem.
+pub fn first_common_z(cycles: &[GhostCycle]) -> Option<u64> {
+    let all_on_z = |steps: u64| cycles.iter().all(|c| c.is_on_z(steps));
+    let settled = cycles.iter().map(|c| c.entry).max()?;
+
+    // Before every ghost is in its cycle
+    let first = &cycles[0];
+    let early = (0..settled)
+        .filter(|&steps| first.is_on_z(steps))
+        .find(|&steps| all_on_z(steps));
+    if early.is_some() {
+        return early;
+    }
+
+    // Every combination of Z offsets, one from each ghost
+    let mut combinations: Vec<Vec<(i128, i128)>> = vec![Vec::new()];
+    for cycle in cycles {
+        combinations = combinations
+            .into_iter()
+            .flat_map(|combination| {
+                cycle.offsets.iter().map(move |&offset| {
+                    let mut combination = combination.clone();
+                    combination.push((offset as i128, cycle.length as i128));
+                    combination
+                })
+            })
+            .collect();
+    }
+    combinations
+        .into_iter()
+        .filter_map(math::crt)
+        .map(|(residue, modulus)| {
+            // Smallest step from `settled` on with the right remainder
+            let settled = settled as i128;
+            let behind = (residue - settled).rem_euclid(modulus);
+            (settled + behind) as u64
+        })
+        .min()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::answer::Answer;
+
+    const EXAMPLE_1: &str = "\
+RL
+
+AAA = (BBB, CCC)
+BBB = (DDD, EEE)
+CCC = (ZZZ, GGG)
+DDD = (DDD, DDD)
+EEE = (EEE, EEE)
+GGG = (GGG, GGG)
+ZZZ = (ZZZ, ZZZ)";
+
+    const EXAMPLE_2: &str = "\
+LLR
+
+AAA = (BBB, BBB)
+BBB = (AAA, ZZZ)
+ZZZ = (ZZZ, ZZZ)";
+
+    const EXAMPLE_3: &str = "\
+LR
+
+11A = (11B, XXX)
+11B = (XXX, 11Z)
+11Z = (11B, XXX)
+22A = (22B, XXX)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+22Z = (22B, 22B)
+XXX = (XXX, XXX)";
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(solution1(EXAMPLE_1), Ok(Answer::Int(2)));
+    }
 
-    submit!(2, fct * largest);
+    #[test]
+    fn example_part1_repeated() {
+        assert_eq!(solution1(EXAMPLE_2), Ok(Answer::Int(6)));
+    }
+
+    #[test]
+    fn example_part2() {
+        assert_eq!(solution2(EXAMPLE_3), Ok(Answer::Int(6)));
+    }
+
+    #[test]
+    fn offset_cycles() {
+        // The first ghost is on a Z node every 3 steps from step 2, the second on every
+        // odd step, and the third on every even step from step 2
+        let network = "\
+L
+
+11A = (11B, 11B)
+11B = (11Z, 11Z)
+11Z = (11C, 11C)
+11C = (11B, 11B)
+22A = (22Z, 22Z)
+22Z = (22B, 22B)
+22B = (22Z, 22Z)
+33A = (33B, 33B)
+33B = (33Z, 33Z)
+33Z = (33B, 33B)";
+        let without_third: String = network
+            .lines()
+            .filter(|line| !line.starts_with("33"))
+            .collect::<Vec<&str>>()
+            .join("\n");
+        assert_eq!(solution2(&without_third), Ok(Answer::Int(5)));
+        assert_eq!(
+            solution2(network),
+            Err(SolveError::unsolved(
+                DAY,
+                "the ghosts are never all on a Z node at once"
+            ))
+        );
+
+        let cycle = GhostCycle {
+            entry: 1,
+            length: 3,
+            lead_in: vec![],
+            offsets: vec![2],
+        };
+        assert!(cycle.is_on_z(8) && !cycle.is_on_z(9));
+    }
+
+    #[test]
+    fn network() {
+        let (instructions, network) = parse_map(EXAMPLE_2).unwrap();
+        assert_eq!(instructions, vec![0, 0, 1]);
+        assert_eq!(network.len(), 3);
+        let [a, b, z] = ["AAA", "BBB", "ZZZ"].map(|n| network.index_of(n).unwrap());
+        assert_eq!((network.next(a, 0), network.next(b, 1)), (b, z));
+
+        let dot = network.to_dot();
+        assert!(dot.starts_with("digraph network {\n    \"AAA\" [shape=box];\n"));
+        assert!(dot.contains("    \"BBB\" -> \"ZZZ\" [label=\"R\"];\n"));
+
+        let json: serde_json::Value = serde_json::from_str(&network.to_json()).unwrap();
+        assert_eq!(json[1]["name"], "BBB");
+        assert_eq!(json[1]["right"], "ZZZ");
+    }
+
+    #[test]
+    fn malformed_input() {
+        let error = solution1("LXR\n\nAAA = (AAA, AAA)")
+            .unwrap_err()
+            .into_parse_error()
+            .unwrap();
+        assert_eq!((error.line, error.column, error.text.as_str()), (1, 2, "X"));
+        let error = solution1("LR\n\nAAA = (BBB, CCC)\nBBB = (AAA, AAA)")
+            .unwrap_err()
+            .into_parse_error()
+            .unwrap();
+        assert_eq!(
+            (error.line, error.column, error.text.as_str()),
+            (3, 13, "CCC")
+        );
+        let error = solution1("LR\n\nAAA = (BBB CCC)")
+            .unwrap_err()
+            .into_parse_error()
+            .unwrap();
+        assert_eq!((error.line, error.column), (3, 8));
+        let error = solution2("\n\n11A = (11Z, 11Z)\n11Z = (11A, 11A)")
+            .unwrap_err()
+            .into_parse_error()
+            .unwrap();
+        assert_eq!(
+            (error.line, error.column, error.reason.as_str()),
+            (1, 1, "expected 'L' or 'R'")
+        );
+    }
+
+    #[test]
+    fn unreachable_nodes() {
+        assert_eq!(
+            solution1("L\n\nAAA = (BBB, BBB)\nBBB = (AAA, AAA)"),
+            Err(SolveError::unsolved(DAY, "there is no node ZZZ"))
+        );
+        assert_eq!(
+            solution1("LR\n\nAAA = (BBB, BBB)\nBBB = (AAA, AAA)\nZZZ = (ZZZ, ZZZ)"),
+            Err(SolveError::unsolved(DAY, "ZZZ can't be reached from AAA"))
+        );
+        assert_eq!(
+            solution2("L\n\n11B = (11Z, 11Z)\n11Z = (11B, 11B)"),
+            Err(SolveError::unsolved(DAY, "there are no nodes ending in A"))
+        );
+    }
 }
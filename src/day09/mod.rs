@@ -1,230 +1,193 @@
-use aocd::*;
+use crate::answer::Solution;
+use crate::bench;
+use crate::math::Rational;
+use crate::parse::{self, ParseError};
 use std::str::FromStr;
 
+const DAY: u8 = 9;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct History {
     data: Vec<i64>,
 }
 
-impl History {
-    pub fn extrapolate_score(&self) -> i64 {
-        let mut vecs: Vec<Vec<i64>> = Vec::new();
-        vecs.push(self.data.clone());
-
-        'report: loop {
-            // Copy all elements except the last
-            let mut subtract_vec = vecs.last().unwrap().clone();
-            subtract_vec.pop();
-
-            // Insert 0 to shift the elements forward
-            subtract_vec.insert(0, 0);
-
-            // Subtract those elements from the original vector (x_n - x_n-1)
-            let mut diff_vec: Vec<i64> = vecs
-                .last()
-                .unwrap()
-                .iter()
-                .zip(subtract_vec.iter())
-                .map(|(x, y)| x - y)
-                .collect();
-
-            // Remove the first element
-            diff_vec.remove(0);
-
-            // Store the new vector
-            vecs.push(diff_vec);
-
-            // If the vector is all zeroes
-            if vecs.last().unwrap().iter().all(|n| *n == 0) {
-                break 'report;
-            }
-        }
-
-        let mut extrapolation_vec = Vec::<i64>::new();
-
-        for (i, vec) in vecs.iter_mut().rev().enumerate() {
-            if i == 0 {
-                // Extrapolate last vector (zeroes)
-                extrapolation_vec.push(0);
-            } else if i == 1 {
-                // Extrapolate second-to-last vector (constants)
-                let num = *vec.first().unwrap();
-                extrapolation_vec.push(num);
-            } else {
-                // Extrapolate for any higher vecs
-                let num = vec.last().unwrap() + extrapolation_vec.last().unwrap();
-                extrapolation_vec.push(num);
-            }
-        }
+/// Polynomial in Newton form over the indices 0, 1, 2, ...:
+/// `p(x) = Σ d_k * C(x, k)`, where `d_k` is the k-th forward difference at 0
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Polynomial {
+    differences: Vec<Rational>,
+}
 
-        *extrapolation_vec.last().unwrap()
+impl Polynomial {
+    pub fn degree(&self) -> usize {
+        self.differences.len().saturating_sub(1)
     }
 
-    pub fn reverse_extrapolate_score(&self) -> i64 {
-        let mut vecs: Vec<Vec<i64>> = Vec::new();
-        vecs.push(self.data.clone());
-
-        'report: loop {
-            // Copy all elements except the last
-            let mut subtract_vec = vecs.last().unwrap().clone();
-            subtract_vec.pop();
-
-            // Insert 0 to shift the elements forward
-            subtract_vec.insert(0, 0);
-
-            // Subtract those elements from the original vector (x_n - x_n-1)
-            let mut diff_vec: Vec<i64> = vecs
-                .last()
-                .unwrap()
-                .iter()
-                .zip(subtract_vec.iter())
-                .map(|(x, y)| x - y)
-                .collect();
-
-            // Remove the first element
-            diff_vec.remove(0);
-
-            // Store the new vector
-            vecs.push(diff_vec);
-
-            // If the vector is all zeroes
-            if vecs.last().unwrap().iter().all(|n| *n == 0) {
-                break 'report;
-            }
+    /// Value of the polynomial at any index, including ones before the first value
+    pub fn evaluate(&self, x: i64) -> Rational {
+        let x = Rational::from(x);
+        let mut value = Rational::ZERO;
+        // C(x, k), built up one factor at a time
+        let mut binomial = Rational::ONE;
+        for (k, difference) in self.differences.iter().enumerate() {
+            value = value + *difference * binomial;
+            let k = Rational::from(k as i64);
+            binomial = binomial * (x - k) / (k + Rational::ONE);
         }
+        value
+    }
 
-        let mut extrapolation_vec = Vec::<i64>::new();
-
-        for (i, vec) in vecs.iter_mut().rev().enumerate() {
-            if i == 0 {
-                // Extrapolate last vector (zeroes)
-                extrapolation_vec.push(0);
-            } else if i == 1 {
-                // Extrapolate second-to-last vector (constants)
-                let num = *vec.first().unwrap();
-                extrapolation_vec.push(num);
-            } else {
-                // Extrapolate for any higher vecs
-                let num = vec.first().unwrap() - extrapolation_vec.last().unwrap();
-                extrapolation_vec.push(num);
+    /// Coefficients in the usual form `c_0 + c_1 x + c_2 x^2 + ...`
+    pub fn coefficients(&self) -> Vec<Rational> {
+        let mut coefficients = vec![Rational::ZERO; self.differences.len().max(1)];
+        // Coefficients of C(x, k), built up one factor at a time
+        let mut binomial = vec![Rational::ONE];
+        for (k, difference) in self.differences.iter().enumerate() {
+            for (c, b) in coefficients.iter_mut().zip(binomial.iter()) {
+                *c = *c + *difference * *b;
+            }
+            // Multiply by (x - k) / (k + 1)
+            let k = Rational::from(k as i64);
+            let scale = Rational::ONE / (k + Rational::ONE);
+            let mut next = vec![Rational::ZERO; binomial.len() + 1];
+            for (power, b) in binomial.iter().enumerate() {
+                next[power + 1] = next[power + 1] + *b * scale;
+                next[power] = next[power] - *b * k * scale;
             }
+            binomial = next;
         }
-
-        *extrapolation_vec.last().unwrap()
+        coefficients
     }
+}
 
-    pub fn alternative_extrapolate_score(&self) -> i64 {
-        let mut data = self.data.clone();
-        let mut degree: u32 = 0;
-
-        // Calculate degree of generating polynomial
-        'report: loop {
-            // Increment degree
-            degree += 1;
-
-            // Copy all elements except the last
-            let mut subtract_vec = data.clone();
-            subtract_vec.pop();
-
-            // Insert 0 to shift the elements forward
-            subtract_vec.insert(0, 0);
-
-            // Subtract those elements from the original vector (x_n - x_n-1)
-            data = data
-                .iter()
-                .zip(subtract_vec.iter())
-                .map(|(x, y)| x - y)
-                .collect();
+impl History {
+    /// The polynomial of lowest degree through every value, from the leading entries of
+    /// the table of differences
+    pub fn polynomial(&self) -> Polynomial {
+        let mut differences = Vec::new();
+        let mut row = self.data.clone();
+        while row.iter().any(|n| *n != 0) {
+            differences.push(Rational::from(row[0]));
+            row = row.windows(2).map(|w| w[1] - w[0]).collect();
+        }
+        Polynomial { differences }
+    }
 
-            // Remove the first element
-            data.remove(0);
+    pub fn degree(&self) -> usize {
+        self.polynomial().degree()
+    }
 
-            // If the vector is all zeroes
-            if data.iter().all(|n| *n == 0) {
-                degree -= 1;
-                break 'report;
-            }
-        }
+    /// Value of the history at any index. The recorded values are at 0 to len - 1.
+    pub fn value_at(&self, index: i64) -> i64 {
+        let value = self.polynomial().evaluate(index);
+        value.to_integer().unwrap() as i64
+    }
 
-        // Create system of equations
-        let mut matrix: Vec<Vec<i64>> = Vec::new();
-        for x in 0..self.data.len() {
-            let mut row = Vec::<i64>::new();
-            for p in 0..=degree {
-                row.push((x as i64).pow(p));
-            }
-            matrix.push(row);
+    /// Value `steps` after the last one, or before the first one if negative
+    pub fn extrapolate(&self, steps: i64) -> i64 {
+        if steps >= 0 {
+            self.value_at(self.data.len() as i64 - 1 + steps)
+        } else {
+            self.value_at(steps)
         }
+    }
 
-        // Solve system of equations
-        // (not implemented)
+    pub fn extrapolate_score(&self) -> i64 {
+        self.extrapolate(1)
+    }
 
-        0
+    pub fn reverse_extrapolate_score(&self) -> i64 {
+        self.extrapolate(-1)
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
-pub struct ParseHistoryErr;
-
 impl FromStr for History {
-    type Err = ParseHistoryErr;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let data: Vec<i64> = s
-            .split(' ')
-            .map(|s| {
-                let num: i64 = s.parse().unwrap();
-                num
-            })
-            .collect();
+        let data: Vec<i64> = parse::fields(s, ' ')
+            .map(|(column, field)| parse::number(DAY, column, field))
+            .collect::<Result<_, _>>()?;
 
         Ok(History { data })
     }
 }
 
-#[aocd(2023, 9)]
-pub fn solution1() {
-    let histories: Vec<History> = input!()
-        .split('\n')
-        .map(|s| History::from_str(s).unwrap())
-        .collect();
-
-    // for history in histories {
-    //     println!("{:?}", &history);
-    // }
-
-    // let test_history_1 = History::from_str("0 3 6 9 12 15").unwrap();
-    // let test_history_2 = History::from_str("1 3 6 10 15 21").unwrap();
-    // let test_history_3 = History::from_str("10 13 16 21 30 45").unwrap();
-    // let test_history_4 = History::from_str("3 3 3 3 3 3").unwrap();
-    // println!("{}", test_history_4.extrapolate_score());
+pub fn solution1(input: &str) -> Solution {
+    let histories: Vec<History> = parse::lines(input, History::from_str)?;
+    bench::parsed();
 
     let score: i64 = histories.iter().map(|h| h.extrapolate_score()).sum();
 
-    submit!(1, score);
+    Ok(score.into())
 }
 
-#[aocd(2023, 9)]
-pub fn solution2() {
-    let histories: Vec<History> = input!()
-        .split('\n')
-        .map(|s| History::from_str(s).unwrap())
-        .collect();
-
-    // for history in histories {
-    //     println!("{:?}", &history);
-    // }
-
-    // let test_history_1 = History::from_str("0 3 6 9 12 15").unwrap();
-    // let test_history_2 = History::from_str("1 3 6 10 15 21").unwrap();
-    // let test_history_3 = History::from_str("10 13 16 21 30 45").unwrap();
-    // let test_history_4 = History::from_str("3 3 3 3 3 3").unwrap();
-    // println!("{}", test_history_4.extrapolate_score());
+pub fn solution2(input: &str) -> Solution {
+    let histories: Vec<History> = parse::lines(input, History::from_str)?;
+    bench::parsed();
 
     let score: i64 = histories
         .iter()
         .map(|h| h.reverse_extrapolate_score())
         .sum();
 
-    submit!(2, score);
+    Ok(score.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::answer::Answer;
+
+    const EXAMPLE: &str = "\
+0 3 6 9 12 15
+1 3 6 10 15 21
+10 13 16 21 30 45";
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(solution1(EXAMPLE), Ok(Answer::Int(114)));
+    }
+
+    #[test]
+    fn example_part2() {
+        assert_eq!(solution2(EXAMPLE), Ok(Answer::Int(2)));
+    }
+
+    #[test]
+    fn polynomial_fit() {
+        let history = History::from_str("1 3 6 10 15 21").unwrap();
+        assert_eq!(history.degree(), 2);
+        let half = Rational::new(1, 2);
+        assert_eq!(
+            history.polynomial().coefficients(),
+            vec![Rational::ONE, Rational::new(3, 2), half]
+        );
+        assert_eq!(history.value_at(-2), 0);
+        assert_eq!(history.extrapolate(3), 45);
+        assert_eq!(history.extrapolate(-3), 1);
+        assert_eq!(history.polynomial().evaluate(-1), Rational::ZERO);
+
+        let constant = History::from_str("3 3 3").unwrap();
+        assert_eq!(constant.degree(), 0);
+        assert_eq!(constant.extrapolate(100), 3);
+        assert_eq!(History::from_str("0 0 0").unwrap().extrapolate(5), 0);
+    }
+
+    #[test]
+    fn malformed_input() {
+        let error = solution1("0 3 6\n1 3 six 10")
+            .unwrap_err()
+            .into_parse_error()
+            .unwrap();
+        assert_eq!(
+            (error.line, error.column, error.text.as_str()),
+            (2, 5, "six")
+        );
+        let error = solution1("0 3 6\n\n1 3 6")
+            .unwrap_err()
+            .into_parse_error()
+            .unwrap();
+        assert_eq!((error.line, error.column, error.text.as_str()), (2, 1, ""));
+    }
 }
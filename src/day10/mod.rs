@@ -1,49 +1,64 @@
+use crate::answer::{Solution, SolveError};
+use crate::bench;
+use crate::geometry::{Coord, Direction};
+use crate::grid;
+use crate::parse::ParseError;
+use crate::polygon::Polygon;
 use std::collections::HashSet;
 
-use aocd::*;
+const DAY: u8 = 10;
 
-#[derive(Debug, Clone, Copy)]
+/// Pipe shapes and the directions they lead in, not counting S
+const SHAPES: [(char, &[Direction]); 7] = [
+    ('L', &[Direction::Up, Direction::Right]),
+    ('7', &[Direction::Left, Direction::Down]),
+    ('J', &[Direction::Up, Direction::Left]),
+    ('F', &[Direction::Right, Direction::Down]),
+    ('.', &[]),
+    ('-', &[Direction::Left, Direction::Right]),
+    ('|', &[Direction::Up, Direction::Down]),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Tile {
     value: char,
-    gates: u8, // up, left, right, down
-    connects_to_start: bool,
-    port_flag: bool,
-    starboard_flag: bool,
+    /// Directions the pipe leads in
+    gates: HashSet<Direction>,
 }
 
 impl Tile {
-    pub fn from_char(c: char) -> Tile {
+    /// The tile drawn as `c`, if it is S or one of the pipe shapes
+    pub fn from_char(c: char) -> Option<Tile> {
         let gates = match c {
-            'L' => 0b1010,
-            '7' => 0b0101,
-            'J' => 0b1100,
-            'F' => 0b0011,
-            '.' => 0b0000,
-            '-' => 0b0110,
-            '|' => 0b1001,
-            'S' => 0b1111,
-            _ => unreachable!("Character not present in input data."),
+            'S' => Direction::ALL.into_iter().collect(),
+            _ => {
+                let (_, gates) = SHAPES.iter().find(|(shape, _)| *shape == c)?;
+                gates.iter().copied().collect()
+            }
         };
-        let connects_to_start = c == 'S';
+        Some(Tile { value: c, gates })
+    }
+
+    /// Ground, with no pipe on it
+    fn ground() -> Tile {
         Tile {
-            value: c,
-            gates,
-            connects_to_start,
-            port_flag: false,
-            starboard_flag: false,
+            value: '.',
+            gates: HashSet::new(),
         }
     }
 
-    pub fn connects_to(&self, tile: &Tile, from_gate: u8) -> bool {
-        let from_gate_open = (self.gates & from_gate) == from_gate;
-        let to_gate_open = match from_gate {
-            0b1000 => (tile.gates & 0b0001) == 0b0001,
-            0b0100 => (tile.gates & 0b0010) == 0b0010,
-            0b0010 => (tile.gates & 0b0100) == 0b0100,
-            0b0001 => (tile.gates & 0b1000) == 0b1000,
-            _ => unreachable!("Invalid from_gate."),
-        };
-        from_gate_open && to_gate_open
+    /// Pipe with exactly the given gates
+    fn from_gates(gates: &HashSet<Direction>) -> Option<Tile> {
+        SHAPES
+            .iter()
+            .find(|(_, g)| g.len() == gates.len() && g.iter().all(|d| gates.contains(d)))
+            .and_then(|(shape, _)| Tile::from_char(*shape))
+    }
+
+    /// Whether the pipe leads into `tile`, its neighbor in the given direction, and that
+    /// one leads back
+    pub fn connects_to(&self, tile: &Tile, direction: Direction) -> bool {
+        self.gates.contains(&direction) && tile.gates.contains(&direction.reverse())
     }
 
     pub fn display_tile(&self) -> char {
@@ -62,302 +77,358 @@ impl Tile {
 }
 
 pub struct Grid {
-    grid: Vec<Vec<Tile>>,
+    grid: grid::Grid<Tile>,
 }
 
 impl Grid {
-    pub fn tile(&self, position: (usize, usize)) -> Tile {
-        self.grid[position.0][position.1]
+    /// Read in the grid map, with a margin of . tiles around it so we don't have to care
+    /// about out-of-bounds indexing
+    pub fn from_string(s: &str) -> Result<Grid, ParseError> {
+        let grid = grid::Grid::try_parse(s, DAY, "expected S or a pipe", Tile::from_char)?;
+        let starts: Vec<(usize, usize)> = grid
+            .enumerate()
+            .filter(|(_, t)| t.value == 'S')
+            .map(|(position, _)| position)
+            .collect();
+        if starts.len() != 1 {
+            let (i, j) = starts.get(1).copied().unwrap_or((0, 0));
+            let text = if starts.is_empty() { "" } else { "S" };
+            return Err(ParseError::new(DAY, j + 1, text, "expected a single S").on_line(i + 1));
+        }
+        Ok(Grid {
+            grid: grid.padded(1, Tile::ground()),
+        })
     }
 
-    pub fn connect_to_loop(&mut self, position: (usize, usize)) {
-        self.grid[position.0][position.1].connects_to_start = true;
+    /// Coordinate of the S tile
+    pub fn start(&self) -> Coord {
+        Coord::from_index(self.grid.find(|t| t.value == 'S').unwrap())
     }
 
-    pub fn flag_port(&mut self, position: (usize, usize)) {
-        self.grid[position.0][position.1].port_flag = true;
+    pub fn tile(&self, coord: Coord) -> &Tile {
+        self.grid.at(coord).expect("Coordinate off the grid.")
     }
+}
+
+/// Where a tile lies relative to the main loop
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Region {
+    Loop,
+    Inside,
+    Outside,
+}
+
+/// The main loop through S, with every tile of the grid classified against it
+pub struct PipeLoop {
+    /// Tiles of the loop in order, starting at S
+    tiles: Vec<Coord>,
+    /// The pipe hidden under S
+    start_tile: Tile,
+    regions: grid::Grid<Region>,
+}
 
-    pub fn flag_starboard(&mut self, position: (usize, usize)) {
-        self.grid[position.0][position.1].starboard_flag = true;
+impl PipeLoop {
+    /// Follow the pipes from S until they lead back to it. The shape of S is the one
+    /// connecting the two neighbors that connect back to it.
+    pub fn trace(grid: &Grid) -> Result<PipeLoop, SolveError> {
+        let start = grid.start();
+        let start_gates: HashSet<Direction> = Direction::ALL
+            .into_iter()
+            .filter(|d| grid.tile(start).connects_to(grid.tile(start.step(*d)), *d))
+            .collect();
+        let start_tile = Tile::from_gates(&start_gates)
+            .filter(|tile| tile.gates.len() == 2)
+            .ok_or_else(|| {
+                let reason = format!("S connects to {} pipes, not 2", start_gates.len());
+                SolveError::unsolved(DAY, reason)
+            })?;
+
+        // Leave every tile through the gate we didn't enter by
+        let mut tiles = vec![start];
+        let mut position = start;
+        let mut direction = *start_tile.gates.iter().min().unwrap();
+        loop {
+            position = position.step(direction);
+            if position == start {
+                break;
+            }
+            tiles.push(position);
+            // S is known to connect both ways, and every later tile was entered through
+            // one of its own gates, so the only way out is the other one
+            let entered_from = direction.reverse();
+            direction = *grid
+                .tile(position)
+                .gates
+                .iter()
+                .find(|d| **d != entered_from)
+                .unwrap();
+            let next = grid.tile(position.step(direction));
+            if !grid.tile(position).connects_to(next, direction) {
+                // The margin makes grid coordinates match the input's lines and columns
+                let (line, column) = (position.i, position.j);
+                let reason = format!("the loop through S ends at line {line}, column {column}");
+                return Err(SolveError::unsolved(DAY, reason));
+            }
+        }
+
+        // Classify every tile. Going along a row, a tile is inside once the loop has been
+        // crossed an odd number of times, counting pipes that reach upwards.
+        let mut regions = grid::Grid::new(grid.grid.nrows(), grid.grid.ncols(), Region::Outside);
+        for coord in tiles.iter() {
+            let position = regions.position_of(*coord).unwrap();
+            regions[position] = Region::Loop;
+        }
+        for i in 0..regions.nrows() {
+            let mut inside = false;
+            for j in 0..regions.ncols() {
+                if regions[(i, j)] == Region::Loop {
+                    let coord = Coord::from_index((i, j));
+                    let tile = if coord == start {
+                        &start_tile
+                    } else {
+                        grid.tile(coord)
+                    };
+                    inside ^= tile.gates.contains(&Direction::Up);
+                } else if inside {
+                    regions[(i, j)] = Region::Inside;
+                }
+            }
+        }
+
+        Ok(PipeLoop {
+            tiles,
+            start_tile,
+            regions,
+        })
     }
 
-    pub fn delete_tile(&mut self, position: (usize, usize)) {
-        self.grid[position.0][position.1] = Tile::from_char('.');
+    pub fn len(&self) -> usize {
+        self.tiles.len()
     }
 
-    pub fn print(&self) {
-        for row in &self.grid {
-            let row_string: String = row.iter().map(|s| s.value).collect();
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
 
-            println!("{}", row_string);
-        }
+    /// Shape of the pipe under S
+    pub fn start_shape(&self) -> char {
+        self.start_tile.value
     }
-}
 
-#[aocd(2023, 10)]
-pub fn solution1() {
-    // Read in grid map
-    // Add in margin of . tiles around grid so we don't have to care about out-of-bounds indexing
-    let mut grid: Vec<Vec<Tile>> = input!()
-        .split('\n')
-        .map(|s| {
-            let mut row = Vec::<Tile>::new();
-            row.push(Tile::from_char('.')); // margin
-            for c in s.chars() {
-                row.push(Tile::from_char(c))
-            }
-            row.push(Tile::from_char('.')); // margin
-            row
-        })
-        .collect();
+    /// Steps along the loop to the tile farthest from S
+    pub fn farthest(&self) -> usize {
+        self.len() / 2
+    }
 
-    // Add margin rows to top and bottom
-    let mut margin_row = Vec::<Tile>::new();
-    let ncols = grid[0].len();
-    for _ in 0..ncols {
-        margin_row.push(Tile::from_char('.'));
+    /// The loop as a polygon through the centers of its tiles
+    pub fn polygon(&self) -> Polygon {
+        Polygon::from_vertices(self.tiles.clone())
     }
-    grid.insert(0, margin_row.clone());
-    grid.push(margin_row.clone());
-
-    // Determine grid size
-    let nrows = grid.len() - 1;
-    let ncols = grid[0].len() - 1;
-
-    // Find starting position
-    let mut position = (0, 0);
-    for i in 1..nrows {
-        for j in 1..ncols {
-            if grid[i][j].value == 'S' {
-                position = (i, j);
-            }
-        }
+
+    /// Number of tiles enclosed by the loop, by the shoelace formula and Pick's theorem
+    pub fn enclosed(&self) -> i64 {
+        self.polygon().interior_points()
     }
 
-    // Helper struct
-    let mut grid = Grid { grid };
-
-    // Traverse tiles until no more main loop tiles are present
-    let mut main_loop_count = 1;
-    'traverse: loop {
-        // Check adjacent tiles for connectivity
-        // Current position
-        let i = position.0;
-        let j = position.1;
-        // Position of adjacent tiles
-        let positions = [(i - 1, j), (i + 1, j), (i, j - 1), (i, j + 1)];
-        // Gates that those respective adjacent tiles can connect to
-        let from_gates: [u8; 4] = [0b1000, 0b0001, 0b0100, 0b0010];
-        // Iterate through adjacent tiles
-        for (pos, from_gate) in positions.iter().zip(from_gates.iter()) {
-            // Check if it connects to the current tile
-            let connects = grid.tile((i, j)).connects_to(&grid.tile(*pos), *from_gate);
-            // Check if it has been flagged as added to the main loop (connects to start)
-            let added_to_loop = grid.tile(*pos).connects_to_start;
-            // If it connects and hasn't been added, flag it as added and move our current position
-            if connects && !added_to_loop {
-                position = *pos;
-                grid.connect_to_loop(*pos);
-                main_loop_count += 1;
-                continue 'traverse;
-            }
-        }
-        break 'traverse;
+    /// Where a tile lies relative to the loop. Tiles off the grid are outside.
+    pub fn region(&self, coord: Coord) -> Region {
+        self.regions.at(coord).copied().unwrap_or(Region::Outside)
     }
 
-    // Remove non main loop tiles (just for displaying)
-    for i in 1..nrows {
-        for j in 1..ncols {
-            if !grid.tile((i, j)).connects_to_start {
-                grid.delete_tile((i, j));
+    /// The grid with the loop drawn in pipes, S replaced by its shape, and the other tiles
+    /// marked I if inside the loop and O if outside
+    pub fn render(&self, grid: &Grid) -> String {
+        let start = self.tiles[0];
+        let mut rendering = String::new();
+        for (i, row) in self.regions.rows().enumerate() {
+            for (j, region) in row.iter().enumerate() {
+                let coord = Coord::from_index((i, j));
+                rendering.push(match region {
+                    Region::Loop if coord == start => self.start_tile.display_tile(),
+                    Region::Loop => grid.tile(coord).display_tile(),
+                    Region::Inside => 'I',
+                    Region::Outside => 'O',
+                });
             }
+            rendering.push('\n');
         }
+        rendering
     }
+}
 
-    // Display grid
-    grid.print();
+pub fn solution1(input: &str) -> Solution {
+    let grid = Grid::from_string(input)?;
+    bench::parsed();
+    let pipe_loop = PipeLoop::trace(&grid)?;
+    Ok(pipe_loop.farthest().into())
+}
 
-    // Submit solution
-    submit!(1, main_loop_count / 2);
+pub fn solution2(input: &str) -> Solution {
+    let grid = Grid::from_string(input)?;
+    bench::parsed();
+    let pipe_loop = PipeLoop::trace(&grid)?;
+    Ok(pipe_loop.enclosed().into())
 }
 
-#[aocd(2023, 10)]
-pub fn solution2() {
-    // let contents = fs::read_to_string("src/day10/test4.txt").expect("Unable to read file.");
-    // let mut grid: Vec<Vec<Tile>> = contents.split('\n')
-    //     .map(|s| {
-    //         let mut row = Vec::<Tile>::new();
-    //         row.push(Tile::from_char('.')); // margin
-    //         for c in s.chars() {
-    //             if "F7JL|-S.".contains(c) {
-    //                 row.push(Tile::from_char(c))
-    //             }
-    //         }
-    //         row.push(Tile::from_char('.')); // margin
-    //         row
-    //     })
-    //     .collect();
-
-    // Read in grid map
-    // Add in margin of . tiles around grid so we don't have to care about out-of-bounds indexing
-    let mut grid: Vec<Vec<Tile>> = input!()
-        .split('\n')
-        .map(|s| {
-            let mut row = Vec::<Tile>::new();
-            row.push(Tile::from_char('.')); // margin
-            for c in s.chars() {
-                row.push(Tile::from_char(c))
-            }
-            row.push(Tile::from_char('.')); // margin
-            row
-        })
-        .collect();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::answer::Answer;
+
+    const EXAMPLE_1: &str = "\
+.....
+.S-7.
+.|.|.
+.L-J.
+.....";
+
+    const EXAMPLE_2: &str = "\
+..F7.
+.FJ|.
+SJ.L7
+|F--J
+LJ...";
+
+    const EXAMPLE_3: &str = "\
+...........
+.S-------7.
+.|F-----7|.
+.||.....||.
+.||.....||.
+.|L-7.F-J|.
+.|..|.|..|.
+.L--J.L--J.
+...........";
+
+    const EXAMPLE_4: &str = "\
+.F----7F7F7F7F-7....
+.|F--7||||||||FJ....
+.||.FJ||||||||L7....
+FJL7L7LJLJ||LJ.L-7..
+L--J.L7...LJS7F-7L7.
+....F-J..F7FJ|L7L7L7
+....L7.F7||L7|.L7L7|
+.....|FJLJ|FJ|F7|.LJ
+....FJL-7.||.||||...
+....L---J.LJ.LJLJ...";
+
+    const EXAMPLE_5: &str = "\
+FF7FSF7F7F7F7F7F---7
+L|LJ||||||||||||F--J
+FL-7LJLJ||||||LJL-77
+F--JF--7||LJLJ7F7FJ-
+L---JF-JLJ.||-FJLJJ7
+|F|F-JF---7F7-L7L|7|
+|FFJF7L7F-JF7|JL---7
+7-L-JL7||F7|L7F-7F7|
+L.L7LFJ|||||FJL7||LJ
+L7JLJL-JLJLJL--JLJ.L";
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(solution1(EXAMPLE_1), Ok(Answer::Int(4)));
+    }
 
-    // Add margin rows to top and bottom
-    let mut margin_row = Vec::<Tile>::new();
-    let ncols = grid[0].len();
-    for _ in 0..ncols {
-        margin_row.push(Tile::from_char('.'));
+    #[test]
+    fn example_part1_complex() {
+        assert_eq!(solution1(EXAMPLE_2), Ok(Answer::Int(8)));
     }
-    grid.insert(0, margin_row.clone());
-    grid.push(margin_row.clone());
-
-    // Determine grid size
-    let nrows = grid.len() - 1;
-    let ncols = grid[0].len() - 1;
-
-    // Find starting position
-    let mut position = (0, 0);
-    for i in 1..nrows {
-        for j in 1..ncols {
-            if grid[i][j].value == 'S' {
-                position = (i, j);
-            }
-        }
+
+    #[test]
+    fn example_part2() {
+        assert_eq!(solution2(EXAMPLE_3), Ok(Answer::Int(4)));
     }
 
-    // Helper struct
-    let mut grid = Grid { grid };
-
-    // Traverse tiles
-    // Visualize the traversal as a ship moving through a sea :)
-    // It has a port and starboard; we will track the tiles on its port and starboard as we go
-    let mut port = HashSet::<(usize, usize)>::new();
-    let mut starboard = HashSet::<(usize, usize)>::new();
-    let mut main_loop_count = 1;
-    'traverse: loop {
-        // Check adjacent tiles for connectivity
-        // Current position
-        let i = position.0;
-        let j = position.1;
-        // Position of adjacent tiles
-        let positions = [(i - 1, j), (i + 1, j), (i, j - 1), (i, j + 1)];
-        // Gates that those respective adjacent tiles can connect to
-        let from_gates: [u8; 4] = [0b1000, 0b0001, 0b0100, 0b0010];
-        // Iterate through adjacent tiles
-        for (pos, from_gate) in positions.iter().zip(from_gates.iter()) {
-            // Check if it connects to the current tile
-            let connects = grid.tile((i, j)).connects_to(&grid.tile(*pos), *from_gate);
-            // Check if it has been flagged as added to the main loop (connects to start)
-            let added_to_loop = grid.tile(*pos).connects_to_start;
-            // If it connects and hasn't been added, flag it as added and move our current position
-            if connects && !added_to_loop {
-                // Mark the port & starboard of new position
-                let (new_port_1, new_port_2, new_starboard_1, new_starboard_2) =
-                    if *pos == (i - 1, j) {
-                        // Moving up
-                        ((i - 1, j - 1), (i, j - 1), (i - 1, j + 1), (i, j + 1))
-                    } else if *pos == (i + 1, j) {
-                        // Moving down
-                        ((i + 1, j + 1), (i, j + 1), (i + 1, j - 1), (i, j - 1))
-                    } else if *pos == (i, j - 1) {
-                        // Moving left
-                        ((i + 1, j - 1), (i + 1, j), (i - 1, j - 1), (i - 1, j))
-                    } else {
-                        // Moving right
-                        ((i - 1, j + 1), (i - 1, j), (i + 1, j + 1), (i + 1, j))
-                    };
-                port.insert(new_port_1);
-                port.insert(new_port_2);
-                starboard.insert(new_starboard_1);
-                starboard.insert(new_starboard_2);
-                position = *pos;
-                grid.connect_to_loop(*pos);
-                main_loop_count += 1;
-                continue 'traverse;
-            }
-        }
-        break 'traverse;
+    #[test]
+    fn example_part2_larger() {
+        assert_eq!(solution2(EXAMPLE_4), Ok(Answer::Int(8)));
     }
 
-    // Flag tiles for port & starboard
-    for pos in port {
-        if !grid.tile(pos).connects_to_start {
-            grid.flag_port(pos);
-        }
+    #[test]
+    fn example_part2_junk() {
+        assert_eq!(solution2(EXAMPLE_5), Ok(Answer::Int(10)));
     }
-    for pos in starboard {
-        if !grid.tile(pos).connects_to_start {
-            grid.flag_starboard(pos);
-        }
+
+    #[test]
+    fn malformed_input() {
+        let error = solution1(".S-7.\n.|.x.").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!((error.line, error.column, error.text.as_str()), (2, 4, "x"));
+        let error = solution2(".S-7.\n.|.|").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!(
+            (error.line, error.reason.as_str()),
+            (2, "expected a row of 5 tiles")
+        );
+        let error = solution1(".S-7.\n.|.S.").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!(
+            (error.line, error.column, error.reason.as_str()),
+            (2, 4, "expected a single S")
+        );
+        assert_eq!(
+            solution1(".S-7.\n.|.|.\n.L-..").map_err(|e| e.to_string()),
+            Err("Day 10 input has no answer: the loop through S ends at line 3, column 3".into())
+        );
+        assert_eq!(
+            solution2("S.\n.."),
+            Err(SolveError::unsolved(DAY, "S connects to 0 pipes, not 2"))
+        );
     }
 
-    // Iterate through tiles. If an empty tile is adjacent to a port or starboard tile, flag them as well
-    'flagging: loop {
-        let mut added_flag = false;
-        for i in 1..nrows {
-            for j in 1..ncols {
-                if !grid.tile((i, j)).connects_to_start {
-                    let positions = [(i - 1, j), (i + 1, j), (i, j - 1), (i, j + 1)];
-                    for pos in positions {
-                        if grid.tile(pos).port_flag && !grid.tile((i, j)).port_flag {
-                            grid.flag_port((i, j));
-                            added_flag = true;
-                        }
-                        if grid.tile(pos).starboard_flag && !grid.tile((i, j)).starboard_flag {
-                            grid.flag_starboard((i, j));
-                            added_flag = true;
-                        }
-                    }
-                }
-            }
-        }
-        if !added_flag {
-            break 'flagging;
+    #[test]
+    fn classification() {
+        for (example, shape) in [(EXAMPLE_2, 'F'), (EXAMPLE_4, 'F'), (EXAMPLE_5, '7')] {
+            let grid = Grid::from_string(example).unwrap();
+            let pipe_loop = PipeLoop::trace(&grid).unwrap();
+            assert_eq!(pipe_loop.start_shape(), shape);
+
+            // The scanline classification agrees with Pick's theorem
+            let inside = grid
+                .grid
+                .positions()
+                .filter(|p| pipe_loop.region(Coord::from_index(*p)) == Region::Inside)
+                .count();
+            assert_eq!(inside as i64, pipe_loop.enclosed());
         }
+
+        let grid = Grid::from_string(EXAMPLE_3).unwrap();
+        let rendering = PipeLoop::trace(&grid).unwrap().render(&grid);
+        assert_eq!(rendering.lines().nth(7), Some("OO│II│O│II│OO"));
     }
 
-    // Display grid
-    let mut starboard_count = 0;
-    let mut port_count = 0;
-    for row in grid.grid {
-        let row_string: String = row
-            .iter()
-            .map(|s| {
-                if s.port_flag && s.starboard_flag {
-                    panic!("Should not be possible!");
-                }
-                if (s.port_flag || s.starboard_flag) && s.connects_to_start {
-                    panic!("Inner/outer tiles cannot be on the main loop!");
-                }
-                if s.port_flag {
-                    port_count += 1;
-                    '@'
-                } else if s.starboard_flag {
-                    starboard_count += 1;
-                    '#'
-                } else {
-                    s.display_tile()
-                }
+    #[test]
+    fn either_orientation() {
+        // Mirroring the map makes the loop run the other way round from S, which puts the
+        // inside on the other side of the direction of travel
+        let mirrored: Vec<String> = EXAMPLE_3
+            .lines()
+            .map(|line| {
+                line.chars()
+                    .rev()
+                    .map(|c| match c {
+                        'F' => '7',
+                        '7' => 'F',
+                        'L' => 'J',
+                        'J' => 'L',
+                        c => c,
+                    })
+                    .collect()
             })
             .collect();
-
-        println!("{}", row_string);
+        let mirrored = mirrored.join("\n");
+        let traced = |example: &str| {
+            let pipe_loop = PipeLoop::trace(&Grid::from_string(example).unwrap()).unwrap();
+            let vertices = pipe_loop.polygon().vertices().to_vec();
+            let signed_area: i64 = vertices
+                .iter()
+                .zip(vertices.iter().cycle().skip(1))
+                .map(|(a, b)| a.i * b.j - b.i * a.j)
+                .sum();
+            (signed_area.signum(), pipe_loop.enclosed())
+        };
+        let (orientation, enclosed) = traced(EXAMPLE_3);
+        assert_eq!(traced(&mirrored), (-orientation, enclosed));
+        assert_eq!(solution2(&mirrored), Ok(Answer::Int(4)));
     }
-
-    // Submit solution
-    println!("Main loop count: {}", main_loop_count / 2);
-    println!("{} {}", port_count, starboard_count);
-    submit!(2, port_count);
 }
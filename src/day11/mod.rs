@@ -1,17 +1,23 @@
+use crate::answer::Solution;
+use crate::bench;
+use crate::grid::Grid;
+use crate::parse::ParseError;
 use std::collections::HashSet;
 
-use aocd::*;
+const DAY: u8 = 11;
 
-#[aocd(2023, 11)]
-pub fn solution1() {
+/// Read the image of empty space and galaxies
+fn parse_image(input: &str) -> Result<Grid<char>, ParseError> {
+    Grid::try_parse(input, DAY, "expected '.' or '#'", |c| {
+        matches!(c, '.' | '#').then_some(c)
+    })
+}
+
+pub fn solution1(input: &str) -> Solution {
     // Read input
-    let mut grid: Vec<Vec<char>> = input!()
-        .split('\n')
-        .map(|s| {
-            let chars: Vec<char> = s.chars().collect();
-            chars
-        })
-        .collect();
+    let image = parse_image(input)?;
+    bench::parsed();
+    let mut grid: Vec<Vec<char>> = image.rows().map(|row| row.to_vec()).collect();
 
     // Cosmic expansion:
     // Duplicate empty rows
@@ -40,9 +46,9 @@ pub fn solution1() {
 
     // Get galaxy positions
     let mut coordinates: HashSet<(usize, usize)> = HashSet::new();
-    for i in 0..grid.len() {
-        for j in 0..grid[0].len() {
-            if grid[i][j] == '#' {
+    for (i, row) in grid.iter().enumerate() {
+        for (j, c) in row.iter().enumerate() {
+            if *c == '#' {
                 coordinates.insert((i, j));
             }
         }
@@ -70,19 +76,13 @@ pub fn solution1() {
 
     // Get sum of distances
     let total: u64 = dists.iter().sum();
-    submit!(1, total);
+    Ok(total.into())
 }
 
-#[aocd(2023, 11)]
-pub fn solution2() {
-    // Read input
-    let grid: Vec<Vec<char>> = input!()
-        .split('\n')
-        .map(|s| {
-            let chars: Vec<char> = s.chars().collect();
-            chars
-        })
-        .collect();
+/// Sum of the distances between all galaxy pairs, when every empty row and column is
+/// replaced by `expansion` of them
+fn total_distance(image: &Grid<char>, expansion: u64) -> u64 {
+    let grid: Vec<Vec<char>> = image.rows().map(|row| row.to_vec()).collect();
 
     // Cosmic expansion:
     // Get indices of empty rows
@@ -103,9 +103,9 @@ pub fn solution2() {
 
     // Get galaxy positions
     let mut coordinates: HashSet<(usize, usize)> = HashSet::new();
-    for i in 0..grid.len() {
-        for j in 0..grid[0].len() {
-            if grid[i][j] == '#' {
+    for (i, row) in grid.iter().enumerate() {
+        for (j, c) in row.iter().enumerate() {
+            if *c == '#' {
                 coordinates.insert((i, j));
             }
         }
@@ -153,12 +153,66 @@ pub fn solution2() {
             })
             .sum();
 
-        let row_dist = (row_max as u64 - row_min as u64) + (empty_row_count * 999_999);
-        let col_dist = (col_max as u64 - col_min as u64) + (empty_col_count * 999_999);
+        let row_dist = (row_max as u64 - row_min as u64) + (empty_row_count * (expansion - 1));
+        let col_dist = (col_max as u64 - col_min as u64) + (empty_col_count * (expansion - 1));
         dists.push(row_dist + col_dist);
     }
 
     // Get sum of distances
     let total: u64 = dists.iter().sum();
-    submit!(2, total);
+    total
+}
+
+pub fn solution2(input: &str) -> Solution {
+    let image = parse_image(input)?;
+    bench::parsed();
+    Ok(total_distance(&image, 1_000_000).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::answer::Answer;
+
+    const EXAMPLE: &str = "\
+...#......
+.......#..
+#.........
+..........
+......#...
+.#........
+.........#
+..........
+.......#..
+#...#.....";
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(solution1(EXAMPLE), Ok(Answer::Int(374)));
+    }
+
+    #[test]
+    fn example_part2_expansion_10() {
+        let image = parse_image(EXAMPLE).unwrap();
+        assert_eq!(total_distance(&image, 10), 1030);
+    }
+
+    #[test]
+    fn example_part2_expansion_100() {
+        let image = parse_image(EXAMPLE).unwrap();
+        assert_eq!(total_distance(&image, 100), 8410);
+    }
+
+    #[test]
+    fn malformed_input() {
+        let error = solution1("...#\n.x..").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!((error.line, error.column, error.text.as_str()), (2, 2, "x"));
+        let error = solution2("...#\n..").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!(
+            (error.line, error.reason.as_str()),
+            (2, "expected a row of 4 tiles")
+        );
+    }
 }
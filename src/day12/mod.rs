@@ -1,73 +1,114 @@
-use aocd::*;
-use regex::Regex;
+use crate::answer::Solution;
+use crate::bench;
+use crate::parse::{self, ParseError};
+use std::collections::HashMap;
 use std::str::FromStr;
-// use std::fs;
+
+const DAY: u8 = 12;
 
 pub struct Record {
     row: Vec<char>,
     broken_groups: Vec<u64>,
 }
 
-#[derive(Debug, PartialEq, Eq)]
-pub struct RecordParseErr;
-
 impl FromStr for Record {
-    type Err = RecordParseErr;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let data: Vec<&str> = s.split(' ').collect();
-        let row: Vec<char> = data[0].chars().collect();
-        let broken_groups: Vec<u64> = data[1]
-            .trim()
-            .split(',')
-            .map(|c| c.parse::<u64>().unwrap())
-            .collect();
+        let (row, groups) = s
+            .split_once(' ')
+            .ok_or_else(|| ParseError::new(DAY, 1, s, "expected springs and group sizes"))?;
+
+        // Springs are operational, damaged or unknown
+        if let Some((i, c)) = row.chars().enumerate().find(|(_, c)| !".#?".contains(*c)) {
+            return Err(ParseError::new(
+                DAY,
+                i + 1,
+                &c.to_string(),
+                "expected a spring",
+            ));
+        }
+        let row: Vec<char> = row.chars().collect();
+
+        // Group sizes start after the springs and the space
+        let offset = row.len() + 1;
+        let broken_groups: Vec<u64> = parse::fields(groups.trim_end(), ',')
+            .map(|(column, field)| parse::number(DAY, column + offset, field))
+            .collect::<Result<_, _>>()?;
         Ok(Record { row, broken_groups })
     }
 }
 
 impl Record {
-    pub fn generate_candidate_rows(&self) -> Vec<String> {
-        let mut rows: Vec<Vec<char>> = vec![vec!['.']];
-        for c in self.row.iter() {
-            let nrows = rows.len();
-            if *c == '?' {
-                for i in 0..nrows {
-                    let mut row_copy = rows[i].clone();
-                    rows[i].push('.');
-                    row_copy.push('#');
-                    rows.push(row_copy);
-                }
-            } else {
-                for i in 0..nrows {
-                    rows[i].push(*c);
-                }
-            }
+    /// Record with its springs repeated `times` times, separated by unknown springs,
+    /// and its group sizes repeated as well
+    pub fn unfold(&self, times: usize) -> Record {
+        let rows = vec![self.row.clone(); times];
+        Record {
+            row: rows.join(&'?'),
+            broken_groups: self.broken_groups.repeat(times),
         }
-        rows.iter().map(|v| v.iter().collect::<String>()).collect()
     }
 
-    pub fn generate_regex_pattern(&self) -> String {
-        let mut pattern = String::new();
-        for (i, group) in self.broken_groups.iter().enumerate() {
-            if i == 0 {
-                pattern = pattern.clone() + format!("^\\.*#{{{}}}", group).as_str();
-            } else {
-                pattern = pattern.clone() + format!("\\.+#{{{}}}", group).as_str();
+    /// Whether a spring can be damaged or operational, given what the record says about it
+    fn can_be(&self, position: usize, spring: char) -> bool {
+        self.row[position] == spring || self.row[position] == '?'
+    }
+
+    /// Number of ways to fill in the unknown springs so that the damaged springs form
+    /// exactly the recorded groups
+    pub fn count_arrangements(&self) -> u128 {
+        let mut memo = HashMap::new();
+        self.count_from(0, 0, 0, &mut memo)
+    }
+
+    /// Arrangements of the springs from `position` on, given that `group` groups have been
+    /// completed and the current group has `run` damaged springs so far
+    fn count_from(
+        &self,
+        position: usize,
+        group: usize,
+        run: u64,
+        memo: &mut HashMap<(usize, usize, u64), u128>,
+    ) -> u128 {
+        // At the end, every group has to be complete
+        if position == self.row.len() {
+            let ngroups = self.broken_groups.len();
+            let complete = (group == ngroups && run == 0)
+                || (group + 1 == ngroups && run == self.broken_groups[group]);
+            return complete as u128;
+        }
+        if let Some(count) = memo.get(&(position, group, run)) {
+            return *count;
+        }
+
+        let mut count = 0;
+        // Extend the current group, if it isn't full yet
+        if self.can_be(position, '#')
+            && group < self.broken_groups.len()
+            && run < self.broken_groups[group]
+        {
+            count += self.count_from(position + 1, group, run + 1, memo);
+        }
+        // Close the current group, if there is one and it is full
+        if self.can_be(position, '.') {
+            if run == 0 {
+                count += self.count_from(position + 1, group, 0, memo);
+            } else if run == self.broken_groups[group] {
+                count += self.count_from(position + 1, group + 1, 0, memo);
             }
         }
-        pattern = pattern.clone() + "\\.*$";
-        pattern
+
+        memo.insert((position, group, run), count);
+        count
     }
 
-    pub fn count_possible_rows(&self) -> u64 {
-        let candidates = self.generate_candidate_rows();
-        let pattern = self.generate_regex_pattern();
-        let re = Regex::new(pattern.as_str()).unwrap();
-        candidates
-            .iter()
-            .map(|s| if re.find(s.as_str()).is_some() { 1 } else { 0 })
-            .sum()
+    /// Every arrangement of the springs, worked out one at a time as they are asked for
+    pub fn arrangements(&self) -> Arrangements<'_> {
+        Arrangements {
+            record: self,
+            stack: vec![(0, 0, 0, String::new())],
+        }
     }
 
     pub fn display_row(&self) -> String {
@@ -75,20 +116,126 @@ impl Record {
     }
 }
 
-// No idea how to do this without brute force :(
-#[aocd(2023, 12)]
-pub fn solution1() {
-    // let input_data = fs::read_to_string("src/day12/test.txt").expect("Unable to read file.");
-    let input_data = input!();
-    let records: Vec<Record> = input_data
-        .split('\n')
-        .map(|s| Record::from_str(s).unwrap())
-        .collect();
+/// Depth-first walk over the arrangements of a record. Each state is the position,
+/// completed groups and current run, as in `Record::count_arrangements`, along with
+/// the springs chosen so far.
+pub struct Arrangements<'a> {
+    record: &'a Record,
+    stack: Vec<(usize, usize, u64, String)>,
+}
 
-    let total: u64 = records.iter().map(|r| r.count_possible_rows()).sum();
+impl Iterator for Arrangements<'_> {
+    type Item = String;
 
-    submit!(1, total);
+    fn next(&mut self) -> Option<String> {
+        let record = self.record;
+        let groups = &record.broken_groups;
+        while let Some((position, group, run, springs)) = self.stack.pop() {
+            if position == record.row.len() {
+                let complete = (group == groups.len() && run == 0)
+                    || (group + 1 == groups.len() && run == groups[group]);
+                if complete {
+                    return Some(springs);
+                }
+                continue;
+            }
+            // Push operational first, so damaged springs come out first
+            if record.can_be(position, '.') {
+                let next = if run == 0 {
+                    Some((group, 0))
+                } else if run == groups[group] {
+                    Some((group + 1, 0))
+                } else {
+                    None
+                };
+                if let Some((group, run)) = next {
+                    self.stack
+                        .push((position + 1, group, run, springs.clone() + "."));
+                }
+            }
+            if record.can_be(position, '#') && group < groups.len() && run < groups[group] {
+                self.stack
+                    .push((position + 1, group, run + 1, springs + "#"));
+            }
+        }
+        None
+    }
+}
+
+pub fn solution1(input: &str) -> Solution {
+    let records: Vec<Record> = parse::lines(input, Record::from_str)?;
+    bench::parsed();
+
+    let total: u128 = records.iter().map(|r| r.count_arrangements()).sum();
+
+    Ok(total.into())
+}
+
+pub fn solution2(input: &str) -> Solution {
+    let records: Vec<Record> = parse::lines(input, Record::from_str)?;
+    bench::parsed();
+
+    let total: u128 = records
+        .iter()
+        .map(|r| r.unfold(5).count_arrangements())
+        .sum();
+
+    Ok(total.into())
 }
 
-#[aocd(2023, 12)]
-pub fn solution2() {}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::answer::Answer;
+
+    const EXAMPLE: &str = "\
+???.### 1,1,3
+.??..??...?##. 1,1,3
+?#?#?#?#?#?#?#? 1,3,1,6
+????.#...#... 4,1,1
+????.######..#####. 1,6,5
+?###???????? 3,2,1";
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(solution1(EXAMPLE), Ok(Answer::Int(21)));
+    }
+
+    #[test]
+    fn example_part2() {
+        assert_eq!(solution2(EXAMPLE), Ok(Answer::Int(525152)));
+    }
+
+    #[test]
+    fn arrangements() {
+        let record = Record::from_str("?###???????? 3,2,1").unwrap();
+        let arrangements: Vec<String> = record.arrangements().collect();
+        assert_eq!(arrangements.len(), 10);
+        assert_eq!(arrangements[0], ".###.##.#...");
+        assert!(arrangements.iter().all(|a| a.len() == 12));
+
+        let record = Record::from_str(".??..??...?##. 1,1,3").unwrap();
+        assert_eq!(record.unfold(5).display_row().len(), 14 * 5 + 4);
+        assert_eq!(record.unfold(5).count_arrangements(), 16384);
+        assert_eq!(record.unfold(5).arrangements().take(3).count(), 3);
+    }
+
+    #[test]
+    fn malformed_input() {
+        let error = solution1("???.### 1,1,3\n.??..??...?##.")
+            .unwrap_err()
+            .into_parse_error()
+            .unwrap();
+        assert_eq!((error.line, error.column), (2, 1));
+        let error = solution1("???.### 1,1,3\n.??.x 1,1")
+            .unwrap_err()
+            .into_parse_error()
+            .unwrap();
+        assert_eq!((error.line, error.column, error.text.as_str()), (2, 5, "x"));
+        let error = solution1("???.### 1,1,3\n.??. 1,,1")
+            .unwrap_err()
+            .into_parse_error()
+            .unwrap();
+        assert_eq!((error.line, error.column, error.text.as_str()), (2, 8, ""));
+    }
+}
@@ -1,62 +1,46 @@
-use aocd::*;
+use crate::answer::Solution;
+use crate::bench;
+use crate::grid::Grid;
+use crate::parse::ParseError;
+
+const DAY: u8 = 13;
 
 pub struct Map {
-    h_data: Vec<Vec<u8>>,
-    v_data: Vec<Vec<u8>>,
+    h_data: Grid<u8>,
+    v_data: Grid<u8>,
 }
 
 impl Map {
-    pub fn from_vec(v: Vec<&str>) -> Map {
-        // Convert lines into vectors of integers
-        let mut h_data: Vec<Vec<u8>> = Vec::new();
-        for line in v.iter() {
-            let mut row = Vec::new();
-            for c in line.trim().chars() {
-                if c == '.' {
-                    row.push(0);
-                } else {
-                    row.push(1);
-                }
-            }
-            h_data.push(row);
-        }
+    pub fn from_vec(v: Vec<&str>) -> Result<Map, ParseError> {
+        // Convert lines into rows of integers
+        let h_data = Grid::try_parse(&v.join("\n"), DAY, "expected '.' or '#'", |c| match c {
+            '.' => Some(0),
+            '#' => Some(1),
+            _ => None,
+        })?;
 
         // Convert horizontal data into vertical data
-        let mut v_data: Vec<Vec<u8>> = Vec::new();
-        for j in 0..h_data[0].len() {
-            let mut col = Vec::new();
-            for row in h_data.iter() {
-                col.push(row[j]);
-            }
-            v_data.push(col);
-        }
+        let v_data = h_data.transpose();
 
-        Map { h_data, v_data }
+        Ok(Map { h_data, v_data })
     }
 
     pub fn display_horizontal_map(&self) {
-        for row in self.h_data.iter() {
-            let s: String = row.iter().map(|n| format!("{n}")).collect();
-            println!("{}", s);
-        }
-        println!();
+        println!("{}", self.h_data);
     }
 
     pub fn display_vertical_map(&self) {
-        for row in self.v_data.iter() {
-            let s: String = row.iter().map(|n| format!("{n}")).collect();
-            println!("{}", s);
-        }
-        println!();
+        println!("{}", self.v_data);
     }
 
     pub fn find_horizontal_symmetry(&self) -> u64 {
-        let n = self.h_data.len();
+        let data: Vec<&[u8]> = self.h_data.rows().collect();
+        let n = data.len();
         for i in 1..n {
             let mut mirrored = Vec::<bool>::new();
             for j in 0..i {
-                let a = self.h_data[j].clone();
-                if let Some(b) = self.h_data.get(2 * i - 1 - j) {
+                let a = data[j];
+                if let Some(b) = data.get(2 * i - 1 - j) {
                     let row_mirrored = a.iter().zip(b.iter()).all(|(x, y)| x == y);
                     mirrored.push(row_mirrored);
                 }
@@ -69,12 +53,13 @@ impl Map {
     }
 
     pub fn find_vertical_symmetry(&self) -> u64 {
-        let n = self.v_data.len();
+        let data: Vec<&[u8]> = self.v_data.rows().collect();
+        let n = data.len();
         for i in 1..n {
             let mut mirrored = Vec::<bool>::new();
             for j in 0..i {
-                let a = self.v_data[j].clone();
-                if let Some(b) = self.v_data.get(2 * i - 1 - j) {
+                let a = data[j];
+                if let Some(b) = data.get(2 * i - 1 - j) {
                     let row_mirrored = a.iter().zip(b.iter()).all(|(x, y)| x == y);
                     mirrored.push(row_mirrored);
                 }
@@ -87,12 +72,13 @@ impl Map {
     }
 
     pub fn find_near_horizontal_symmetry(&self) -> u64 {
-        let n = self.h_data.len();
+        let data: Vec<&[u8]> = self.h_data.rows().collect();
+        let n = data.len();
         for i in 1..n {
             let mut mirrored = Vec::<u64>::new();
             for j in 0..i {
-                let a = self.h_data[j].clone();
-                if let Some(b) = self.h_data.get(2 * i - 1 - j) {
+                let a = data[j];
+                if let Some(b) = data.get(2 * i - 1 - j) {
                     let row_mirrored: u64 = a
                         .iter()
                         .zip(b.iter())
@@ -109,12 +95,13 @@ impl Map {
     }
 
     pub fn find_near_vertical_symmetry(&self) -> u64 {
-        let n = self.v_data.len();
+        let data: Vec<&[u8]> = self.v_data.rows().collect();
+        let n = data.len();
         for i in 1..n {
             let mut mirrored = Vec::<u64>::new();
             for j in 0..i {
-                let a = self.v_data[j].clone();
-                if let Some(b) = self.v_data.get(2 * i - 1 - j) {
+                let a = data[j];
+                if let Some(b) = data.get(2 * i - 1 - j) {
                     let row_mirrored: u64 = a
                         .iter()
                         .zip(b.iter())
@@ -139,44 +126,101 @@ impl Map {
     }
 }
 
-#[aocd(2023, 13)]
-pub fn solution1() {
-    let input_data = input!();
-    let lines: Vec<&str> = input_data.split("\n\n").collect();
+/// Read the maps, one per block of lines, placing any error on its line of the input
+fn read_maps(blocks: &[&str]) -> Result<Vec<Map>, ParseError> {
+    let mut maps = Vec::<Map>::new();
+    let mut first_line = 1;
+    for block in blocks {
+        let map_rows: Vec<&str> = block.split('\n').collect();
+        let rows = map_rows.len();
+        let map = Map::from_vec(map_rows).map_err(|e| {
+            let line = e.line + first_line - 1;
+            e.on_line(line)
+        })?;
+        maps.push(map);
+        first_line += rows + 1;
+    }
+    Ok(maps)
+}
+
+pub fn solution1(input: &str) -> Solution {
+    let lines: Vec<&str> = input.split("\n\n").collect();
     // let input_data = fs::read_to_string("src/day13/test.txt").expect("Unable to read file.");
     // let lines: Vec<&str> = input_data.split("\r\n\r\n").collect();
 
     // Read data
-    let mut maps = Vec::<Map>::new();
-    for line in lines.iter() {
-        let map_rows: Vec<&str> = line.split('\n').collect();
-        let map = Map::from_vec(map_rows);
-        maps.push(map);
-    }
+    let maps = read_maps(&lines)?;
+    bench::parsed();
 
     // Find symmetry scores
     let total: u64 = maps.iter().map(|s| s.find_symmetry()).sum();
 
-    submit!(1, total);
+    Ok(total.into())
 }
 
-#[aocd(2023, 13)]
-pub fn solution2() {
-    let input_data = input!();
-    let lines: Vec<&str> = input_data.split("\n\n").collect();
+pub fn solution2(input: &str) -> Solution {
+    let lines: Vec<&str> = input.split("\n\n").collect();
     // let input_data = fs::read_to_string("src/day13/test.txt").expect("Unable to read file.");
     // let lines: Vec<&str> = input_data.split("\r\n\r\n").collect();
 
     // Read data
-    let mut maps = Vec::<Map>::new();
-    for line in lines.iter() {
-        let map_rows: Vec<&str> = line.split('\n').collect();
-        let map = Map::from_vec(map_rows);
-        maps.push(map);
-    }
+    let maps = read_maps(&lines)?;
+    bench::parsed();
 
     // Find symmetry scores
     let total: u64 = maps.iter().map(|s| s.find_near_symmetry()).sum();
 
-    submit!(2, total);
+    Ok(total.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::answer::Answer;
+
+    const EXAMPLE: &str = "\
+#.##..##.
+..#.##.#.
+##......#
+##......#
+..#.##.#.
+..##..##.
+#.##..##.
+
+#...##..#
+#....#..#
+..##..###
+#####.##.
+#####.##.
+..##..###
+#....#..#";
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(solution1(EXAMPLE), Ok(Answer::Int(405)));
+    }
+
+    #[test]
+    fn example_part2() {
+        assert_eq!(solution2(EXAMPLE), Ok(Answer::Int(400)));
+    }
+
+    #[test]
+    fn malformed_input() {
+        let error = solution1("#.#\n..#\n\n##.\n#o#").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!((error.line, error.column, error.text.as_str()), (5, 2, "o"));
+        let error = solution2("#.#\n..#\n\n##.\n#.#.").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!(
+            (error.line, error.reason.as_str()),
+            (5, "expected a row of 3 tiles")
+        );
+        let error = solution1("#.#\n\n\n##.").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!(
+            (error.line, error.reason.as_str()),
+            (3, "expected a row of tiles")
+        );
+    }
 }
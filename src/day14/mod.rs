@@ -1,42 +1,39 @@
-use aocd::*;
+use crate::answer::Solution;
+use crate::bench;
+use crate::grid;
+use crate::parse::ParseError;
 use std::{
     collections::hash_map::DefaultHasher,
     hash::{Hash, Hasher},
 };
 
+const DAY: u8 = 14;
+
 pub struct Grid {
-    pub data: Vec<Vec<char>>,
+    pub data: grid::Grid<char>,
 }
 
 impl Grid {
-    pub fn from_vec_string(v: Vec<String>) -> Grid {
-        let data: Vec<Vec<char>> = v.iter().map(|s| s.chars().collect::<Vec<char>>()).collect();
-        Grid { data }
+    pub fn from_vec_string(v: Vec<String>) -> Result<Grid, ParseError> {
+        // Tilting sorts the tiles between cube rocks, which only works for these
+        let data = grid::Grid::try_parse(&v.join("\n"), DAY, "expected '.', 'O' or '#'", |c| {
+            matches!(c, '.' | 'O' | '#').then_some(c)
+        })?;
+        Ok(Grid { data })
     }
 
     pub fn rotate_clockwise(&mut self) {
-        let mut new_data: Vec<Vec<char>> = Vec::new();
-        for j in 0..self.data[0].len() {
-            let mut new_row = Vec::<char>::new();
-            for row in self.data.iter().rev() {
-                new_row.push(row[j]);
-            }
-            new_data.push(new_row);
-        }
-        self.data = new_data;
+        self.data = self.data.rotate_clockwise();
     }
 
     pub fn display(&self) {
-        for line in self.data.iter() {
-            let s = line.iter().collect::<String>();
-            println!("{}", s);
-        }
+        print!("{}", self.data);
     }
 
     pub fn tilt(&mut self) {
         let tilted_data: Vec<Vec<char>> = self
             .data
-            .iter()
+            .rows()
             .map(|v| {
                 let s: String = v.iter().collect();
                 let mut lines = s
@@ -52,12 +49,12 @@ impl Grid {
                 lines
             })
             .collect();
-        self.data = tilted_data;
+        self.data = grid::Grid::from_rows(tilted_data);
     }
 
     pub fn score_load(&self) -> u64 {
         let mut total: u64 = 0;
-        for row in self.data.iter() {
+        for row in self.data.rows() {
             for (i, c) in row.iter().enumerate() {
                 if *c == 'O' {
                     total += i as u64 + 1;
@@ -85,22 +82,20 @@ impl Grid {
     }
 }
 
-#[aocd(2023, 14)]
-pub fn solution1() {
-    let input_data = input!();
+pub fn solution1(input: &str) -> Solution {
     //let input_data = fs::read_to_string("src/day14/test.txt").expect("Unable to read file.");
-    let lines: Vec<String> = input_data.split('\n').map(|s| s.to_string()).collect();
-    let mut grid = Grid::from_vec_string(lines);
+    let lines: Vec<String> = input.split('\n').map(|s| s.to_string()).collect();
+    let mut grid = Grid::from_vec_string(lines)?;
+    bench::parsed();
     grid.rotate_clockwise();
     grid.tilt();
-    submit!(1, grid.score_load());
+    Ok(grid.score_load().into())
 }
 
-#[aocd(2023, 14)]
-pub fn solution2() {
-    let input_data = input!();
-    let lines: Vec<String> = input_data.split('\n').map(|s| s.to_string()).collect();
-    let mut grid = Grid::from_vec_string(lines.clone());
+pub fn solution2(input: &str) -> Solution {
+    let lines: Vec<String> = input.split('\n').map(|s| s.to_string()).collect();
+    let mut grid = Grid::from_vec_string(lines.clone())?;
+    bench::parsed();
 
     // Store hashes of grid after each cycle to identify
     let mut hashes: Vec<u64> = Vec::new();
@@ -134,12 +129,53 @@ pub fn solution2() {
     let n_cycles = initial_cycle + (n - initial_cycle) % cycle_duration;
 
     // Put it through abbreviated cycles
-    let mut grid = Grid::from_vec_string(lines);
+    let mut grid = Grid::from_vec_string(lines)?;
     for _ in 1..=n_cycles {
         grid.cycle();
     }
 
     // Rotate one more time to get it in the right position
     grid.rotate_clockwise();
-    submit!(2, grid.score_load());
+    Ok(grid.score_load().into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::answer::Answer;
+
+    const EXAMPLE: &str = "\
+O....#....
+O.OO#....#
+.....##...
+OO.#O....O
+.O.....O#.
+O.#..O.#.#
+..O..#O..O
+.......O..
+#....###..
+#OO..#....";
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(solution1(EXAMPLE), Ok(Answer::Int(136)));
+    }
+
+    #[test]
+    fn example_part2() {
+        assert_eq!(solution2(EXAMPLE), Ok(Answer::Int(64)));
+    }
+
+    #[test]
+    fn malformed_input() {
+        let error = solution1("O..#\n.#0.").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!((error.line, error.column, error.text.as_str()), (2, 3, "0"));
+        let error = solution2("O..#\n.#O.\n..").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!(
+            (error.line, error.reason.as_str()),
+            (3, "expected a row of 4 tiles")
+        );
+    }
 }
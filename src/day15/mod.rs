@@ -1,20 +1,43 @@
-use aocd::*;
+use crate::answer::Solution;
+use crate::bench;
+use crate::parse::{self, ParseError};
 use std::str::FromStr;
 
+const DAY: u8 = 15;
+
+/// Parse the comma separated steps of the initialization sequence
+fn parse_steps<T: FromStr<Err = ParseError>>(input: &str) -> Result<Vec<T>, ParseError> {
+    let lines = parse::lines(input, |line| {
+        parse::fields(line, ',')
+            .map(|(column, step)| T::from_str(step).map_err(|e| e.shifted(column - 1)))
+            .collect::<Result<Vec<T>, ParseError>>()
+    })?;
+    Ok(lines.into_iter().flatten().collect())
+}
+
 #[derive(Debug, Clone)]
 pub struct Sequence {
     original: String,
     values: Vec<u8>,
 }
 
-#[derive(Debug, PartialEq, Eq)]
-pub struct ParseSequenceError;
-
 impl FromStr for Sequence {
-    type Err = ParseSequenceError;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let values: Vec<u8> = s.chars().map(|c| c as u8).collect();
+        if s.is_empty() {
+            return Err(ParseError::new(DAY, 1, s, "expected a step"));
+        }
+        // The hash is defined on ASCII codes
+        if let Some((i, c)) = s.chars().enumerate().find(|(_, c)| !c.is_ascii()) {
+            return Err(ParseError::new(
+                DAY,
+                i + 1,
+                &c.to_string(),
+                "expected an ASCII character",
+            ));
+        }
+        let values: Vec<u8> = s.bytes().collect();
         Ok(Sequence {
             original: s.to_owned(),
             values,
@@ -33,16 +56,13 @@ impl Sequence {
     }
 }
 
-#[aocd(2023, 15)]
-pub fn solution1() {
-    let sequences: Vec<Sequence> = input!()
-        .split(',')
-        .map(|s| Sequence::from_str(s.trim()).unwrap())
-        .collect();
+pub fn solution1(input: &str) -> Solution {
+    let sequences: Vec<Sequence> = parse_steps(input)?;
+    bench::parsed();
 
     let total: u64 = sequences.iter().map(|seq| seq.hash()).sum();
 
-    submit!(1, total);
+    Ok(total.into())
 }
 
 #[derive(Debug, Clone)]
@@ -52,35 +72,31 @@ pub struct Lens {
     operator: char,
 }
 
-#[derive(Debug, PartialEq, Eq)]
-pub struct ParseStepError;
-
 impl FromStr for Lens {
-    type Err = ParseStepError;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.contains('=') {
-            let mut s_vec: Vec<char> = s.chars().collect();
-            let focal_length = s_vec.pop().unwrap().to_string().parse::<u8>().unwrap();
-            let operator = s_vec.pop().unwrap(); // Remove operator
-            let s = s_vec.iter().collect::<String>();
-            let label = Sequence::from_str(s.as_str()).unwrap();
+        if let Some((label, focal_length)) = s.split_once('=') {
+            // The focal length comes after the label and the operator
+            let column = label.chars().count() + 2;
             Ok(Lens {
-                label,
-                focal_length,
-                operator,
+                label: Sequence::from_str(label)?,
+                focal_length: parse::number(DAY, column, focal_length)?,
+                operator: '=',
             })
-        } else {
-            let mut s_vec: Vec<char> = s.chars().collect();
-            let focal_length: u8 = 0;
-            let operator = s_vec.pop().unwrap();
-            let s = s_vec.iter().collect::<String>();
-            let label = Sequence::from_str(s.as_str()).unwrap();
+        } else if let Some(label) = s.strip_suffix('-') {
             Ok(Lens {
-                label,
-                focal_length,
-                operator,
+                label: Sequence::from_str(label)?,
+                focal_length: 0,
+                operator: '-',
             })
+        } else {
+            Err(ParseError::new(
+                DAY,
+                1,
+                s,
+                "expected a step with '=' or '-'",
+            ))
         }
     }
 }
@@ -122,7 +138,7 @@ impl LensBox {
             }
         } else if lens.operator == '=' {
             let mut already_exists = false;
-            'search: for (_, l) in self.lenses.iter_mut().enumerate() {
+            'search: for l in self.lenses.iter_mut() {
                 if l.label.original == lens.label.original {
                     already_exists = true;
                     l.focal_length = lens.focal_length;
@@ -136,8 +152,10 @@ impl LensBox {
     }
 }
 
-#[aocd(2023, 15)]
-pub fn solution2() {
+pub fn solution2(input: &str) -> Solution {
+    let lenses: Vec<Lens> = parse_steps(input)?;
+    bench::parsed();
+
     // Create boxes
     let mut boxes: Vec<LensBox> = Vec::new();
     for i in 0..=255 {
@@ -145,13 +163,56 @@ pub fn solution2() {
     }
 
     // Move lenses into boxes
-    for s in input!().split(',') {
-        let lens = Lens::from_str(s.trim()).unwrap();
+    for lens in lenses {
         let box_no = lens.label.hash() as usize;
         boxes[box_no].handle_lens(lens);
     }
 
     // Score lenses
     let total: u64 = boxes.iter().map(|b| b.score()).sum();
-    submit!(2, total);
+    Ok(total.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::answer::Answer;
+
+    const EXAMPLE: &str = "\
+rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7";
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(solution1(EXAMPLE), Ok(Answer::Int(1320)));
+    }
+
+    #[test]
+    fn example_part2() {
+        assert_eq!(solution2(EXAMPLE), Ok(Answer::Int(145)));
+    }
+
+    #[test]
+    fn malformed_input() {
+        let error = solution2("rn=1,cm-,qp=x,cm=2")
+            .unwrap_err()
+            .into_parse_error()
+            .unwrap();
+        assert_eq!(
+            (error.line, error.column, error.text.as_str()),
+            (1, 13, "x")
+        );
+        let error = solution2("rn=1,cm,qp=3")
+            .unwrap_err()
+            .into_parse_error()
+            .unwrap();
+        assert_eq!(
+            (error.line, error.column, error.text.as_str()),
+            (1, 6, "cm")
+        );
+        let error = solution1("rn=1,,qp=3")
+            .unwrap_err()
+            .into_parse_error()
+            .unwrap();
+        assert_eq!((error.line, error.column), (1, 6));
+    }
 }
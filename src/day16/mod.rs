@@ -1,6 +1,12 @@
-use aocd::*;
+use crate::answer::Solution;
+use crate::bench;
+use crate::geometry::{Coord, Direction};
+use crate::grid;
+use crate::parse::ParseError;
 use std::hash::Hash;
 
+const DAY: u8 = 16;
+
 #[derive(Debug, Hash)]
 pub struct Tile {
     tile_type: char,
@@ -9,12 +15,13 @@ pub struct Tile {
 }
 
 impl Tile {
-    pub fn new(tile_type: char) -> Tile {
-        Tile {
+    /// Empty space or a mirror or splitter, or `None` for any other character
+    pub fn new(tile_type: char) -> Option<Tile> {
+        matches!(tile_type, '.' | '/' | '\\' | '|' | '-').then(|| Tile {
             tile_type,
             energized: false,
             history: Vec::new(),
-        }
+        })
     }
 
     pub fn energize(&mut self) {
@@ -97,12 +104,19 @@ impl Tile {
 
 #[derive(Hash)]
 pub struct Grid {
-    grid: Vec<Vec<Tile>>,
+    grid: grid::Grid<Tile>,
 }
 
 impl Grid {
+    pub fn from_string(s: &str) -> Result<Grid, ParseError> {
+        let expected = "expected '.' or a mirror or splitter";
+        Ok(Grid {
+            grid: grid::Grid::try_parse(s, DAY, expected, Tile::new)?,
+        })
+    }
+
     pub fn display(&self) {
-        for row in self.grid.iter() {
+        for row in self.grid.rows() {
             let s: String = row
                 .iter()
                 .map(|t| if t.energized { '#' } else { t.tile_type })
@@ -111,22 +125,12 @@ impl Grid {
         }
     }
 
-    pub fn tile(&mut self, position: (i32, i32)) -> Option<&mut Tile> {
-        if !self.out_of_bounds(position) {
-            Some(&mut self.grid[position.0 as usize][position.1 as usize])
-        } else {
-            None
-        }
-    }
-
-    pub fn out_of_bounds(&self, position: (i32, i32)) -> bool {
-        (position.0 < 0)
-            || (position.0 >= self.grid.len() as i32)
-            || (position.1 < 0)
-            || (position.1 >= self.grid[0].len() as i32)
+    pub fn tile(&mut self, position: Coord) -> Option<&mut Tile> {
+        let position = self.grid.position_of(position)?;
+        self.grid.get_mut(position)
     }
 
-    pub fn beam_photon_at(&mut self, position: (i32, i32), direction: Direction) {
+    pub fn beam_photon_at(&mut self, position: Coord, direction: Direction) {
         // Create first photon
         let initial_photon = Photon {
             position,
@@ -156,48 +160,25 @@ impl Grid {
     }
 
     pub fn score(&self) -> u64 {
-        let mut score: u64 = 0;
-        for row in self.grid.iter() {
-            for tile in row.iter() {
-                if tile.energized {
-                    score += 1;
-                }
-            }
-        }
-        score
+        self.grid.iter().filter(|tile| tile.energized).count() as u64
     }
 
     pub fn clear(&mut self) {
-        for row in self.grid.iter_mut() {
-            for tile in row.iter_mut() {
-                tile.clear();
-            }
+        for tile in self.grid.iter_mut() {
+            tile.clear();
         }
     }
 }
 
-#[derive(Hash, Debug, Clone, Copy, PartialEq, Eq)]
-pub enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
-}
-
 #[derive(Hash, Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Photon {
-    position: (i32, i32),
+    position: Coord,
     direction: Direction,
 }
 
 impl Photon {
-    pub fn trajectory(&self) -> (i32, i32) {
-        match self.direction {
-            Direction::Up => (self.position.0 - 1, self.position.1),
-            Direction::Down => (self.position.0 + 1, self.position.1),
-            Direction::Left => (self.position.0, self.position.1 - 1),
-            Direction::Right => (self.position.0, self.position.1 + 1),
-        }
+    pub fn trajectory(&self) -> Coord {
+        self.position.step(self.direction)
     }
 
     pub fn travel(&mut self, grid: &mut Grid) -> bool {
@@ -210,70 +191,54 @@ impl Photon {
     }
 }
 
-#[aocd(2023, 16, "src/day16/test.txt")]
-pub fn solution1() {
-    let input_data: Vec<Vec<Tile>> = input!()
-        .split('\n')
-        .map(|s| s.trim().chars().map(Tile::new).collect::<Vec<Tile>>())
-        .collect();
-
+pub fn solution1(input: &str) -> Solution {
     // Initialize grid
-    let mut grid = Grid { grid: input_data };
-
-    // Display grid
-    grid.display();
+    let mut grid = Grid::from_string(input)?;
+    bench::parsed();
 
     // Shoot a photon
-    grid.beam_photon_at((0, -1), Direction::Right);
+    grid.beam_photon_at(Coord::new(0, -1), Direction::Right);
 
     // Submit score
-    submit!(1, grid.score());
+    Ok(grid.score().into())
 }
 
-#[aocd(2023, 16)]
-pub fn solution2() {
-    let input_data: Vec<Vec<Tile>> = input!()
-        .split('\n')
-        .map(|s| s.trim().chars().map(Tile::new).collect::<Vec<Tile>>())
-        .collect();
-
+pub fn solution2(input: &str) -> Solution {
     // Initialize grid
-    let mut grid = Grid { grid: input_data };
-
-    // Display grid
-    grid.display();
+    let mut grid = Grid::from_string(input)?;
+    bench::parsed();
 
     // Get rows and columns
-    let nrows = grid.grid.len();
-    let ncols = grid.grid[0].len();
+    let nrows = grid.grid.nrows();
+    let ncols = grid.grid.ncols();
 
     // Store energy scores for each grid configuration
     let mut scores: Vec<u64> = Vec::new();
 
     // From top
     for i in 0..ncols {
-        grid.beam_photon_at((-1, i as i32), Direction::Down);
+        grid.beam_photon_at(Coord::new(-1, i as i64), Direction::Down);
         scores.push(grid.score());
         grid.clear();
     }
 
     // From left
     for i in 0..nrows {
-        grid.beam_photon_at((i as i32, -1), Direction::Right);
+        grid.beam_photon_at(Coord::new(i as i64, -1), Direction::Right);
         scores.push(grid.score());
         grid.clear();
     }
 
     // From right
     for i in 0..nrows {
-        grid.beam_photon_at((i as i32, ncols as i32), Direction::Left);
+        grid.beam_photon_at(Coord::new(i as i64, ncols as i64), Direction::Left);
         scores.push(grid.score());
         grid.clear();
     }
 
     // From bottom
     for i in 0..ncols {
-        grid.beam_photon_at((nrows as i32, i as i32), Direction::Up);
+        grid.beam_photon_at(Coord::new(nrows as i64, i as i64), Direction::Up);
         scores.push(grid.score());
         grid.clear();
     }
@@ -282,5 +247,42 @@ pub fn solution2() {
     let max_score = *scores.iter().max().unwrap();
 
     // Submit score
-    submit!(2, max_score);
+    Ok(max_score.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::answer::Answer;
+
+    const EXAMPLE: &str = r".|...\....
+|.-.\.....
+.....|-...
+........|.
+..........
+.........\
+..../.\\..
+.-.-/..|..
+.|....-|.\
+..//.|....";
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(solution1(EXAMPLE), Ok(Answer::Int(46)));
+    }
+
+    #[test]
+    fn example_part2() {
+        assert_eq!(solution2(EXAMPLE), Ok(Answer::Int(51)));
+    }
+
+    #[test]
+    fn malformed_input() {
+        let error = solution1(".|..\n.+..").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!((error.line, error.column, error.text.as_str()), (2, 2, "+"));
+        let error = solution2("").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!(error.reason, "expected a grid");
+    }
 }
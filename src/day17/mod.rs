@@ -1,237 +1,202 @@
-use aocd::*;
-use std::collections::HashMap;
-
-/// Manhattan distance heuristic
-fn heuristic(a: (i32, i32), b: (i32, i32)) -> i32 {
-    (a.1 - b.1).abs() + (a.0 - b.0).abs()
+use crate::answer::{Solution, SolveError};
+use crate::bench;
+use crate::geometry::{Coord, Direction};
+use crate::grid;
+use crate::parse::ParseError;
+use crate::search;
+
+const DAY: u8 = 17;
+
+/// Fewest and most tiles a crucible can move in a straight line before it has to turn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RunLimits {
+    pub min: u8,
+    pub max: u8,
 }
 
-/// Given a node, and a hashmap of nodes and their parents, backtrack into the full
-/// path that arrives at the given node.
-fn reconstruct_path(came_from: HashMap<(i32, i32), (i32, i32)>, current: Node) -> Vec<(i32, i32)> {
-    let mut current_position = current.position;
-    let mut path = vec![current_position];
-    while came_from.contains_key(&current_position) {
-        current_position = *came_from.get(&current_position).unwrap();
-        path.push(current_position);
-    }
-    path.into_iter().rev().collect()
+pub const CRUCIBLE: RunLimits = RunLimits { min: 1, max: 3 };
+pub const ULTRA_CRUCIBLE: RunLimits = RunLimits { min: 4, max: 10 };
+
+/// Position of the crucible, along with the direction it arrived from and how many tiles
+/// it has moved in that direction without turning
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Crucible {
+    pub position: Coord,
+    pub direction: Option<Direction>,
+    pub run: u8,
 }
 
 /// Store the maze as a grid. Each tile has an associated "heat loss" value.
 #[derive(Debug, Clone)]
 pub struct Grid {
-    grid: Vec<Vec<u8>>,
+    grid: grid::Grid<u8>,
 }
 
 impl Grid {
-    /// Display the grid with the path highlighted
-    pub fn display(&self, path: &[(i32, i32)]) {
-        let mut loss: u64 = 0;
-        for (i, row) in self.grid.iter().enumerate() {
-            let mut row_str = String::new();
-            for (j, value) in row.iter().enumerate() {
-                if path.contains(&(i as i32, j as i32)) {
-                    loss += *value as u64;
-                    row_str += format!("[{value}]").to_string().as_str();
-                } else {
-                    row_str += format!(".{value}.").to_string().as_str();
-                }
+    pub fn from_string(s: &str) -> Result<Grid, ParseError> {
+        let grid = grid::Grid::try_parse(s, DAY, "expected a digit", |c| {
+            c.to_digit(10).map(|d| d as u8)
+        })?;
+        Ok(Grid { grid })
+    }
+
+    /// The grid as text, with the tiles of the path replaced by the direction they are entered in
+    pub fn draw(&self, path: &[Crucible]) -> String {
+        let mut tiles = self
+            .grid
+            .map(|value| char::from_digit(*value as u32, 10).unwrap());
+        for crucible in path {
+            if let Some(direction) = crucible.direction {
+                let position = self.grid.position_of(crucible.position).unwrap();
+                tiles[position] = direction.arrow();
             }
-            println!("{}", row_str);
         }
-        println!("Heat loss: {}", loss);
+        tiles.to_string()
     }
-}
-
-/// Vector of Nodes that is always sorted by f-score
-#[derive(Default)]
-pub struct NodeStack {
-    nodes: Vec<Node>,
-}
 
-impl NodeStack {
-    pub fn add_node(&mut self, node: Node, f_scores: &HashMap<(i32, i32), i32>) {
-        self.nodes.push(node);
-        self.nodes.sort_by(|a, b| {
-            let a_score = *f_scores.get(&a.position).unwrap_or(&(i32::MAX / 2));
-            let b_score = *f_scores.get(&b.position).unwrap_or(&(i32::MAX / 2));
-            b_score.cmp(&a_score)
-        });
+    /// Display the grid with the path drawn in
+    pub fn display(&self, path: &[Crucible]) {
+        print!("{}", self.draw(path));
+        println!("Heat loss: {}", self.heat_loss(path));
     }
 
-    pub fn is_empty(&self) -> bool {
-        self.nodes.is_empty()
+    /// Total heat loss along a path. The starting tile is not entered, so it does not count.
+    pub fn heat_loss(&self, path: &[Crucible]) -> u64 {
+        path.iter()
+            .skip(1)
+            .map(|crucible| *self.grid.at(crucible.position).unwrap() as u64)
+            .sum()
     }
 
-    /// Returns the node with the minimum f-score
-    pub fn pop(&mut self) -> Node {
-        self.nodes.pop().unwrap()
+    /// States the crucible can move to next, along with the heat lost by entering the tile.
+    /// It can't reverse, can only turn once it has moved far enough in a straight line,
+    /// and has to turn before it moves too far.
+    fn successors(&self, crucible: &Crucible, limits: RunLimits) -> Vec<(Crucible, u64)> {
+        let mut successors = Vec::new();
+        for direction in Direction::ALL {
+            let straight = crucible.direction == Some(direction);
+            if crucible.direction == Some(direction.reverse())
+                || (straight && crucible.run == limits.max)
+                || (!straight && crucible.direction.is_some() && crucible.run < limits.min)
+            {
+                continue;
+            }
+            let position = crucible.position.step(direction);
+            if let Some(loss) = self.grid.at(position) {
+                let run = if straight { crucible.run + 1 } else { 1 };
+                let next = Crucible {
+                    position,
+                    direction: Some(direction),
+                    run,
+                };
+                successors.push((next, *loss as u64));
+            }
+        }
+        successors
     }
 
-    pub fn contains(&self, position: (i32, i32)) -> bool {
-        self.nodes.iter().any(|n| n.position == position)
+    /// Least heat loss from the top left to the bottom right, and the path that achieves it.
+    /// The crucible has to move at least the minimum run before it can stop at the end.
+    pub fn least_heat_loss(&self, limits: RunLimits) -> Option<(u64, Vec<Crucible>)> {
+        let start = Crucible {
+            position: Coord::ORIGIN,
+            direction: None,
+            run: 0,
+        };
+        let end = Coord::new(self.grid.nrows() as i64 - 1, self.grid.ncols() as i64 - 1);
+        search::a_star(
+            start,
+            |crucible| self.successors(crucible, limits),
+            |crucible| crucible.position.manhattan(end) as u64,
+            |crucible| crucible.position == end && crucible.run >= limits.min,
+        )
     }
 }
 
-/// Struct to contain a position
-pub struct Node {
-    position: (i32, i32),
-}
-
-impl Node {
-    /// Helper function for calculating positions of adjacent nodes
-    pub fn adjacent(&self, i_limit: usize, j_limit: usize) -> Vec<(i32, i32)> {
-        let i_limit = i_limit as i32;
-        let j_limit = j_limit as i32;
-        let candidates = [
-            (self.position.0, self.position.1 - 1),
-            (self.position.0, self.position.1 + 1),
-            (self.position.0 - 1, self.position.1),
-            (self.position.0 + 1, self.position.1),
-        ];
-        let mut adjacent = Vec::<(i32, i32)>::new();
-        for c in candidates {
-            if c.0 >= 0 && c.0 < i_limit && c.1 >= 0 && c.1 < j_limit {
-                adjacent.push(c);
-            }
-        }
-        adjacent
-    }
+pub fn solution1(input: &str) -> Solution {
+    let maze = Grid::from_string(input)?;
+    bench::parsed();
+    let (loss, _) = maze
+        .least_heat_loss(CRUCIBLE)
+        .ok_or_else(|| SolveError::unsolved(DAY, "the crucible can't reach the bottom right"))?;
+    Ok(loss.into())
 }
 
-/// Enum to describe which direction the path is going for a given tile
-#[derive(Debug, PartialEq, Eq)]
-pub enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
-    None,
+pub fn solution2(input: &str) -> Solution {
+    let maze = Grid::from_string(input)?;
+    bench::parsed();
+    let (loss, _) = maze
+        .least_heat_loss(ULTRA_CRUCIBLE)
+        .ok_or_else(|| SolveError::unsolved(DAY, "the crucible can't reach the bottom right"))?;
+    Ok(loss.into())
 }
 
-/// Helper function to determine the direction from a position and a hashmap of node-parents
-fn direction(position: (i32, i32), came_from: &HashMap<(i32, i32), (i32, i32)>) -> Direction {
-    if let Some(came_from_position) = came_from.get(&position) {
-        if came_from_position.0 < position.0 {
-            Direction::Down
-        } else if came_from_position.0 > position.0 {
-            Direction::Up
-        } else if came_from_position.1 < position.1 {
-            Direction::Right
-        } else if came_from_position.1 > position.1 {
-            Direction::Left
-        } else {
-            unreachable!("Impossible position configuration.")
-        }
-    } else {
-        Direction::None
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::answer::Answer;
+
+    const EXAMPLE: &str = "\
+2413432311323
+3215453535623
+3255245654254
+3446585845452
+4546657867536
+1438598798454
+4457876987766
+3637877979653
+4654967986887
+4564679986453
+1224686865563
+2546548887735
+4322674655533";
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(solution1(EXAMPLE), Ok(Answer::Int(102)));
     }
-}
 
-/// Get the number of times the path has gone in the same direction up until a given point
-fn same_direction_count(position: (i32, i32), came_from: &HashMap<(i32, i32), (i32, i32)>) -> i32 {
-    let mut current_position = position;
-    let mut came_from_position = if let Some(pos) = came_from.get(&position) {
-        *pos
-    } else {
-        return 1;
-    };
-    let mut count = 1;
-    'chain: while direction(current_position, came_from) == direction(came_from_position, came_from)
-    {
-        count += 1;
-        current_position = came_from_position;
-        came_from_position = if let Some(pos) = came_from.get(&came_from_position) {
-            *pos
-        } else {
-            break 'chain;
-        }
+    #[test]
+    fn example_part2() {
+        assert_eq!(solution2(EXAMPLE), Ok(Answer::Int(94)));
+        let unfortunate = "\
+111111111111
+999999999991
+999999999991
+999999999991
+999999999991";
+        assert_eq!(solution2(unfortunate), Ok(Answer::Int(71)));
     }
-    count
-}
-
-/// A* path-finding algorithm
-fn a_star(maze: &Grid, start: (i32, i32), end: (i32, i32)) -> Vec<(i32, i32)> {
-    let i_limit = maze.grid.len();
-    let j_limit = maze.grid[0].len();
-
-    let mut f_scores = HashMap::<(i32, i32), i32>::new();
-    f_scores.insert(start, heuristic(start, end));
-
-    let mut open_set = NodeStack::default();
-    open_set.add_node(Node { position: start }, &f_scores);
-
-    let mut came_from = HashMap::<(i32, i32), (i32, i32)>::new();
-
-    let mut g_scores = HashMap::<(i32, i32), i32>::new();
-    g_scores.insert(start, 0);
-
-    while !open_set.is_empty() {
-        let current_node = open_set.pop();
 
-        if current_node.position == end {
-            return reconstruct_path(came_from, current_node);
-        }
-
-        let neighbors = current_node.adjacent(i_limit, j_limit);
-
-        'consideration: for neighbor in neighbors.iter() {
-            let current_g_score = *g_scores
-                .get(&current_node.position)
-                .unwrap_or(&(i32::MAX / 2));
-            let edge_weight = maze.grid[neighbor.0 as usize][neighbor.1 as usize] as i32;
-            let tentative_g_score = current_g_score + edge_weight;
-            let neighbor_g_score = *g_scores.get(neighbor).unwrap_or(&(i32::MAX / 2));
-            if tentative_g_score < neighbor_g_score {
-                let prior_came_from_option = came_from.insert(*neighbor, current_node.position);
-                if same_direction_count(*neighbor, &came_from) > 3 {
-                    if let Some(prior) = prior_came_from_option {
-                        came_from.insert(*neighbor, prior);
-                    } else {
-                        came_from.remove(neighbor);
-                    }
-                    continue 'consideration;
-                }
-                g_scores.insert(*neighbor, tentative_g_score);
-                let h = heuristic(*neighbor, end);
-                f_scores.insert(*neighbor, tentative_g_score + h);
-                if !open_set.contains(*neighbor) {
-                    open_set.add_node(
-                        Node {
-                            position: *neighbor,
-                        },
-                        &f_scores,
-                    )
-                }
-            }
-        }
+    #[test]
+    fn malformed_input() {
+        let error = solution1("241\n32a").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!((error.line, error.column, error.text.as_str()), (2, 3, "a"));
+        let error = solution2("241\n3215").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!(
+            (error.line, error.reason.as_str()),
+            (2, "expected a row of 3 tiles")
+        );
+        assert_eq!(
+            solution2("11\n11"),
+            Err(SolveError::unsolved(
+                DAY,
+                "the crucible can't reach the bottom right"
+            ))
+        );
     }
 
-    Vec::new()
-}
-
-#[aocd(2023, 17, "src/day17/test.txt")]
-pub fn solution1() {
-    let input_data: Vec<Vec<u8>> = input!()
-        .split('\n')
-        .map(|s| {
-            let nodes = s
-                .trim()
-                .chars()
-                .map(|c| c.to_string().parse::<u8>().unwrap())
-                .collect();
-            nodes
-        })
-        .collect();
-
-    let maze = Grid { grid: input_data };
-    let i_limit = maze.grid.len() as i32;
-    let j_limit = maze.grid[0].len() as i32;
-
-    let path = a_star(&maze, (0, 0), (i_limit - 1, j_limit - 1));
-    maze.display(&path);
+    #[test]
+    fn path() {
+        let maze = Grid::from_string(EXAMPLE).unwrap();
+        let (loss, path) = maze.least_heat_loss(CRUCIBLE).unwrap();
+        assert_eq!(maze.heat_loss(&path), loss);
+        assert_eq!(path[0].position, Coord::ORIGIN);
+        assert!(path.iter().all(|crucible| crucible.run <= CRUCIBLE.max));
+        let drawing = maze.draw(&path);
+        assert_eq!(drawing.lines().count(), 13);
+        assert!(drawing.starts_with("2>>34^>>>1323"));
+    }
 }
-
-#[aocd(2023, 17)]
-pub fn solution2() {}
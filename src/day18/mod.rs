@@ -1,263 +1,168 @@
-use aocd::*;
+use crate::answer::Solution;
+use crate::bench;
+use crate::geometry::Direction;
+use crate::parse::{self, ParseError};
+use crate::polygon::Polygon;
+
+const DAY: u8 = 18;
+
+/// How to read an instruction of the dig plan
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decoding {
+    /// Direction and length as written, e.g. `R 6`
+    Plain,
+    /// Direction and length hidden in the color: five hex digits of length, then
+    /// one digit of direction
+    Color,
+}
 
-#[derive(Debug)]
-pub struct Command<'a> {
-    direction: char,
-    length: usize,
-    color: &'a str,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Command {
+    direction: Direction,
+    length: i64,
 }
 
-impl<'a> Command<'a> {
-    fn from_str(s: &str) -> Command {
-        let data: Vec<&str> = s.split(' ').collect();
-        Command {
-            direction: data[0].chars().next().unwrap(),
-            length: data[1].parse::<usize>().unwrap(),
-            color: data[2],
+impl Command {
+    /// Parse a line of the plan such as `R 6 (#70c710)`
+    fn from_str(s: &str, decoding: Decoding) -> Result<Command, ParseError> {
+        let data: Vec<(usize, &str)> = parse::fields(s, ' ').collect();
+        if data.len() != 3 {
+            let reason = "expected a direction, a length and a color";
+            return Err(ParseError::new(DAY, 1, s, reason));
         }
-    }
-
-    fn extract_direction_from_color(&self) -> char {
-        let color_vec = self.color.chars().collect::<Vec<char>>();
-        let nchars = color_vec.len();
-        // Second to last character is the direction digit (parentheses is last char)
-        match color_vec[nchars - 2] {
-            '0' => 'R',
-            '1' => 'D',
-            '2' => 'L',
-            '3' => 'U',
-            _ => unreachable!("Undefined color."),
+        match decoding {
+            Decoding::Plain => {
+                let (column, direction) = data[0];
+                let mut chars = direction.chars();
+                let direction = match (chars.next(), chars.next()) {
+                    (Some(c), None) => Direction::from_char(c),
+                    _ => None,
+                }
+                .ok_or_else(|| ParseError::new(DAY, column, direction, "expected U, D, L or R"))?;
+                let (column, length) = data[1];
+                let length: u32 = parse::number(DAY, column, length)?;
+                Ok(Command {
+                    direction,
+                    length: length.into(),
+                })
+            }
+            Decoding::Color => {
+                let (column, color) = data[2];
+                Command::from_color(color).map_err(|e| e.shifted(column - 1))
+            }
         }
     }
 
-    fn extract_length_from_color(&self) -> usize {
-        let mut color_vec = self.color.chars().collect::<Vec<char>>();
-        // Remove leading parentheses
-        color_vec.remove(0);
-        // Remove hashtag
-        color_vec.remove(0);
-        // Remove last parentheses
-        color_vec.pop();
-        // Remove last digit
-        color_vec.pop();
-        // Convert to str
-        let color_str = color_vec.iter().collect::<String>();
-        // Convert hex string to usize
-        usize::from_str_radix(color_str.as_str(), 16).unwrap()
+    /// Decode a color of the form `(#70c710)`
+    fn from_color(color: &str) -> Result<Command, ParseError> {
+        // Strip the parentheses and hashtag
+        let hex = color
+            .strip_prefix("(#")
+            .and_then(|hex| hex.strip_suffix(')'))
+            .filter(|hex| hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+            .ok_or_else(|| ParseError::new(DAY, 1, color, "expected a color such as (#70c710)"))?;
+        let (length, direction) = hex.split_at(hex.len() - 1);
+        let direction = match direction {
+            "0" => Direction::Right,
+            "1" => Direction::Down,
+            "2" => Direction::Left,
+            "3" => Direction::Up,
+            _ => {
+                let reason = "expected a direction from 0 to 3";
+                return Err(ParseError::new(DAY, 8, direction, reason));
+            }
+        };
+        Ok(Command {
+            direction,
+            // Five hex digits always fit
+            length: i64::from_str_radix(length, 16).unwrap(),
+        })
     }
 }
 
-#[derive(PartialEq, Eq)]
-pub enum TileType {
-    Empty,
-    Inner,
-    Edge,
+/// The trench dug by following the plan, as a polygon through the centers of its tiles
+pub fn trench(input: &str, decoding: Decoding) -> Result<Polygon, ParseError> {
+    let commands: Vec<Command> = parse::lines(input, |s| Command::from_str(s, decoding))?;
+    Ok(Polygon::from_moves(
+        commands.iter().map(|c| (c.direction, c.length)),
+    ))
 }
 
-pub struct Tile {
-    tile_type: TileType,
+pub fn solution1(input: &str) -> Solution {
+    let polygon = trench(input, Decoding::Plain)?;
+    bench::parsed();
+    Ok(polygon.lattice_area().into())
 }
 
-pub struct Grid {
-    grid: Vec<Vec<Tile>>,
-    start: (usize, usize),
+pub fn solution2(input: &str) -> Solution {
+    let polygon = trench(input, Decoding::Color)?;
+    bench::parsed();
+    Ok(polygon.lattice_area().into())
 }
 
-impl Grid {
-    pub fn from_commands(commands: &[Command]) -> Grid {
-        // Initial minimum and maximum positions
-        let mut i_min: i32 = 0;
-        let mut j_min: i32 = 0;
-        let mut i_max: i32 = 0;
-        let mut j_max: i32 = 0;
-
-        // Track up/down, left/right movement through the commands
-        let mut i_pos: i32 = 0;
-        let mut j_pos: i32 = 0;
-
-        // If our current vertical/horizontal position exceeds our current row/column
-        // limits, expand them.
-        for command in commands.iter() {
-            match command.direction {
-                'R' => {
-                    j_pos += command.length as i32;
-                    j_max = j_max.max(j_pos);
-                    j_min = j_min.min(j_pos);
-                }
-                'L' => {
-                    j_pos -= command.length as i32;
-                    j_max = j_max.max(j_pos);
-                    j_min = j_min.min(j_pos);
-                }
-                'U' => {
-                    i_pos -= command.length as i32;
-                    i_max = i_max.max(i_pos);
-                    i_min = i_min.min(i_pos);
-                }
-                'D' => {
-                    i_pos += command.length as i32;
-                    i_max = i_max.max(i_pos);
-                    i_min = i_min.min(i_pos);
-                }
-                _ => unreachable!("Undefined direction."),
-            }
-        }
-
-        // Populate grid with empty tiles
-        let i_size = i_max - i_min + 1;
-        let j_size = j_max - j_min + 1;
-        let start = (i_min.unsigned_abs() as usize, j_min.unsigned_abs() as usize);
-        let mut grid = Vec::new();
-        for _i in 0..i_size {
-            let mut row = Vec::<Tile>::new();
-            for _j in 0..j_size {
-                row.push(Tile {
-                    tile_type: TileType::Empty,
-                });
-            }
-            grid.push(row);
-        }
-
-        Grid { grid, start }
-    }
-
-    pub fn display(&self) {
-        println!("({}, {})", self.grid.len(), self.grid[0].len());
-        for row in self.grid.iter() {
-            let row_string = row
-                .iter()
-                .map(|t| match t.tile_type {
-                    TileType::Edge => '#',
-                    TileType::Empty => '.',
-                    TileType::Inner => 'o',
-                })
-                .collect::<String>();
-            println!("{}", row_string);
-        }
-    }
-
-    pub fn nrows(&self) -> usize {
-        self.grid.len()
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::answer::Answer;
+
+    const EXAMPLE: &str = "\
+R 6 (#70c710)
+D 5 (#0dc571)
+L 2 (#5713f0)
+D 2 (#d2c081)
+R 2 (#59c680)
+D 2 (#411b91)
+L 5 (#8ceee2)
+U 2 (#caa173)
+L 1 (#1b58a2)
+U 2 (#caa171)
+R 2 (#7807d2)
+U 3 (#a77fa3)
+L 2 (#015232)
+U 2 (#7a21e3)";
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(solution1(EXAMPLE), Ok(Answer::Int(62)));
     }
 
-    pub fn ncols(&self) -> usize {
-        self.grid[0].len()
+    #[test]
+    fn example_part2() {
+        assert_eq!(solution2(EXAMPLE), Ok(Answer::Int(952408144115)));
     }
 
-    pub fn traversal(&mut self, commands: &[Command]) {
-        let mut i_pos: i32 = self.start.0 as i32;
-        let mut j_pos: i32 = self.start.1 as i32;
-        let mut tiles = vec![(i_pos as usize, j_pos as usize)];
-        let mut starboards = Vec::<(usize, usize)>::new();
-
-        // Traverse the grid using the commands
-        for command in commands.iter() {
-            let (i_incr, j_incr, i_offset, j_offset) = match command.direction {
-                'R' => (0_i32, 1_i32, 1_i32, 0_i32),
-                'L' => (0, -1, -1, 0),
-                'U' => (-1, 0, 0, 1),
-                'D' => (1, 0, 0, -1),
-                _ => unreachable!("Undefined direction."),
-            };
-            for _ in 0..command.length {
-                // Add starboard tile next to current tile
-                let i_starboard = i_pos + i_offset;
-                let j_starboard = j_pos + j_offset;
-                starboards.push((i_starboard as usize, j_starboard as usize));
-
-                // Move position to next tile
-                i_pos += i_incr;
-                j_pos += j_incr;
-                tiles.push((i_pos as usize, j_pos as usize));
-
-                // Add starboard tile of that one
-                let i_starboard = i_pos + i_offset;
-                let j_starboard = j_pos + j_offset;
-                starboards.push((i_starboard as usize, j_starboard as usize));
-            }
-        }
-
-        // Label tiles
-        for (i, j) in starboards.iter() {
-            self.grid[*i][*j].tile_type = TileType::Inner;
-        }
-        for (i, j) in tiles.iter() {
-            self.grid[*i][*j].tile_type = TileType::Edge;
-        }
-
-        // Grow inner tiles to any adjacent empty tiles
-        let nrows = self.nrows();
-        let ncols = self.ncols();
-        let i_offsets = [-1, 1];
-        let j_offsets = [-1, 1];
-        let mut growing = true;
-        while growing {
-            growing = false;
-            for i in 0..nrows {
-                for j in 0..ncols {
-                    if self.grid[i][j].tile_type == TileType::Inner {
-                        for i_offset in i_offsets {
-                            for j_offset in j_offsets {
-                                let i_index = (i as i32 + i_offset) as usize;
-                                let j_index = (j as i32 + j_offset) as usize;
-                                if self.grid[i_index][j_index].tile_type == TileType::Empty {
-                                    self.grid[i_index][j_index].tile_type = TileType::Inner;
-                                    growing = true;
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
+    #[test]
+    fn malformed_input() {
+        let error = solution1("R 6 (#70c710)\nX 5 (#0dc571)").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!((error.line, error.column, error.text.as_str()), (2, 1, "X"));
+        let error = solution1("R -6 (#70c710)").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!((error.column, error.text.as_str()), (3, "-6"));
+        let error = solution2("R 6 (#70c714)").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!((error.column, error.text.as_str()), (12, "4"));
+        let error = solution2("R 6 (#70c7)").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!((error.column, error.text.as_str()), (5, "(#70c7)"));
+        let error = solution2("R 6").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!((error.line, error.column), (1, 1));
     }
 
-    pub fn area(&self) -> u64 {
-        self.grid
-            .iter()
-            .map(|row| {
-                let row_score: u64 = row
-                    .iter()
-                    .map(|t| match t.tile_type {
-                        TileType::Edge | TileType::Inner => 1,
-                        TileType::Empty => 0,
-                    })
-                    .sum();
-                row_score
-            })
-            .sum()
+    #[test]
+    fn decodings() {
+        let command = Command::from_str("R 6 (#70c710)", Decoding::Plain).unwrap();
+        assert_eq!((command.direction, command.length), (Direction::Right, 6));
+        let command = Command::from_str("U 2 (#caa171)", Decoding::Color).unwrap();
+        assert_eq!(
+            (command.direction, command.length),
+            (Direction::Down, 829975)
+        );
+
+        let trench = trench(EXAMPLE, Decoding::Plain).unwrap();
+        assert_eq!(trench.vertices().len(), 14);
+        assert_eq!(trench.boundary_points(), 38);
+        assert_eq!(trench.interior_points(), 24);
     }
 }
-
-#[aocd(2023, 18)]
-pub fn solution1() {
-    let input_data = input!();
-    let commands: Vec<Command> = input_data
-        .split('\n')
-        .map(|s| Command::from_str(s.trim()))
-        .collect();
-
-    let mut grid = Grid::from_commands(&commands);
-    grid.traversal(&commands);
-    grid.display();
-    submit!(1, grid.area());
-}
-
-#[aocd(2023, 18, "src/day18/test.txt")]
-pub fn solution2() {
-    let input_data = input!();
-    let mut commands: Vec<Command> = input_data
-        .split('\n')
-        .map(|s| Command::from_str(s.trim()))
-        .collect();
-
-    for command in commands.iter_mut() {
-        command.direction = command.extract_direction_from_color();
-        command.length = command.extract_length_from_color();
-    }
-
-    let mut grid = Grid::from_commands(&commands);
-    grid.traversal(&commands);
-    grid.display();
-    submit!(2, grid.area());
-}
@@ -1,7 +1,10 @@
+use crate::answer::{Solution, SolveError};
+use crate::bench;
+use crate::parse::{self, ParseError};
 use std::collections::HashMap;
+use std::ops::RangeInclusive;
 
-use aocd::*;
-use regex::Regex;
+const DAY: u8 = 19;
 
 #[derive(Debug)]
 pub struct Workflow<'a> {
@@ -9,21 +12,33 @@ pub struct Workflow<'a> {
 }
 
 impl<'a> Workflow<'a> {
-    fn from_str(s: &'a str) -> (&'a str, Workflow<'a>) {
+    fn from_str(s: &'a str) -> Result<(&'a str, Workflow<'a>), ParseError> {
         // Get id
         // Alphabetical letters preceding the first curly bracket
-        let id_regex = Regex::new(r"^([a-z]+)\{").unwrap();
-        let id = id_regex.captures(s).unwrap().get(1).unwrap().as_str();
+        let (id, rest) = s
+            .split_once('{')
+            .filter(|(id, _)| !id.is_empty() && id.chars().all(|c| c.is_ascii_lowercase()))
+            .ok_or_else(|| ParseError::new(DAY, 1, s, "expected a workflow name and '{'"))?;
 
         // Get rules
         // Chunk of text between curly brackets
-        let rules_regex = Regex::new(r"\{(.*)\}").unwrap();
-        let rules_str = rules_regex.captures(s).unwrap().get(1).unwrap().as_str();
+        let rules_str = rest.strip_suffix('}').ok_or_else(|| {
+            ParseError::new(DAY, id.len() + 2, rest, "expected rules ending in '}'")
+        })?;
 
         // List of rules can be split by commas
-        let rules: Vec<Rule> = rules_str.split(',').map(Rule::from_str).collect();
+        let rules: Vec<Rule> = parse::fields(rules_str, ',')
+            .map(|(column, rule)| Rule::from_str(rule).map_err(|e| e.shifted(id.len() + column)))
+            .collect::<Result<_, _>>()?;
 
-        (id, Workflow { rules })
+        // Parts that meet none of the conditions have to be sent somewhere
+        if rules.last().is_none_or(|rule| rule.category.is_some()) {
+            let last = rules_str.rsplit(',').next().unwrap_or_default();
+            let reason = "expected the last rule to have no condition";
+            return Err(ParseError::new(DAY, s.len() - last.len(), last, reason));
+        }
+
+        Ok((id, Workflow { rules }))
     }
 }
 
@@ -36,40 +51,42 @@ pub struct Rule<'a> {
 }
 
 impl<'a> Rule<'a> {
-    fn from_str(s: &'a str) -> Rule<'a> {
+    fn from_str(s: &'a str) -> Result<Rule<'a>, ParseError> {
         // The rule condition, if there is one, is defined by the chunk of text before
         // the colon.
-        let condition_regex = Regex::new(r"(.+):").unwrap();
-
-        // If our regex finds a condition in the string...
-        if let Some(captures) = condition_regex.captures(s) {
+        if let Some((condition, target)) = s.split_once(':') {
             // Get the characters of the condition text
-            let mut condition = captures.get(1).unwrap().as_str().chars();
+            let mut condition = condition.chars();
             // The category (x, m, a, s) is the first character
-            let category = Some(condition.next().unwrap());
+            let category = condition.next().filter(|c| "xmas".contains(*c));
+            if category.is_none() {
+                return Err(ParseError::new(DAY, 1, s, "expected x, m, a or s"));
+            }
             // The operator (<, >) is the second character
-            let operator = Some(condition.next().unwrap());
+            let operator = condition.next().filter(|c| "<>".contains(*c));
+            if operator.is_none() {
+                return Err(ParseError::new(DAY, 2, &s[1..], "expected '<' or '>'"));
+            }
             // The threshold number is the rest of the condition text
-            let threshold = Some(condition.collect::<String>().parse::<u32>().unwrap());
+            let threshold = Some(parse::number(DAY, 3, condition.as_str())?);
             // Meanwhile, the target (workflow id, Accepted, or Rejected), is the text
             // after the colon
-            let target_regex = Regex::new(r":(\w+)$").unwrap();
-            let target = target_regex.captures(s).unwrap().get(1).unwrap().as_str();
-            Rule {
+            let column = s.len() - target.len() + 1;
+            Ok(Rule {
                 category,
                 operator,
                 threshold,
-                target,
-            }
+                target: parse_target(target).map_err(|e| e.shifted(column - 1))?,
+            })
         } else {
-            // If our regex does not find a condition in the string, then the rule simply
-            // points to a target (another workflow, Accepted, or Rejected)
-            Rule {
+            // If there is no condition in the string, then the rule simply points to a
+            // target (another workflow, Accepted, or Rejected)
+            Ok(Rule {
                 category: None,
                 operator: None,
                 threshold: None,
-                target: s,
-            }
+                target: parse_target(s)?,
+            })
         }
     }
 
@@ -92,6 +109,130 @@ impl<'a> Rule<'a> {
             Some(self.target)
         }
     }
+
+    // Symbolic version of `operate`: split a hypercube of parts into the parts the rule
+    // sends to its target and the parts that move on to the next rule
+    fn split(&self, cube: &Hypercube) -> (Option<Hypercube>, Option<Hypercube>) {
+        let Some(category) = self.category else {
+            return (Some(cube.clone()), None);
+        };
+        let range = cube.ranges.get(&category).unwrap();
+        let (start, end) = (*range.start(), *range.end());
+        let threshold = self.threshold.unwrap();
+        let (matched, rest) = if self.operator.unwrap() == '>' {
+            ((threshold + 1).max(start)..=end, start..=threshold.min(end))
+        } else {
+            (
+                start..=threshold.saturating_sub(1).min(end),
+                threshold.max(start)..=end,
+            )
+        };
+        (cube.with(category, matched), cube.with(category, rest))
+    }
+}
+
+/// A workflow id, or A or R
+fn parse_target(s: &str) -> Result<&str, ParseError> {
+    if s.is_empty() || !s.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(ParseError::new(
+            DAY,
+            1,
+            s,
+            "expected a workflow name, A or R",
+        ));
+    }
+    Ok(s)
+}
+
+/// The workflow with the given id, which a rule or the start may refer to even if it
+/// does not exist
+fn workflow<'w, 'a>(
+    workflows: &'w HashMap<&'a str, Workflow<'a>>,
+    id: &str,
+) -> Result<&'w Workflow<'a>, SolveError> {
+    workflows
+        .get(id)
+        .ok_or_else(|| SolveError::unsolved(DAY, format!("there is no workflow named {id}")))
+}
+
+/// Every part whose ratings fall in the given inclusive ranges
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hypercube {
+    pub ranges: HashMap<char, RangeInclusive<u32>>,
+}
+
+impl Hypercube {
+    pub fn new(min: u32, max: u32) -> Hypercube {
+        let ranges = "xmas".chars().map(|c| (c, min..=max)).collect();
+        Hypercube { ranges }
+    }
+
+    /// The same hypercube with one category narrowed down, unless that leaves it empty
+    fn with(&self, category: char, range: RangeInclusive<u32>) -> Option<Hypercube> {
+        if range.is_empty() {
+            return None;
+        }
+        let mut cube = self.clone();
+        cube.ranges.insert(category, range);
+        Some(cube)
+    }
+
+    /// Number of distinct parts in the hypercube
+    pub fn combinations(&self) -> u64 {
+        self.ranges
+            .values()
+            .map(|r| (*r.end() as u64 + 1).saturating_sub(*r.start() as u64))
+            .product()
+    }
+}
+
+impl Default for Hypercube {
+    fn default() -> Hypercube {
+        Hypercube::new(1, 4000)
+    }
+}
+
+/// Parts that end up accepted, as disjoint hypercubes along with the workflows each one
+/// passes through
+#[derive(Debug)]
+pub struct Acceptance<'a> {
+    pub count: u64,
+    pub accepted: Vec<(Hypercube, Vec<&'a str>)>,
+}
+
+/// Run a whole hypercube of parts through the workflows at once, starting at `in`
+pub fn acceptance<'a>(
+    workflows: &HashMap<&'a str, Workflow<'a>>,
+    cube: Hypercube,
+) -> Result<Acceptance<'a>, SolveError> {
+    let mut accepted = Vec::new();
+    let mut stack = vec![(cube, vec!["in"])];
+    while let Some((cube, path)) = stack.pop() {
+        let workflow = workflow(workflows, path.last().unwrap())?;
+        let mut remaining = Some(cube);
+        for rule in workflow.rules.iter() {
+            let Some(cube) = remaining else { break };
+            let (matched, rest) = rule.split(&cube);
+            remaining = rest;
+            if let Some(matched) = matched {
+                match rule.target {
+                    "A" => accepted.push((matched, path.clone())),
+                    "R" => {}
+                    target => {
+                        if path.contains(&target) {
+                            let reason = format!("workflow {target} sends parts back to itself");
+                            return Err(SolveError::unsolved(DAY, reason));
+                        }
+                        let mut path = path.clone();
+                        path.push(target);
+                        stack.push((matched, path));
+                    }
+                }
+            }
+        }
+    }
+    let count = accepted.iter().map(|(cube, _)| cube.combinations()).sum();
+    Ok(Acceptance { count, accepted })
 }
 
 pub struct Part {
@@ -99,57 +240,52 @@ pub struct Part {
 }
 
 impl Part {
-    fn from_str(s: &str) -> Part {
-        let x_regex = Regex::new(r"x=(\d+)").unwrap();
-        let x = x_regex
-            .captures(s)
-            .unwrap()
-            .get(1)
-            .unwrap()
-            .as_str()
-            .parse::<u32>()
-            .unwrap();
-        let m_regex = Regex::new(r"m=(\d+)").unwrap();
-        let m = m_regex
-            .captures(s)
-            .unwrap()
-            .get(1)
-            .unwrap()
-            .as_str()
-            .parse::<u32>()
-            .unwrap();
-        let a_regex = Regex::new(r"a=(\d+)").unwrap();
-        let a = a_regex
-            .captures(s)
-            .unwrap()
-            .get(1)
-            .unwrap()
-            .as_str()
-            .parse::<u32>()
-            .unwrap();
-        let s_regex = Regex::new(r"s=(\d+)").unwrap();
-        let s = s_regex
-            .captures(s)
-            .unwrap()
-            .get(1)
-            .unwrap()
-            .as_str()
-            .parse::<u32>()
-            .unwrap();
-        let ratings = HashMap::from([('x', x), ('m', m), ('a', a), ('s', s)]);
-        Part { ratings }
+    /// Parse ratings such as `{x=787,m=2655,a=1222,s=2876}`
+    fn from_str(s: &str) -> Result<Part, ParseError> {
+        let expected = "expected ratings such as {x=787,m=2655,a=1222,s=2876}";
+        let ratings = s
+            .strip_prefix('{')
+            .and_then(|ratings| ratings.strip_suffix('}'))
+            .ok_or_else(|| ParseError::new(DAY, 1, s, expected))?;
+        let fields: Vec<(usize, &str)> = parse::fields(ratings, ',').collect();
+        if fields.len() != 4 {
+            return Err(ParseError::new(DAY, 1, s, expected));
+        }
+        let ratings = fields
+            .into_iter()
+            .zip("xmas".chars())
+            .map(|((column, field), category)| {
+                let column = column + 1;
+                let rating = field
+                    .strip_prefix(category)
+                    .and_then(|rating| rating.strip_prefix('='))
+                    .ok_or_else(|| {
+                        let reason = format!("expected {category}=");
+                        ParseError::new(DAY, column, field, reason)
+                    })?;
+                Ok((category, parse::number(DAY, column + 2, rating)?))
+            })
+            .collect::<Result<_, ParseError>>()?;
+        Ok(Part { ratings })
     }
 
-    fn work(&self, workflows: &HashMap<&str, Workflow>) -> bool {
+    fn work(&self, workflows: &HashMap<&str, Workflow>) -> Result<bool, SolveError> {
         let mut workflow_id = "in";
+        let mut visited = 0;
         'working: loop {
-            let workflow = workflows.get(workflow_id).unwrap();
+            let workflow = workflow(workflows, workflow_id)?;
+            visited += 1;
+            if visited > workflows.len() {
+                let reason = format!("workflow {workflow_id} sends parts back to itself");
+                return Err(SolveError::unsolved(DAY, reason));
+            }
+            // Every workflow ends in a rule without a condition, so one of them applies
             for rule in workflow.rules.iter() {
                 if let Some(target) = rule.operate(self) {
                     if target == "A" {
-                        return true;
+                        return Ok(true);
                     } else if target == "R" {
-                        return false;
+                        return Ok(false);
                     } else {
                         workflow_id = target;
                         continue 'working;
@@ -164,27 +300,167 @@ impl Part {
     }
 }
 
-#[aocd(2023, 19)]
-pub fn solution1() {
-    let input_data = input!();
-    let input_data: Vec<&str> = input_data.split("\n\n").collect();
+/// Read the workflows and, if there is a blank line after them, the block of parts
+fn read_input(input: &str) -> Result<(HashMap<&str, Workflow<'_>>, Option<&str>), ParseError> {
+    let mut input_data = input.splitn(2, "\n\n");
 
     // Extract workflows
-    let workflows: HashMap<&str, Workflow> =
-        input_data[0].split('\n').map(Workflow::from_str).collect();
+    let workflows = input_data.next().unwrap_or_default();
+    let workflows: HashMap<&str, Workflow> = parse::lines(workflows, Workflow::from_str)?
+        .into_iter()
+        .collect();
+    Ok((workflows, input_data.next()))
+}
+
+pub fn solution1(input: &str) -> Solution {
+    let (workflows, parts) = read_input(input)?;
 
-    // Extract parts
-    let parts: Vec<Part> = input_data[1].split('\n').map(Part::from_str).collect();
+    // Extract parts, which start on the line after the blank one
+    let first_line = input.lines().take_while(|line| !line.is_empty()).count() + 2;
+    let parts = parts.ok_or_else(|| {
+        ParseError::new(DAY, 1, "", "expected a blank line and then the parts")
+            .on_line(first_line - 1)
+    })?;
+    let parts: Vec<Part> = parse::lines(parts, Part::from_str).map_err(|e| {
+        let line = e.line + first_line - 1;
+        e.on_line(line)
+    })?;
+    bench::parsed();
 
     // Run parts through workflows
-    let total: u32 = parts
-        .iter()
-        .filter(|p| p.work(&workflows))
-        .map(|p| p.total_rating())
-        .sum();
+    let mut total: u32 = 0;
+    for part in parts.iter() {
+        if part.work(&workflows)? {
+            total += part.total_rating();
+        }
+    }
 
-    submit!(1, total);
+    Ok(total.into())
 }
 
-#[aocd(2023, 19)]
-pub fn solution2() {}
+pub fn solution2(input: &str) -> Solution {
+    let (workflows, _) = read_input(input)?;
+    bench::parsed();
+
+    Ok(acceptance(&workflows, Hypercube::default())?.count.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::answer::Answer;
+
+    const EXAMPLE: &str = "\
+px{a<2006:qkq,m>2090:A,rfg}
+pv{a>1716:R,A}
+lnx{m>1548:A,A}
+rfg{s<537:gd,x>2440:R,A}
+qs{s>3448:A,lnx}
+qkq{x<1416:A,crn}
+crn{x>2662:A,R}
+in{s<1351:px,qqz}
+qqz{s>2770:qs,m<1801:hdj,R}
+gd{a>3333:R,R}
+hdj{m>838:A,pv}
+
+{x=787,m=2655,a=1222,s=2876}
+{x=1679,m=44,a=2067,s=496}
+{x=2036,m=264,a=79,s=2244}
+{x=2461,m=1339,a=466,s=291}
+{x=2127,m=1623,a=2188,s=1013}";
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(solution1(EXAMPLE), Ok(Answer::Int(19114)));
+    }
+
+    #[test]
+    fn example_part2() {
+        assert_eq!(solution2(EXAMPLE), Ok(Answer::Int(167409079868000)));
+    }
+
+    #[test]
+    fn redundant_rules() {
+        // The second threshold is already met by every part that reaches it
+        let redundant = "in{x>2000:a,R}\na{x>1000:A,R}\n\n{x=1,m=1,a=1,s=1}";
+        assert_eq!(solution2(redundant), Ok(Answer::Int(128000000000000)));
+        let redundant = "in{x<1000:a,R}\na{x<2000:A,R}\n\n{x=1,m=1,a=1,s=1}";
+        assert_eq!(solution2(redundant), Ok(Answer::Int(63936000000000)));
+    }
+
+    #[test]
+    fn malformed_input() {
+        let error = solution1("in{x>10:A,R}\nab{x>1o:A,R}\n\n{x=1,m=1,a=1,s=1}").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!(
+            (error.line, error.column, error.text.as_str()),
+            (2, 6, "1o")
+        );
+        let error = solution2("in{x>10:A,q<3:R,R}").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!((error.column, error.text.as_str()), (11, "q<3:R"));
+        let error = solution2("in{x>10:A,R").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!(error.column, 4);
+        let error = solution2("in{x>10:A,m<5:R}").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!(error.reason, "expected the last rule to have no condition");
+        let error = solution1("in{A}\n\n{x=1,m=1,a=1,s=1}\n{x=1,m=1,s=1,a=1}").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!(
+            (error.line, error.column, error.text.as_str()),
+            (4, 10, "s=1")
+        );
+        let error = solution1("in{A}").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!((error.line, error.column), (2, 1));
+
+        let error = SolveError::unsolved(DAY, "there is no workflow named qs");
+        assert_eq!(solution1("in{qs}\n\n{x=1,m=1,a=1,s=1}"), Err(error.clone()));
+        assert_eq!(solution2("in{qs}"), Err(error));
+        let looping = "in{x>5:a,R}\na{in}\n\n{x=10,m=1,a=1,s=1}";
+        let error = SolveError::unsolved(DAY, "workflow in sends parts back to itself");
+        assert_eq!(solution2(looping), Err(error));
+        assert!(solution1(looping).is_err());
+    }
+
+    #[test]
+    fn accepted_hypercubes() {
+        let workflows: HashMap<&str, Workflow> = EXAMPLE
+            .split("\n\n")
+            .next()
+            .unwrap()
+            .lines()
+            .map(|line| Workflow::from_str(line).unwrap())
+            .collect();
+        let acceptance = acceptance(&workflows, Hypercube::new(1, 4000)).unwrap();
+        assert!(acceptance.accepted.iter().all(|(_, path)| path[0] == "in"));
+
+        // Every concrete part agrees with the hypercube it falls in
+        let parts = EXAMPLE
+            .split("\n\n")
+            .nth(1)
+            .unwrap()
+            .lines()
+            .map(|line| Part::from_str(line).unwrap());
+        for part in parts {
+            let contained = acceptance.accepted.iter().any(|(cube, _)| {
+                part.ratings
+                    .iter()
+                    .all(|(c, rating)| cube.ranges[c].contains(rating))
+            });
+            assert_eq!(Ok(contained), part.work(&workflows));
+        }
+
+        // "in" -> "px" -> "qkq" accepts x < 1416 when s < 1351, a < 2006
+        let cube = &acceptance
+            .accepted
+            .iter()
+            .find(|(_, path)| path == &vec!["in", "px", "qkq"])
+            .unwrap()
+            .0;
+        assert_eq!(cube.ranges[&'x'], 1..=1415);
+        assert_eq!(cube.ranges[&'s'], 1..=1350);
+        assert_eq!(cube.ranges[&'a'], 1..=2005);
+    }
+}
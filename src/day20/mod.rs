@@ -1,36 +1,35 @@
-use std::collections::HashMap;
+use crate::answer::{Solution, SolveError};
+use crate::bench;
+use crate::math;
+use crate::parse::{self, ParseError};
+use std::collections::{HashMap, VecDeque};
+use std::error::Error;
+use std::fmt;
 
-use aocd::*;
-use regex::Regex;
+const DAY: u8 = 20;
 
 pub struct Broadcaster<'a> {
     id: &'a str,
-    targets: Vec<&'a str> 
+    targets: Vec<&'a str>,
 }
 
 impl<'a> Module<'a> for Broadcaster<'a> {
     fn receive(&mut self, pulse: Pulse) -> Vec<Pulse<'a>> {
         self.targets
             .iter()
-            .map(|t| {
-                Pulse {
-                    sender: self.id,
-                    receiver: t,
-                    low: pulse.low
-                }
+            .map(|t| Pulse {
+                sender: self.id,
+                receiver: t,
+                low: pulse.low,
             })
             .collect()
     }
-
-    fn id(&'a self) -> &'a str {
-        self.id
-    }
 }
 
 pub struct FlipFlop<'a> {
     id: &'a str,
     targets: Vec<&'a str>,
-    on: bool
+    on: bool,
 }
 
 impl<'a> Module<'a> for FlipFlop<'a> {
@@ -40,28 +39,22 @@ impl<'a> Module<'a> for FlipFlop<'a> {
             self.on = !self.on;
             self.targets
                 .iter()
-                .map(|t| {
-                    Pulse {
-                        sender: self.id,
-                        receiver: t,
-                        low
-                    }
+                .map(|t| Pulse {
+                    sender: self.id,
+                    receiver: t,
+                    low,
                 })
                 .collect()
         } else {
             Vec::new()
         }
     }
-
-    fn id(&'a self) -> &'a str {
-        self.id
-    }
 }
 
 pub struct Conjunction<'a> {
     id: &'a str,
     inputs: HashMap<&'a str, bool>,
-    targets: Vec<&'a str>
+    targets: Vec<&'a str>,
 }
 
 impl<'a> Module<'a> for Conjunction<'a> {
@@ -70,277 +63,503 @@ impl<'a> Module<'a> for Conjunction<'a> {
         let all_high = self.inputs.values().all(|v| !*v);
         self.targets
             .iter()
-            .map(|t| {
-                Pulse {
-                    sender: self.id,
-                    receiver: t,
-                    low: all_high
-                }
+            .map(|t| Pulse {
+                sender: self.id,
+                receiver: t,
+                low: all_high,
             })
             .collect()
     }
-
-    fn id(&'a self) -> &'a str {
-        self.id
-    }
 }
 
-pub struct Untyped<'a> {
-    id: &'a str
-}
+pub struct Untyped;
 
-impl<'a> Module<'a> for Untyped<'a> {
+impl<'a> Module<'a> for Untyped {
     fn receive(&mut self, _pulse: Pulse) -> Vec<Pulse<'a>> {
         Vec::new()
     }
-
-    fn id(&'a self) -> &'a str {
-        self.id
-    }
 }
 
 #[derive(Clone, Debug)]
 pub struct Pulse<'a> {
     sender: &'a str,
     receiver: &'a str,
-    low: bool
+    low: bool,
 }
 
 trait Module<'a> {
     fn receive(&mut self, pulse: Pulse<'a>) -> Vec<Pulse<'a>>;
-    fn id(&'a self) -> &'a str;
-}
-
-fn get_sender_from_str(s: &str) -> &str {
-    let sender_regex = Regex::new(r"(\w+) ->").unwrap();
-    sender_regex.captures(s).unwrap().get(1).unwrap().as_str()
-}
-
-fn get_receivers_from_str(s: &str) -> Vec<&str> {
-    let receivers_regex = Regex::new(r"-> (.*)$").unwrap();
-    let receivers = receivers_regex.captures(s).unwrap().get(1).unwrap().as_str();
-    let receivers: Vec<&str> = receivers.trim().split(", ").collect();
-    receivers
 }
 
 pub enum ModuleType {
     Broadcaster,
     FlipFlop,
     Conjunction,
-    Untyped
+    Untyped,
 }
 
-fn get_module_type_from_str(s: &str) -> ModuleType {
-    let sender_with_prefix_regex = Regex::new(r"(.+) ->").unwrap();
-    let sender_with_prefix = sender_with_prefix_regex.captures(s).unwrap().get(1).unwrap().as_str();
-    if sender_with_prefix == "broadcaster" {
-        ModuleType::Broadcaster
-    } else if sender_with_prefix.contains('%') {
-        ModuleType::FlipFlop
-    } else if sender_with_prefix.contains('&') {
-        ModuleType::Conjunction
+/// One line of the configuration: a module and the modules it sends pulses to
+struct Connection<'a> {
+    module: ModuleType,
+    sender: &'a str,
+    receivers: Vec<&'a str>,
+}
+
+/// Whether a module name is made up of letters and digits only
+fn is_module_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+/// Parse a line such as `%a -> inv, con`
+fn parse_line(s: &str) -> Result<Connection<'_>, ParseError> {
+    let (sender_with_prefix, receivers) = s
+        .split_once(" -> ")
+        .ok_or_else(|| ParseError::new(DAY, 1, s, "expected a module, ' -> ' and its receivers"))?;
+    let (module, sender) = if sender_with_prefix == "broadcaster" {
+        (ModuleType::Broadcaster, sender_with_prefix)
+    } else if let Some(sender) = sender_with_prefix.strip_prefix('%') {
+        (ModuleType::FlipFlop, sender)
+    } else if let Some(sender) = sender_with_prefix.strip_prefix('&') {
+        (ModuleType::Conjunction, sender)
     } else {
-        unreachable!("Sender must be one of the three types above.")
+        let reason = "expected broadcaster, or a name after % or &";
+        return Err(ParseError::new(DAY, 1, sender_with_prefix, reason));
+    };
+    if !is_module_name(sender) {
+        return Err(ParseError::new(DAY, 2, sender, "expected a module name"));
     }
-}
 
-fn get_all_module_names_from_str(s: &str) -> Vec<&str> {
-    let sender = get_sender_from_str(s);
-    let receivers_regex = Regex::new(r"-> (.*)$").unwrap();
-    let receivers = receivers_regex.captures(s).unwrap().get(1).unwrap().as_str();
-    let mut receivers: Vec<&str> = receivers.trim().split(", ").collect();
-    let mut all_module_names = Vec::new();
-    all_module_names.push(sender);
-    all_module_names.append(&mut receivers);
-    all_module_names
+    let offset = sender_with_prefix.len() + " -> ".len();
+    let receivers = parse::fields(receivers, ',')
+        .map(|(column, receiver)| {
+            let name = receiver.trim_start();
+            let column = offset + column + receiver.len() - name.len();
+            if is_module_name(name) {
+                Ok(name)
+            } else {
+                Err(ParseError::new(DAY, column, name, "expected a module name"))
+            }
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(Connection {
+        module,
+        sender,
+        receivers,
+    })
 }
 
 pub struct ModuleData<'a> {
     module: ModuleType,
     sender: &'a str,
     receivers: Vec<&'a str>,
-    inputs: Vec<&'a str>
+    inputs: Vec<&'a str>,
 }
 
 impl<'a> ModuleData<'a> {
-    fn new(sender: &str) -> ModuleData {
+    fn new(sender: &str) -> ModuleData<'_> {
         ModuleData {
             module: ModuleType::Untyped,
             sender,
             receivers: Vec::<&str>::new(),
-            inputs: Vec::<&str>::new()
+            inputs: Vec::<&str>::new(),
         }
     }
 
-    fn to_module(self) -> Box<dyn Module<'a> + 'a> {
+    fn into_module(self) -> Box<dyn Module<'a> + 'a> {
         match self.module {
-            ModuleType::Broadcaster => {
-                Box::new(Broadcaster {
-                    id: self.sender,
-                    targets: self.receivers
-                })
-            },
-            ModuleType::Conjunction => {
-                Box::new(Conjunction {
-                    id: self.sender,
-                    targets: self.receivers,
-                    inputs: self.inputs.into_iter().map(|s| (s, true)).collect()
-                })
-            },
-            ModuleType::FlipFlop => {
-                Box::new(FlipFlop {
-                    id: self.sender,
-                    targets: self.receivers,
-                    on: false
-                })
-            },
-            ModuleType::Untyped => {
-                Box::new(Untyped {
-                    id: self.sender
-                })
-            }
+            ModuleType::Broadcaster => Box::new(Broadcaster {
+                id: self.sender,
+                targets: self.receivers,
+            }),
+            ModuleType::Conjunction => Box::new(Conjunction {
+                id: self.sender,
+                targets: self.receivers,
+                inputs: self.inputs.into_iter().map(|s| (s, true)).collect(),
+            }),
+            ModuleType::FlipFlop => Box::new(FlipFlop {
+                id: self.sender,
+                targets: self.receivers,
+                on: false,
+            }),
+            ModuleType::Untyped => Box::new(Untyped),
         }
     }
 }
 
-#[aocd(2023, 20, "src/day20/input.txt")]
-pub fn solution1() {
-    let input_data = input!();
-
-    // Get names of all referenced modules in senders or receivers
-    let module_names: Vec<&str> = input_data
-        .split('\n')
-        .flat_map(get_all_module_names_from_str)
-        .collect();
-    
-    // Data structure to store all pertinent module data for each referenced module
-    let mut module_data: HashMap<&str, ModuleData> = module_names
-        .iter()
-        .map(|s| {
-            (*s, ModuleData::new(s))
-        })
-        .collect();
-
-    // Loop back through the input data and fill the data structure
-    for data in input_data.split('\n') {
-        let sender = get_sender_from_str(data);
-        let receivers = get_receivers_from_str(data);
-        let module_type = get_module_type_from_str(data);
-        let module = module_data.get_mut(&sender).unwrap();
-        module.receivers = receivers;
-        module.module = module_type;
-    }        
-    
-    // Loop back through the input data and fill in input modules
-    for data in input_data.split('\n') {
-        let sender = get_sender_from_str(data);
-        let receivers = get_receivers_from_str(data);
+fn module_data_from_str(input: &str) -> Result<HashMap<&str, ModuleData<'_>>, ParseError> {
+    let connections = parse::lines(input, parse_line)?;
+
+    // Data structure to store all pertinent module data for each referenced module,
+    // whether it is a sender or only a receiver
+    let mut module_data: HashMap<&str, ModuleData> = HashMap::new();
+    for connection in connections.iter() {
+        for name in connection.receivers.iter().chain([&connection.sender]) {
+            module_data
+                .entry(name)
+                .or_insert_with(|| ModuleData::new(name));
+        }
+    }
+
+    // Loop back through the connections and fill the data structure
+    for connection in connections {
         // For each receiver listed, find that module and add in the sender as an input
-        for receiver in receivers.iter() {
+        for receiver in connection.receivers.iter() {
             let module = module_data.get_mut(receiver).unwrap();
-            module.inputs.push(sender);
+            module.inputs.push(connection.sender);
         }
+        let module = module_data.get_mut(connection.sender).unwrap();
+        module.receivers = connection.receivers;
+        module.module = connection.module;
     }
 
-    // Create modules from the module data
-    let mut modules: HashMap<&str, Box<dyn Module>> = module_data.into_iter()
-        .map(|(module_name, module_data)| {
-            (module_name, module_data.to_module())
-        })
-        .collect();
+    Ok(module_data)
+}
+
+/// The button sends its pulse to the broadcaster, so there has to be one
+fn check_broadcaster(module_data: &HashMap<&str, ModuleData>) -> Result<(), SolveError> {
+    match module_data.get("broadcaster") {
+        Some(ModuleData {
+            module: ModuleType::Broadcaster,
+            ..
+        }) => Ok(()),
+        _ => Err(SolveError::unsolved(DAY, "there is no broadcaster")),
+    }
+}
+
+fn modules_from_data<'a>(
+    module_data: HashMap<&'a str, ModuleData<'a>>,
+) -> HashMap<&'a str, Box<dyn Module<'a> + 'a>> {
+    module_data
+        .into_iter()
+        .map(|(module_name, module_data)| (module_name, module_data.into_module()))
+        .collect()
+}
+
+// Press the button once, handing every pulse sent to `observe` as it is delivered
+fn press_button<'a>(
+    modules: &mut HashMap<&'a str, Box<dyn Module<'a> + 'a>>,
+    mut observe: impl FnMut(&Pulse<'a>),
+) {
+    let button_pulse = Pulse {
+        sender: "button",
+        receiver: "broadcaster",
+        low: true,
+    };
+    let mut pulse_queue = VecDeque::from([button_pulse]);
+    while let Some(current_pulse) = pulse_queue.pop_front() {
+        observe(&current_pulse);
+        let module = modules.get_mut(current_pulse.receiver).unwrap();
+        pulse_queue.extend(module.receive(current_pulse));
+    }
+}
+
+/// Presses at which one input of the conjunction feeding `rx` sends it a high pulse:
+/// first at press `first`, then every `period` presses
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputCycle<'a> {
+    pub input: &'a str,
+    pub first: u64,
+    pub period: u64,
+}
+
+/// How the single low pulse to `rx` comes about
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RxAnalysis<'a> {
+    pub feeder: &'a str,
+    pub cycles: Vec<InputCycle<'a>>,
+    pub presses: u64,
+}
+
+/// Why the circuit does not have the cycle structure the analysis relies on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CycleError {
+    /// No module sends pulses to `rx`
+    NoFeeder,
+    /// `rx` is fed by something other than a single conjunction
+    FeederNotConjunction(String),
+    /// An input did not send enough high pulses to establish a period
+    NoCycle { input: String, presses: u64 },
+    /// An input sent high pulses at irregular intervals
+    Irregular { input: String, presses: Vec<u64> },
+    /// The input cycles never line up on the same press
+    NeverAligned,
+}
+
+impl fmt::Display for CycleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CycleError::NoFeeder => write!(f, "no module sends pulses to rx"),
+            CycleError::FeederNotConjunction(feeder) => {
+                write!(
+                    f,
+                    "rx is fed by {feeder}, which is not a single conjunction"
+                )
+            }
+            CycleError::NoCycle { input, presses } => {
+                write!(
+                    f,
+                    "{input} did not send a high pulse three times in {presses} presses"
+                )
+            }
+            CycleError::Irregular { input, presses } => {
+                write!(
+                    f,
+                    "{input} sent high pulses at irregular presses {presses:?}"
+                )
+            }
+            CycleError::NeverAligned => write!(f, "the input cycles never line up"),
+        }
+    }
+}
+
+impl Error for CycleError {}
+
+impl From<CycleError> for SolveError {
+    fn from(error: CycleError) -> SolveError {
+        SolveError::unsolved(
+            DAY,
+            format!("could not find when rx gets a low pulse, {error}"),
+        )
+    }
+}
+
+/// Find the conjunction feeding `rx` and press the button until each of its inputs has
+/// sent it a high pulse three times, or `max_presses` is reached. The first low pulse to
+/// `rx` comes on the first press at which all inputs send a high pulse together.
+pub fn analyze_rx<'a>(
+    module_data: HashMap<&'a str, ModuleData<'a>>,
+    max_presses: u64,
+) -> Result<RxAnalysis<'a>, CycleError> {
+    // Find the module feeding rx
+    let rx_inputs = module_data
+        .get("rx")
+        .map(|m| m.inputs.clone())
+        .unwrap_or_default();
+    let feeder = match rx_inputs[..] {
+        [] => return Err(CycleError::NoFeeder),
+        [feeder] => feeder,
+        _ => return Err(CycleError::FeederNotConjunction(rx_inputs.join(", "))),
+    };
+    let feeder_data = module_data.get(feeder).unwrap();
+    if !matches!(feeder_data.module, ModuleType::Conjunction) {
+        return Err(CycleError::FeederNotConjunction(feeder.to_string()));
+    }
+    let feeder_inputs = feeder_data.inputs.clone();
+
+    // Record the presses at which each input sends the feeder a high pulse
+    let mut modules = modules_from_data(module_data);
+    let mut high_presses: HashMap<&str, Vec<u64>> = HashMap::new();
+    let mut presses = 0;
+    while presses < max_presses
+        && feeder_inputs
+            .iter()
+            .any(|i| high_presses.get(i).map_or(0, |p| p.len()) < 3)
+    {
+        presses += 1;
+        press_button(&mut modules, |pulse| {
+            if pulse.receiver == feeder && !pulse.low {
+                let seen = high_presses.entry(pulse.sender).or_default();
+                if seen.last() != Some(&presses) {
+                    seen.push(presses);
+                }
+            }
+        });
+    }
+
+    // Each input should be periodic
+    let mut cycles = Vec::new();
+    for input in feeder_inputs {
+        let seen = high_presses.remove(input).unwrap_or_default();
+        if seen.len() < 3 {
+            return Err(CycleError::NoCycle {
+                input: input.to_string(),
+                presses,
+            });
+        }
+        let period = seen[1] - seen[0];
+        if seen[2] - seen[1] != period {
+            return Err(CycleError::Irregular {
+                input: input.to_string(),
+                presses: seen,
+            });
+        }
+        cycles.push(InputCycle {
+            input,
+            first: seen[0],
+            period,
+        });
+    }
+
+    // The earliest press on or after every first high pulse that is in all cycles
+    let (residue, modulus) = math::crt(cycles.iter().map(|c| (c.first as i128, c.period as i128)))
+        .ok_or(CycleError::NeverAligned)?;
+    let latest_first = cycles.iter().map(|c| c.first as i128).max().unwrap();
+    let mut press = residue;
+    if press < latest_first {
+        press += (latest_first - press + modulus - 1) / modulus * modulus;
+    }
+
+    Ok(RxAnalysis {
+        feeder,
+        cycles,
+        presses: press as u64,
+    })
+}
+
+pub fn solution1(input: &str) -> Solution {
+    let module_data = module_data_from_str(input)?;
+    bench::parsed();
+    check_broadcaster(&module_data)?;
+    let mut modules = modules_from_data(module_data);
 
     let mut low_count: u64 = 0;
     let mut high_count: u64 = 0;
     for _ in 0..1000 {
-        let button_pulse = Pulse {
-            sender: "button",
-            receiver: "broadcaster",
-            low: true
-        };
-        let mut pulse_queue = vec![button_pulse];
-        while !pulse_queue.is_empty() {
-            let current_pulse = pulse_queue.remove(0);
-            if current_pulse.low {
+        press_button(&mut modules, |pulse| {
+            if pulse.low {
                 low_count += 1;
             } else {
                 high_count += 1;
             }
-            let module = modules.get_mut(current_pulse.receiver).unwrap();
-            let mut pulses = module.receive(current_pulse);
-            pulse_queue.append(&mut pulses);
-        }
+        });
     }
 
-    submit!(1, low_count * high_count);
+    Ok((low_count * high_count).into())
 }
 
-#[aocd(2023, 20)]
-pub fn solution2() {
-    let input_data = input!();
-
-    // Get names of all referenced modules in senders or receivers
-    let module_names: Vec<&str> = input_data
-        .split('\n')
-        .flat_map(get_all_module_names_from_str)
-        .collect();
-    
-    // Data structure to store all pertinent module data for each referenced module
-    let mut module_data: HashMap<&str, ModuleData> = module_names
-        .iter()
-        .map(|s| {
-            (*s, ModuleData::new(s))
-        })
-        .collect();
-
-    // Loop back through the input data and fill the data structure
-    for data in input_data.split('\n') {
-        let sender = get_sender_from_str(data);
-        let receivers = get_receivers_from_str(data);
-        let module_type = get_module_type_from_str(data);
-        let module = module_data.get_mut(&sender).unwrap();
-        module.receivers = receivers;
-        module.module = module_type;
-    }        
-    
-    // Loop back through the input data and fill in input modules
-    for data in input_data.split('\n') {
-        let sender = get_sender_from_str(data);
-        let receivers = get_receivers_from_str(data);
-        // For each receiver listed, find that module and add in the sender as an input
-        for receiver in receivers.iter() {
-            let module = module_data.get_mut(receiver).unwrap();
-            module.inputs.push(sender);
-        }
+pub fn solution2(input: &str) -> Solution {
+    let module_data = module_data_from_str(input)?;
+    bench::parsed();
+    check_broadcaster(&module_data)?;
+    let analysis = analyze_rx(module_data, 100_000)?;
+
+    Ok(analysis.presses.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::answer::Answer;
+
+    const EXAMPLE_1: &str = "\
+broadcaster -> a, b, c
+%a -> b
+%b -> c
+%c -> inv
+&inv -> a";
+
+    const EXAMPLE_2: &str = "\
+broadcaster -> a
+%a -> inv, con
+&inv -> b
+%b -> con
+&con -> output";
+
+    // Two counters, reset every 3 and every 5 presses, feeding rx through `fd`
+    const EXAMPLE_RX: &str = "\
+broadcaster -> a0, b0
+%a0 -> a1, ca
+%a1 -> ca
+&ca -> a0, ia
+&ia -> fd
+%b0 -> b1, cb
+%b1 -> b2
+%b2 -> cb
+&cb -> b0, b1, ib
+&ib -> fd
+&fd -> rx";
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(solution1(EXAMPLE_1), Ok(Answer::Int(32000000)));
     }
 
-    // Create modules from the module data
-    let mut modules: HashMap<&str, Box<dyn Module>> = module_data.into_iter()
-        .map(|(module_name, module_data)| {
-            (module_name, module_data.to_module())
-        })
-        .collect();
-
-    let mut button_presses = 0;
-    let mut rx_low_pulse_count = 0;
-    while rx_low_pulse_count != 1 {
-        let button_pulse = Pulse {
-            sender: "button",
-            receiver: "broadcaster",
-            low: true
-        };
-        button_presses += 1;
-        rx_low_pulse_count = 0;
-        let mut pulse_queue = vec![button_pulse];
-        while !pulse_queue.is_empty() {
-            let current_pulse = pulse_queue.remove(0);
-            if current_pulse.receiver == "rx" && current_pulse.low {
-                rx_low_pulse_count += 1;
-            }
-            let module = modules.get_mut(current_pulse.receiver).unwrap();
-            let mut pulses = module.receive(current_pulse);
-            pulse_queue.append(&mut pulses);
+    #[test]
+    fn example_part1_interesting() {
+        assert_eq!(solution1(EXAMPLE_2), Ok(Answer::Int(11687500)));
+    }
+
+    #[test]
+    fn example_part2() {
+        assert_eq!(solution2(EXAMPLE_RX), Ok(Answer::Int(15)));
+        let analysis = analyze_rx(module_data_from_str(EXAMPLE_RX).unwrap(), 1000).unwrap();
+        assert_eq!(analysis.feeder, "fd");
+        assert_eq!(
+            analysis.cycles,
+            vec![
+                InputCycle {
+                    input: "ia",
+                    first: 3,
+                    period: 3
+                },
+                InputCycle {
+                    input: "ib",
+                    first: 5,
+                    period: 5
+                }
+            ]
+        );
+
+        // The analysis agrees with pressing the button until rx gets a low pulse
+        let mut modules = modules_from_data(module_data_from_str(EXAMPLE_RX).unwrap());
+        let mut presses = 0;
+        let mut rx_low = false;
+        while !rx_low {
+            presses += 1;
+            press_button(&mut modules, |pulse| {
+                rx_low |= pulse.receiver == "rx" && pulse.low
+            });
         }
+        assert_eq!(presses, analysis.presses);
+    }
+
+    #[test]
+    fn malformed_input() {
+        let error = solution1("broadcaster -> a\n%a -> b, c-d").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!(
+            (error.line, error.column, error.text.as_str()),
+            (2, 10, "c-d")
+        );
+        let error = solution2("broadcaster -> a\n*a -> b").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!(
+            (error.line, error.column, error.text.as_str()),
+            (2, 1, "*a")
+        );
+        let error = solution1("broadcaster a").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!((error.line, error.column), (1, 1));
+        assert_eq!(
+            solution1("%a -> broadcaster"),
+            Err(SolveError::unsolved(DAY, "there is no broadcaster"))
+        );
     }
 
-    submit!(2, button_presses);
+    #[test]
+    fn unexpected_cycles() {
+        assert_eq!(
+            analyze_rx(module_data_from_str(EXAMPLE_1).unwrap(), 1000),
+            Err(CycleError::NoFeeder)
+        );
+        assert_eq!(
+            analyze_rx(
+                module_data_from_str("broadcaster -> x\n%x -> rx").unwrap(),
+                1000
+            ),
+            Err(CycleError::FeederNotConjunction("x".to_string()))
+        );
+        assert_eq!(
+            analyze_rx(module_data_from_str(EXAMPLE_RX).unwrap(), 4),
+            Err(CycleError::NoCycle {
+                input: "ia".to_string(),
+                presses: 4
+            })
+        );
+        assert_eq!(
+            solution2(EXAMPLE_1).unwrap_err().to_string(),
+            "Day 20 input has no answer: could not find when rx gets a low pulse, \
+             no module sends pulses to rx"
+        );
+    }
 }
@@ -1,171 +1,253 @@
-use aocd::*;
+use crate::answer::Solution;
+use crate::bench;
+use crate::geometry::Coord;
+use crate::grid;
+use crate::parse::ParseError;
+use crate::search;
+use std::fmt;
+use std::ops::RangeInclusive;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-struct Coord(i32, i32);
-
-impl Coord {
-    fn from_i32(i: i32, j: i32) -> Coord {
-        Coord(i, j)
-    }
-
-    fn from_usize(i: usize, j: usize) -> Coord {
-        Coord(i as i32, j as i32)
-    }
-
-    fn index(&self) -> (usize, usize) {
-        (self.0 as usize, self.1 as usize)
-    }
-
-    fn adjacent(&self) -> [Coord; 4] {
-        [
-            Coord::from_i32(self.0 - 1, self.1),
-            Coord::from_i32(self.0 + 1, self.1),
-            Coord::from_i32(self.0, self.1 - 1),
-            Coord::from_i32(self.0, self.1 + 1)
-        ]
-    }
-}
+const DAY: u8 = 21;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum TileType {
     Start,
     Terrain,
-    Empty
+    Empty,
 }
 
 #[derive(Debug, Clone, Copy)]
 struct Tile {
     tile_type: TileType,
-    position: Coord,
-    on: bool
 }
 
 impl Tile {
-    fn new(c: char, i: usize, j: usize) -> Tile {
+    fn new(c: char) -> Option<Tile> {
         let tile_type = match c {
             '#' => TileType::Terrain,
             '.' => TileType::Empty,
             'S' => TileType::Start,
-            _ => unreachable!("Undefined tile type")
+            _ => return None,
         };
-        Tile {
-            tile_type,
-            position: Coord::from_usize(i, j),
-            on: false
-        }
+        Some(Tile { tile_type })
     }
 
-    fn str(&self) -> char {
-        match (self.tile_type, self.on) {
+    fn str(&self, on: bool) -> char {
+        match (self.tile_type, on) {
             (TileType::Terrain, _) => '#',
             (TileType::Empty, true) => 'O',
             (TileType::Empty, false) => '.',
-            (TileType::Start, _) => 'S' 
+            (TileType::Start, _) => 'S',
         }
     }
 }
 
-struct Grid {
-    data: Vec<Vec<Tile>>,
+/// Whether the garden is surrounded by rocks or repeats infinitely in every direction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tiling {
+    Single,
+    Infinite,
+}
+
+pub struct Grid {
+    data: grid::Grid<Tile>,
+    start: Coord,
     on: Vec<Coord>,
-    nrows: usize,
-    ncols: usize
 }
 
 impl Grid {
-    fn read_from_string(input_data: String) -> Grid {
-        let mut start_i = 0;
-        let mut start_j = 0;
-        let data: Vec<Vec<Tile>> = input_data
-            .lines()
+    pub fn read_from_string(input: &str) -> Result<Grid, ParseError> {
+        let data = grid::Grid::try_parse(input, DAY, "expected '.', '#' or S", Tile::new)?;
+        let starts: Vec<(usize, usize)> = data
             .enumerate()
-            .map(|(i, s)| {
-                s.chars()
-                    .enumerate()
-                    .map(|(j, c)| {
-                        if c == 'S' {
-                            start_i = i;
-                            start_j = j;
-                        }
-                        Tile::new(c, i, j)
-                    })
-                    .collect()
-            })
+            .filter(|(_, t)| t.tile_type == TileType::Start)
+            .map(|(position, _)| position)
             .collect();
-        let nrows = data.len();
-        let ncols = data[0].len();
-        let start_coord = Coord::from_usize(start_i, start_j);
-        Grid {
+        let start = match starts[..] {
+            [start] => start,
+            _ => {
+                let (i, j) = starts.get(1).copied().unwrap_or((0, 0));
+                let text = if starts.is_empty() { "" } else { "S" };
+                let error = ParseError::new(DAY, j + 1, text, "expected a single S");
+                return Err(error.on_line(i + 1));
+            }
+        };
+        let start_coord = Coord::from_index(start);
+        Ok(Grid {
             data,
+            start: start_coord,
             on: vec![start_coord],
-            nrows,
-            ncols
+        })
+    }
+
+    fn tile(&self, coord: &Coord, tiling: Tiling) -> Option<&Tile> {
+        match tiling {
+            Tiling::Single => self.data.at(*coord),
+            Tiling::Infinite => {
+                // Wrap the coordinate back onto the original garden
+                let i = coord.i.rem_euclid(self.data.nrows() as i64);
+                let j = coord.j.rem_euclid(self.data.ncols() as i64);
+                self.data.at(Coord::new(i, j))
+            }
         }
     }
 
-    fn tile(&self, coord: &Coord) -> &Tile {
-        let (i, j) = coord.index();
-        &self.data[i][j]
+    /// Turn on every tile the elf can be standing on after exactly `steps` steps.
+    /// The elf can step back and forth, so a tile is reachable if it is at most `steps`
+    /// away and its distance has the same parity as `steps`.
+    fn walk(&mut self, steps: usize, tiling: Tiling) {
+        // A tile further than `steps` from the start as the crow flies can't be reached,
+        // which keeps the search finite on an infinite garden
+        let distances = search::bfs_distances(self.start, |coord| {
+            coord
+                .neighbors()
+                .into_iter()
+                .filter(|c| c.manhattan(self.start) as usize <= steps)
+                .filter(|c| {
+                    self.tile(c, tiling)
+                        .is_some_and(|t| t.tile_type != TileType::Terrain)
+                })
+                .collect::<Vec<Coord>>()
+        });
+        self.on = distances
+            .into_iter()
+            .filter(|&(_, d)| d <= steps && d % 2 == steps % 2)
+            .map(|(coord, _)| coord)
+            .collect();
+        self.on.sort();
     }
 
-    fn out_of_bounds(&self, coord: &Coord) -> bool {
-        coord.0 < 0 || coord.0 >= self.nrows as i32 || coord.1 < 0 || coord.1 >= self.ncols as i32
+    fn count_on(&self) -> usize {
+        self.on.len()
     }
+}
 
-    fn display(&self) {
-        for row in self.data.iter() {
+impl fmt::Display for Grid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, row) in self.data.rows().enumerate() {
             let row_str: String = row
                 .iter()
-                .map(|t| t.str())
+                .enumerate()
+                .map(|(j, t)| t.str(self.on.contains(&Coord::from_index((i, j)))))
                 .collect();
-            println!("{}", row_str);
+            writeln!(f, "{}", row_str)?;
         }
+        Ok(())
     }
+}
 
-    fn traverse(&mut self) {
-        self.on = self.on
-            .iter()
-            .flat_map(|coord| {
-                coord.adjacent()
-            })
-            .filter(|coord| {
-                !self.out_of_bounds(coord) && self.tile(coord).tile_type != TileType::Terrain
-            })
-            .collect();
-        self.on.sort();
-        self.on.dedup();
-    }
+/// Number of garden plots the elf can end up on after exactly the given number of steps
+pub fn count_reachable(grid: &mut Grid, steps: usize, tiling: Tiling) -> usize {
+    grid.walk(steps, tiling);
+    grid.count_on()
+}
 
-    fn sync_on(&mut self) {
-        for row in self.data.iter_mut() {
-            for tile in row.iter_mut() {
-                tile.on = false;
+/// Number of plots reachable on the infinite garden, for step counts too large to search.
+/// Once the reachable area spans a few copies of the garden, it grows by whole copies each
+/// time the elf walks another garden width, so the count is quadratic in the number of
+/// widths walked. Search step counts with the same remainder until the second difference
+/// settles, then continue the quadratic from there.
+pub fn extrapolate_reachable(grid: &mut Grid, steps: usize) -> u64 {
+    let size = grid.data.nrows();
+    let remainder = steps % size;
+    let widths = steps / size;
+    let mut counts: Vec<i64> = Vec::new();
+    for n in 0..=widths {
+        grid.walk(remainder + n * size, Tiling::Infinite);
+        counts.push(grid.count_on() as i64);
+        if let [a, b, c, d] = counts[counts.len().saturating_sub(4)..] {
+            let second_difference = d - 2 * c + b;
+            if second_difference == c - 2 * b + a {
+                let m = (widths - n) as i64;
+                return (d + m * (d - c) + m * (m + 1) / 2 * second_difference) as u64;
             }
         }
-        for coord in self.on.iter() {
-            let (i, j) = coord.index();
-            self.data[i][j].on = true;
+    }
+    *counts.last().unwrap() as u64
+}
+
+/// Brute force check of the extrapolation: the step counts in the range whose extrapolated
+/// count differs from a full search, with both counts
+pub fn check_extrapolation(
+    grid: &mut Grid,
+    steps: RangeInclusive<usize>,
+) -> Vec<(usize, u64, u64)> {
+    steps
+        .map(|steps| {
+            let searched = count_reachable(grid, steps, Tiling::Infinite) as u64;
+            (steps, extrapolate_reachable(grid, steps), searched)
+        })
+        .filter(|(_, extrapolated, searched)| extrapolated != searched)
+        .collect()
+}
+
+pub fn solution1(input: &str) -> Solution {
+    let mut grid = Grid::read_from_string(input)?;
+    bench::parsed();
+    Ok(count_reachable(&mut grid, 64, Tiling::Single).into())
+}
+
+pub fn solution2(input: &str) -> Solution {
+    let mut grid = Grid::read_from_string(input)?;
+    bench::parsed();
+    Ok(extrapolate_reachable(&mut grid, 26501365).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+...........
+.....###.#.
+.###.##..#.
+..#.#...#..
+....#.#....
+.##..S####.
+.##..#...#.
+.......##..
+.##.#.####.
+.##..##.##.
+...........";
+
+    #[test]
+    fn example_part1() {
+        let mut grid = Grid::read_from_string(EXAMPLE).unwrap();
+        assert_eq!(count_reachable(&mut grid, 6, Tiling::Single), 16);
+    }
+
+    #[test]
+    fn example_part2() {
+        let mut grid = Grid::read_from_string(EXAMPLE).unwrap();
+        for (steps, plots) in [(6, 16), (10, 50), (50, 1594), (100, 6536)] {
+            assert_eq!(count_reachable(&mut grid, steps, Tiling::Infinite), plots);
         }
+        assert_eq!(extrapolate_reachable(&mut grid, 1000), 668697);
+        assert_eq!(extrapolate_reachable(&mut grid, 5000), 16733044);
     }
 
-    fn count_on(&self) -> usize {
-        self.on.len()
+    #[test]
+    fn malformed_input() {
+        let error = solution1("...\n.S.\n.O.").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!((error.line, error.column, error.text.as_str()), (3, 2, "O"));
+        let error = solution2("...\n.S.\n..S").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!(
+            (error.line, error.column, error.reason.as_str()),
+            (3, 3, "expected a single S")
+        );
+        let error = solution1("...\n...").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!(
+            (error.line, error.reason.as_str()),
+            (1, "expected a single S")
+        );
     }
-}
 
-#[aocd(2023, 21)]
-pub fn solution1() {
-    let input_data = input!();
-    let mut grid = Grid::read_from_string(input_data);
-    for _ in 0..64 {
-        grid.traverse();
+    #[test]
+    fn extrapolation_matches_search() {
+        let mut grid = Grid::read_from_string(EXAMPLE).unwrap();
+        assert_eq!(check_extrapolation(&mut grid, 72..=80), vec![]);
     }
-    grid.sync_on();
-    grid.display();
-    submit!(1, grid.count_on());
-}   
-
-#[aocd(2023, 21)]
-pub fn solution2() {
-    let input_data = input!();
-}
\ No newline at end of file
+}
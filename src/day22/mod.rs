@@ -1,59 +1,59 @@
-use aocd::*;
-
-#[derive(Debug, Eq, PartialEq, PartialOrd, Ord, Clone, Copy)]
-struct Coord {
-    x: u32,
-    y: u32,
-    z: u32
-}
-
-impl Coord {
-    fn from_str(s: &str) -> Coord {
-        let values: Vec<u32> = s
-            .split(',')
-            .map(|c| c.parse::<u32>().unwrap())
-            .collect();
-        Coord {
-            x: values[0],
-            y: values[1],
-            z: values[2]
-        }
+use crate::answer::Solution;
+use crate::bench;
+use crate::geometry::Coord3;
+use crate::parse::{self, ParseError};
+use std::collections::HashMap;
+
+const DAY: u8 = 22;
+
+/// Parse a corner such as `1,0,1`
+fn parse_coord(s: &str) -> Result<Coord3, ParseError> {
+    let values: Vec<i64> = parse::fields(s, ',')
+        .map(|(column, value)| parse::number(DAY, column, value))
+        .collect::<Result<_, _>>()?;
+    match values[..] {
+        [x, y, z] => Ok(Coord3::new(x, y, z)),
+        _ => Err(ParseError::new(DAY, 1, s, "expected three coordinates")),
     }
 }
 
 #[derive(Debug, Clone, Copy)]
-struct Brick {
-    a: Coord,
-    b: Coord
+pub struct Brick {
+    a: Coord3,
+    b: Coord3,
 }
 
 impl Brick {
-    fn from_str(s: &str) -> Brick {
-        let mut coords: Vec<Coord> = s.split('~').map(Coord::from_str).collect();
-        let b = coords.pop().unwrap();
-        let a = coords.pop().unwrap();
-        Brick { a, b }
+    /// Parse a brick such as `1,0,1~1,2,1` from the coordinates of two opposite corners
+    fn from_str(s: &str) -> Result<Brick, ParseError> {
+        let (a, b) = s
+            .split_once('~')
+            .ok_or_else(|| ParseError::new(DAY, 1, s, "expected two corners joined by '~'"))?;
+        Ok(Brick {
+            a: parse_coord(a)?,
+            b: parse_coord(b).map_err(|e| e.shifted(a.len() + 1))?,
+        })
     }
 
-    fn x_range(&self) -> (u32, u32) {
+    fn x_range(&self) -> (i64, i64) {
         let max = self.a.x.max(self.b.x);
         let min = self.a.x.min(self.b.x);
         (min, max)
     }
 
-    fn y_range(&self) -> (u32, u32) {
+    fn y_range(&self) -> (i64, i64) {
         let max = self.a.y.max(self.b.y);
         let min = self.a.y.min(self.b.y);
         (min, max)
     }
 
-    fn z_range(&self) -> (u32, u32) {
+    fn z_range(&self) -> (i64, i64) {
         let max = self.a.z.max(self.b.z);
         let min = self.a.z.min(self.b.z);
         (min, max)
     }
 
-    fn overlaps(range_a: (u32, u32), range_b: (u32, u32)) -> u8 {
+    fn overlaps(range_a: (i64, i64), range_b: (i64, i64)) -> u8 {
         if range_a.0 <= range_b.1 && range_a.1 >= range_b.0 {
             1
         } else {
@@ -61,7 +61,7 @@ impl Brick {
         }
     }
 
-    fn intersects(&self, other: &Brick) -> bool {
+    pub fn intersects(&self, other: &Brick) -> bool {
         let overlap_x = Brick::overlaps(self.x_range(), other.x_range());
         let overlap_y = Brick::overlaps(self.y_range(), other.y_range());
         let overlap_z = Brick::overlaps(self.z_range(), other.z_range());
@@ -69,230 +69,235 @@ impl Brick {
         overlap == 3
     }
 
-    fn min_z(&self) -> u32 {
+    fn min_z(&self) -> i64 {
         self.a.z.min(self.b.z)
     }
 
-    fn max_z(&self) -> u32 {
+    fn max_z(&self) -> i64 {
         self.a.z.max(self.b.z)
     }
 
-    fn drop(&mut self, spaces: u32) {
+    fn drop(&mut self, spaces: i64) {
         self.a.z -= spaces;
         self.b.z -= spaces;
     }
 }
 
-fn drop_bricks(mut bricks: Vec<Brick>) -> (u32, Vec<Brick>) {
-    // Number of bricks fallen
-    let mut bricks_fallen = 0;
-
-    // Sort by highest to lowest
-    bricks.sort_by_key(|b| b.min_z());
-
-    // Drop first brick
-    let brick = bricks.get_mut(0).unwrap();
-    let min_z = brick.min_z();
-    let drop_distance = min_z - 1;
-    brick.drop(drop_distance);
-    if drop_distance > 0 {
-        bricks_fallen += 1;
-    }
-
-    // Drop the rest
-    let mut index = 1;
-    'iterbricks: loop {
-        let mut brick_fell = false;
-
-        // Past last brick, stop loop
-        if index == bricks.len() {
-            break 'iterbricks;
-        }
-
-        // Get the bottom of the current brick
-        let z_min = bricks[index].min_z();
-
-        // Stop if at the bottom already
-        if z_min == 1 {
-            index += 1;
-            continue 'iterbricks;
-        }
+/// Settled bricks, with which bricks rest directly on which
+pub struct Stack {
+    bricks: Vec<Brick>,
+    /// Bricks resting directly on top of each brick
+    supports: Vec<Vec<usize>>,
+    /// Bricks each brick rests directly on, none for bricks on the ground
+    supported_by: Vec<Vec<usize>>,
+}
 
-        // Get the top of all the bricks below the current brick
-        let z_max_option = bricks
-            .iter()
-            .filter(|b| b.max_z() < z_min)
-            .map(|b| b.max_z())
-            .max();
-
-        // If there is a max, drop the current brick to one above that one
-        if let Some(z_max) = z_max_option {
-            let drop_distance = z_min - z_max - 1;
-            bricks[index].drop(drop_distance);
-            if drop_distance > 0 {
-                brick_fell = true;
+impl Stack {
+    /// Let every brick fall as far as it can. Bricks are dropped from the lowest up,
+    /// tracking the top of the stack and the brick there over every x/y column, so each
+    /// brick lands in one step on the highest column under it.
+    pub fn settle(mut bricks: Vec<Brick>) -> Stack {
+        bricks.sort_by_key(|b| b.min_z());
+        let mut heights: HashMap<(i64, i64), (i64, usize)> = HashMap::new();
+        let mut supports = vec![Vec::new(); bricks.len()];
+        let mut supported_by = vec![Vec::new(); bricks.len()];
+
+        for (i, brick) in bricks.iter_mut().enumerate() {
+            let (x_min, x_max) = brick.x_range();
+            let (y_min, y_max) = brick.y_range();
+            let columns: Vec<(i64, i64)> = (x_min..=x_max)
+                .flat_map(|x| (y_min..=y_max).map(move |y| (x, y)))
+                .collect();
+
+            // Land on the highest column, resting on every brick at that height
+            let top = columns
+                .iter()
+                .filter_map(|c| heights.get(c))
+                .map(|(height, _)| *height)
+                .max()
+                .unwrap_or(0);
+            brick.drop(brick.min_z() - top - 1);
+            for column in columns.iter() {
+                if let Some(&(height, below)) = heights.get(column) {
+                    if height == top && !supported_by[i].contains(&below) {
+                        supported_by[i].push(below);
+                        supports[below].push(i);
+                    }
+                }
+                heights.insert(*column, (brick.max_z(), i));
             }
         }
 
-        // Keep dropping it until it is blocked by another brick or the floor
-        // Initial drop distance is 0
-        let mut drop_distance = 0;
-        // Get a copy of the current brick
-        let mut brick = bricks[index].clone();
-        'dropping: loop {
-            // Drop it 1 space down
-            brick.drop(1);
-            let min_z = brick.min_z();
-
-            // If we've hit the floor, stop dropping
-            if min_z == 0 {
-                break 'dropping;
-            }
-
-            // Otherwise, check for collisions with any of the bricks immediately below it
-            let collision = bricks
-                .iter()
-                .filter(|b| b.max_z() == min_z)
-                .any(|b| brick.intersects(b));
+        Stack {
+            bricks,
+            supports,
+            supported_by,
+        }
+    }
 
-            // If there is a collision, stop dropping
-            if collision {
-                break 'dropping;
-            }
+    pub fn bricks(&self) -> &[Brick] {
+        &self.bricks
+    }
 
-            // Keep track of how far we've dropped
-            drop_distance += 1;
-        }
+    /// Whether each brick can be removed without any other brick falling, which is when
+    /// every brick it supports rests on another brick as well
+    pub fn safe_to_disintegrate(&self) -> Vec<bool> {
+        self.supports
+            .iter()
+            .map(|above| above.iter().all(|j| self.supported_by[*j].len() > 1))
+            .collect()
+    }
 
-        // Apply the fall to the actual brick
-        bricks[index].drop(drop_distance);
-        if drop_distance > 0 {
-            brick_fell = true;
+    /// Number of other bricks that fall if each brick is removed. A brick falls exactly
+    /// when every path of support from it down to the ground passes through the removed
+    /// brick, so the falling bricks are those it dominates. Bricks are settled bottom up,
+    /// so each brick's immediate dominator, the lowest common dominator of its supports,
+    /// is known by the time it is reached, and subtree sizes add up top down.
+    pub fn chain_reactions(&self) -> Vec<usize> {
+        let n = self.bricks.len();
+        let mut dominator: Vec<Option<usize>> = vec![None; n];
+        let mut depth = vec![0; n];
+        for i in 0..n {
+            let mut below = self.supported_by[i].iter().map(|j| Some(*j));
+            let first = below.next().flatten();
+            dominator[i] = below.fold(first, |a, b| common_dominator(a, b, &dominator, &depth));
+            depth[i] = dominator[i].map_or(0, |d| depth[d] + 1);
         }
 
-        // Count fallen brick
-        if brick_fell {
-            bricks_fallen += 1;
+        let mut fallen = vec![0; n];
+        for i in (0..n).rev() {
+            if let Some(d) = dominator[i] {
+                fallen[d] += fallen[i] + 1;
+            }
         }
-
-        // Go the next brick
-        index += 1;
+        fallen
     }
-
-    (bricks_fallen, bricks)
 }
 
-fn is_packed(bricks: Vec<Brick>) -> bool {
-    let n_bricks = bricks.len();
-    // For each brick...
-    'simulation: for i in 0..n_bricks {
-        // Create a copy of the current brick for simulating
-        let mut simulate_brick = bricks[i].clone();
-        let min_z = simulate_brick.min_z();
-        // If the current brick is on the floor, go to the next brick
-        if min_z == 1 {
-            continue 'simulation;
-        }
-        // Drop the brick one space
-        simulate_brick.drop(1);
-        // Check if there is a collision with any other bricks immediately below it
-        let collision = bricks
-            .iter()
-            .filter(|b| b.max_z() == min_z - 1)
-            .any(|b| simulate_brick.intersects(b));
-        // If there isn't, then the brick can move, and the stack is not packed
-        if !collision {
-            return false;
+/// Lowest common ancestor of two bricks in the dominator tree, with `None` as the ground
+fn common_dominator(
+    mut a: Option<usize>,
+    mut b: Option<usize>,
+    dominator: &[Option<usize>],
+    depth: &[usize],
+) -> Option<usize> {
+    while a != b {
+        match (a, b) {
+            (Some(i), Some(j)) if depth[i] >= depth[j] => a = dominator[i],
+            (Some(_), Some(j)) => b = dominator[j],
+            _ => return None,
         }
     }
-    // The loop has gone through each brick without detecting any free room
-    true
+    a
 }
 
-#[aocd(2023, 22)]
-pub fn solution1() {
-    let input_data = input!();
-
+pub fn solution1(input: &str) -> Solution {
     // Read bricks
-    let bricks: Vec<Brick> = input_data
-        .lines()
-        .map(|line| Brick::from_str(line))
-        .collect();
-
-    // Initial brick drop
-    let (_, settled_bricks) = drop_bricks(bricks);
-    let n_bricks = settled_bricks.len();
-
-    // For each brick, check if it can be removed
-    let mut destroyable = 0;
-    for i in 0..n_bricks {
-        let mut simulation_bricks = settled_bricks.clone();
-        simulation_bricks.remove(i);
-        let packed = is_packed(simulation_bricks);
-        if packed {
-            destroyable += 1;
-        }
-    }
+    let bricks: Vec<Brick> = parse::lines(input, Brick::from_str)?;
+    bench::parsed();
 
-    submit!(1, destroyable);
-}
+    let stack = Stack::settle(bricks);
+    let destroyable = stack
+        .safe_to_disintegrate()
+        .into_iter()
+        .filter(|safe| *safe)
+        .count();
 
-#[aocd(2023, 22)]
-pub fn solution2() {
-    let input_data = input!();
+    Ok(destroyable.into())
+}
 
+pub fn solution2(input: &str) -> Solution {
     // Read bricks
-    let bricks: Vec<Brick> = input_data
-        .lines()
-        .map(|line| Brick::from_str(line))
-        .collect();
-
-    // Initial brick drop
-    let (_, settled_bricks) = drop_bricks(bricks);
-    let n_bricks = settled_bricks.len();
-
-    // For each brick, check if it can be removed
-    let mut bricks_fallen = 0;
-    for i in 0..n_bricks {
-        println!("Simulation {i}/{n_bricks}");
-        let mut simulation_bricks = settled_bricks.clone();
-        simulation_bricks.remove(i);
-        let (bricks_fallen_incr, _) = drop_bricks(simulation_bricks);
-        bricks_fallen += bricks_fallen_incr;
-    }
+    let bricks: Vec<Brick> = parse::lines(input, Brick::from_str)?;
+    bench::parsed();
 
-    submit!(2, bricks_fallen);
+    let stack = Stack::settle(bricks);
+    let bricks_fallen: usize = stack.chain_reactions().into_iter().sum();
+
+    Ok(bricks_fallen.into())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::answer::Answer;
+
+    const EXAMPLE: &str = "\
+1,0,1~1,2,1
+0,0,2~2,0,2
+0,2,3~2,2,3
+0,0,4~0,2,4
+2,0,5~2,2,5
+0,1,6~2,1,6
+1,1,8~1,1,9";
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(solution1(EXAMPLE), Ok(Answer::Int(5)));
+    }
+
+    #[test]
+    fn example_part2() {
+        assert_eq!(solution2(EXAMPLE), Ok(Answer::Int(7)));
+    }
+
+    #[test]
+    fn malformed_input() {
+        let error = solution1("1,0,1~1,2,1\n0,0,2~2,x,2").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!((error.line, error.column, error.text.as_str()), (2, 9, "x"));
+        let error = solution2("1,0,1~1,2").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!(
+            (error.column, error.reason.as_str()),
+            (7, "expected three coordinates")
+        );
+        let error = solution1("1,0,1").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!((error.column, error.text.as_str()), (1, "1,0,1"));
+    }
+
+    #[test]
+    fn support_graph() {
+        let bricks: Vec<Brick> = parse::lines(EXAMPLE, Brick::from_str).unwrap();
+        let stack = Stack::settle(bricks);
+        assert_eq!(stack.supported_by[1], vec![0]);
+        assert_eq!(stack.supports[1], vec![3, 4]);
+        assert_eq!(stack.supported_by[5], vec![3, 4]);
+        assert_eq!(stack.bricks()[6].min_z(), 5);
+        assert_eq!(stack.chain_reactions(), vec![6, 0, 0, 0, 0, 1, 0]);
+
+        // No two settled bricks overlap
+        for (i, a) in stack.bricks().iter().enumerate() {
+            assert!(stack.bricks()[..i].iter().all(|b| !a.intersects(b)));
+        }
+    }
 
     #[test]
     fn intersects() {
-        let brick_a = Brick::from_str("0,1,0~2,1,0");
-        let brick_b = Brick::from_str("1,0,0~1,2,0");
+        let brick_a = Brick::from_str("0,1,0~2,1,0").unwrap();
+        let brick_b = Brick::from_str("1,0,0~1,2,0").unwrap();
         assert!(brick_a.intersects(&brick_b));
     }
 
     #[test]
     fn does_not_intersect() {
-        let brick_a = Brick::from_str("0,1,0~2,1,0");
-        let brick_b = Brick::from_str("10,0,0~10,2,0");
+        let brick_a = Brick::from_str("0,1,0~2,1,0").unwrap();
+        let brick_b = Brick::from_str("10,0,0~10,2,0").unwrap();
         assert!(!brick_a.intersects(&brick_b));
     }
 
     #[test]
     fn intersects_cube() {
-        let brick_a = Brick::from_str("1,1,1~1,1,1");
-        let brick_b = Brick::from_str("0,1,1~2,1,1");
+        let brick_a = Brick::from_str("1,1,1~1,1,1").unwrap();
+        let brick_b = Brick::from_str("0,1,1~2,1,1").unwrap();
         assert!(brick_a.intersects(&brick_b));
     }
 
     #[test]
     fn does_not_intersect_cube() {
-        let brick_a = Brick::from_str("1,1,1~1,1,1");
-        let brick_b = Brick::from_str("0,1,0~2,1,0");
+        let brick_a = Brick::from_str("1,1,1~1,1,1").unwrap();
+        let brick_b = Brick::from_str("0,1,0~2,1,0").unwrap();
         assert!(!brick_a.intersects(&brick_b));
     }
-}
\ No newline at end of file
+}
@@ -1,37 +1,12 @@
+use crate::answer::{Solution, SolveError};
+use crate::bench;
+use crate::geometry::{Coord, Direction};
+use crate::grid;
+use crate::parse::ParseError;
 use std::collections::HashMap;
-use aocd::*;
+use std::fmt;
 
-const LARGE_NUMBER: i32 = i32::MAX / 2;
-
-#[derive(Hash, PartialEq, Eq, Clone, Copy, Debug)]
-struct Coord {
-    i: i32,
-    j: i32,
-}
-
-impl Coord {
-    fn new(i: i32, j: i32) -> Coord {
-        Coord { i, j }
-    }
-
-    fn manhattan(&self, other: &Coord) -> i32 {
-        // (self.i - other.i).abs() + (self.j - other.j).abs()
-        0
-    }
-
-    fn neighbors(&self) -> [Coord; 4] {
-        [
-            Coord::new(self.i - 1, self.j),
-            Coord::new(self.i + 1, self.j),
-            Coord::new(self.i, self.j - 1),
-            Coord::new(self.i, self.j + 1)
-        ]
-    }
-
-    fn as_usize(&self) -> (usize, usize) {
-        (self.i as usize, self.j as usize)
-    }
-}
+const DAY: u8 = 23;
 
 #[derive(Clone, Copy, PartialEq, Eq)]
 enum TileType {
@@ -40,19 +15,30 @@ enum TileType {
     SlopeUp,
     SlopeDown,
     SlopeLeft,
-    SlopeRight
+    SlopeRight,
 }
 
 impl TileType {
-    fn from_char(c: char) -> TileType {
+    fn from_char(c: char) -> Option<TileType> {
         match c {
-            '#' => TileType::Forest,
-            '.' => TileType::Path,
-            '^' => TileType::SlopeUp,
-            'v' => TileType::SlopeDown,
-            '>' => TileType::SlopeRight,
-            '<' => TileType::SlopeLeft,
-            _ => unreachable!("Undefined tile type.")
+            '#' => Some(TileType::Forest),
+            '.' => Some(TileType::Path),
+            '^' => Some(TileType::SlopeUp),
+            'v' => Some(TileType::SlopeDown),
+            '>' => Some(TileType::SlopeRight),
+            '<' => Some(TileType::SlopeLeft),
+            _ => None,
+        }
+    }
+
+    /// Direction a slope can be walked down in
+    fn slope(&self) -> Option<Direction> {
+        match self {
+            TileType::SlopeUp => Some(Direction::Up),
+            TileType::SlopeDown => Some(Direction::Down),
+            TileType::SlopeLeft => Some(Direction::Left),
+            TileType::SlopeRight => Some(Direction::Right),
+            TileType::Forest | TileType::Path => None,
         }
     }
 }
@@ -60,17 +46,15 @@ impl TileType {
 #[derive(Clone, Copy)]
 struct Tile {
     tile_type: TileType,
-    position: Coord,
-    visited: bool
+    visited: bool,
 }
 
 impl Tile {
-    fn new(c: char, i: usize, j: usize) -> Tile {
-        Tile {
-            tile_type: TileType::from_char(c),
-            position: Coord::new(i as i32, j as i32),
-            visited: false
-        }
+    fn new(c: char) -> Option<Tile> {
+        Some(Tile {
+            tile_type: TileType::from_char(c)?,
+            visited: false,
+        })
     }
 
     fn as_char(&self) -> char {
@@ -81,237 +65,343 @@ impl Tile {
             (TileType::SlopeUp, _) => '^',
             (TileType::SlopeDown, _) => 'v',
             (TileType::SlopeLeft, _) => '<',
-            (TileType::SlopeRight, _) => '>'
+            (TileType::SlopeRight, _) => '>',
         }
     }
 }
 
-
-/// Vector of Nodes that is always sorted by f-score
-#[derive(Default)]
-struct CoordHeap {
-    coords: Vec<Coord>,
+struct Grid {
+    data: grid::Grid<Tile>,
+    start: Coord,
+    end: Coord,
 }
 
-impl CoordHeap {
-    // Sort vector by least to most f-score
-    fn add_coord(&mut self, node: Coord, f_scores: &HashMap<Coord, i32>) {
-        self.coords.push(node);
-        self.coords.sort_by(|a, b| {
-            let a_score = *f_scores.get(&a).unwrap();
-            let b_score = *f_scores.get(&b).unwrap();
-            a_score.cmp(&b_score)
+impl Grid {
+    fn from_string(s: &str) -> Result<Grid, ParseError> {
+        // Extract tiles from string
+        let expected = "expected a path, forest or slope";
+        let data = grid::Grid::try_parse(s, DAY, expected, Tile::new)?;
+
+        // Find start and end tiles, on the path through the first and last rows
+        let nrows = data.nrows();
+        let [start, end] = [0, nrows - 1].map(|i| {
+            let j = data
+                .row(i)
+                .iter()
+                .rposition(|tile| tile.tile_type == TileType::Path)
+                .ok_or_else(|| {
+                    let row: String = data.row(i).iter().map(Tile::as_char).collect();
+                    ParseError::new(DAY, 1, &row, "expected a path").on_line(i + 1)
+                })?;
+            Ok(Coord::new(i as i64, j as i64))
         });
+
+        Ok(Grid {
+            data,
+            start: start?,
+            end: end?,
+        })
     }
 
-    fn is_empty(&self) -> bool {
-        self.coords.is_empty()
+    fn visit(&mut self, coord: &Coord) {
+        self.data[coord.to_index().unwrap()].visited = true;
     }
 
-    /// Returns the node with the maximum f-score
-    fn pop(&mut self) -> Coord {
-        self.coords.pop().unwrap()
+    fn out_of_bounds(&self, coord: &Coord) -> bool {
+        self.data.position_of(*coord).is_none()
     }
 
-    fn contains(&self, position: &Coord) -> bool {
-        self.coords.iter().any(|n| n == position)
+    fn tile(&self, coord: &Coord) -> &Tile {
+        &self.data[coord.to_index().unwrap()]
     }
-}
 
+    /// Tiles that can be stepped onto from a given tile. Slopes can only be walked down
+    /// if they are slippery, otherwise they are ordinary path.
+    fn successors(&self, current: &Coord, slippery: bool) -> Vec<Coord> {
+        let mut successors = Vec::new();
+        'traversal: for neighbor in current.neighbors() {
+            // Skip neighbors that are out of bounds
+            if self.out_of_bounds(&neighbor) {
+                continue 'traversal;
+            }
+            // Skip neighbors that are Forest (obstacles)
+            if self.tile(&neighbor).tile_type == TileType::Forest {
+                continue 'traversal;
+            }
+            // Skip neighbors that slope against current position
+            let step = Direction::between(*current, neighbor).unwrap();
+            if slippery && self.tile(&neighbor).tile_type.slope() == Some(step.reverse()) {
+                continue 'traversal;
+            }
+            successors.push(neighbor);
+        }
+        successors
+    }
 
-struct Grid {
-    data: Vec<Vec<Tile>>,
-    nrows: usize,
-    ncols: usize,
-    start: Coord,
-    end: Coord
-}
+    /// Tiles where the trail forks, along with the start and end
+    fn junctions(&self) -> Vec<Coord> {
+        let mut junctions = vec![self.start];
+        for (index, tile) in self.data.enumerate() {
+            let coord = Coord::from_index(index);
+            if tile.tile_type != TileType::Forest && self.successors(&coord, false).len() > 2 {
+                junctions.push(coord);
+            }
+        }
+        junctions.push(self.end);
+        junctions
+    }
 
-impl Grid {
-    fn from_string(s: String) -> Grid {
-        // Extract tiles from string
-        let data: Vec<Vec<Tile>> = s.lines()
+    /// Reduce the trails to a graph between junctions. Each corridor between two
+    /// junctions becomes a single weighted edge.
+    fn compress(&self, slippery: bool) -> JunctionGraph {
+        let junctions = self.junctions();
+        let index: HashMap<Coord, usize> = junctions
+            .iter()
             .enumerate()
-            .map(|(i, line)| {
-                line.chars()
-                    .enumerate()
-                    .map(|(j, c)| {
-                        Tile::new(c, i, j)
-                    })
-                    .collect::<Vec<Tile>>()
-            })
+            .map(|(i, coord)| (*coord, i))
             .collect();
 
-        // Count rows and columns
-        let nrows = data.len();
-        let ncols = data[0].len();
-
-        // Find start tile
-        let mut start = Coord::new(0, 0);
-        for tile in data[0].iter() {
-            if tile.tile_type == TileType::Path {
-                start = tile.position;
-            }
-        }
-
-        // Find end tile
-        let mut end = Coord::new(0, 0);
-        for tile in data[nrows-1].iter() {
-            if tile.tile_type == TileType::Path {
-                end = tile.position;
+        let mut trails = vec![Vec::new(); junctions.len()];
+        for (from, junction) in junctions.iter().enumerate() {
+            'corridor: for first in self.successors(junction, slippery) {
+                // Follow the corridor until it reaches another junction
+                let mut previous = *junction;
+                let mut tiles = vec![first];
+                while !index.contains_key(tiles.last().unwrap()) {
+                    let current = *tiles.last().unwrap();
+                    let next = self
+                        .successors(&current, slippery)
+                        .into_iter()
+                        .find(|c| *c != previous);
+                    match next {
+                        Some(next) => {
+                            previous = current;
+                            tiles.push(next);
+                        }
+                        // Dead end, or a slope that can't be climbed
+                        None => continue 'corridor,
+                    }
+                }
+                let to = index[tiles.last().unwrap()];
+                trails[from].push(Trail { to, tiles });
             }
         }
 
-        Grid {
-            data,
-            nrows,
-            ncols,
-            start,
-            end
+        JunctionGraph {
+            trails,
+            start: 0,
+            end: junctions.len() - 1,
         }
     }
 
-    fn display(&self) {
-        for row in self.data.iter() {
-            let row_str: String = row
-                .iter()
-                .map(|t| t.as_char())
-                .collect();
-            println!("{}", row_str);
+    /// Mark the tiles of the longest hike from start to end, and return its number of steps
+    fn hike(&mut self, slippery: bool) -> Result<u32, SolveError> {
+        let graph = self.compress(slippery);
+        let (length, path) = graph.longest_path()?;
+        let start = self.start;
+        self.visit(&start);
+        for (junction, i) in path {
+            for coord in graph.trails[junction][i].tiles.iter() {
+                self.visit(coord);
+            }
         }
-        println!("{}", self.score() - 1);
+        Ok(length)
     }
+}
 
-    fn visit(&mut self, coord: &Coord) {
-        let (i, j) = coord.as_usize();
-        self.data[i][j].visited = true;
-    }
+/// Corridor from one junction to another, with the tiles walked after leaving the first
+#[derive(Clone)]
+struct Trail {
+    to: usize,
+    tiles: Vec<Coord>,
+}
 
-    fn out_of_bounds(&self, coord: &Coord) -> bool {
-        coord.i < 0 || coord.i > self.nrows as i32 || coord.j < 0 || coord.j > self.ncols as i32
+impl Trail {
+    fn length(&self) -> u32 {
+        self.tiles.len() as u32
     }
+}
 
-    fn tile(&self, coord: &Coord) -> &Tile {
-        let (i, j) = coord.as_usize();
-        &self.data[i][j]
+/// Junctions of the trail map with the corridors leading out of each
+struct JunctionGraph {
+    trails: Vec<Vec<Trail>>,
+    start: usize,
+    end: usize,
+}
+
+impl JunctionGraph {
+    /// Longest hike that never visits a junction twice, as its length and the trails it
+    /// takes, each given by the junction it leaves and its index among that junction's
+    /// trails. Junctions are tracked in a bitmask, and branches that can't beat the best
+    /// hike so far are cut off.
+    fn longest_path(&self) -> Result<(u32, Vec<(usize, usize)>), SolveError> {
+        if self.trails.len() > 64 {
+            return Err(SolveError::unsolved(
+                DAY,
+                format!("there are {} junctions, more than 64", self.trails.len()),
+            ));
+        }
+
+        // The longest trail into each junction bounds what visiting it can still add
+        let mut longest_into = vec![0; self.trails.len()];
+        for trail in self.trails.iter().flatten() {
+            longest_into[trail.to] = longest_into[trail.to].max(trail.length());
+        }
+
+        let mut best = None;
+        let mut path = Vec::new();
+        self.search(
+            self.start,
+            1 << self.start,
+            0,
+            &longest_into,
+            &mut path,
+            &mut best,
+        );
+        best.ok_or_else(|| SolveError::unsolved(DAY, "there is no hike from start to end"))
     }
-    
-    fn a_star(&mut self) {
-        // F-scores: HashMap of nodes to f-scores, default of a large number
-        let mut f_scores = HashMap::<Coord, i32>::new();
-        for row in self.data.iter() {
-            for tile in row.iter() {
-                f_scores.insert(tile.position, LARGE_NUMBER);
+
+    fn search(
+        &self,
+        junction: usize,
+        visited: u64,
+        length: u32,
+        longest_into: &[u32],
+        path: &mut Vec<(usize, usize)>,
+        best: &mut Option<(u32, Vec<(usize, usize)>)>,
+    ) {
+        if junction == self.end {
+            if best.as_ref().is_none_or(|(b, _)| length > *b) {
+                *best = Some((length, path.clone()));
             }
+            return;
         }
-        f_scores.insert(self.start, self.start.manhattan(&self.end));
-
-        // Open set: set of discovered nodes to expand on
-        // Starts with the starting node
-        let mut open_set = CoordHeap::default();
-        open_set.add_coord(self.start, &f_scores);
-
-        // Came from: HashMap of nodes and parent nodes
-        let mut came_from = HashMap::<Coord, Coord>::new();
-
-        // G-scores: cost of cheapest path from start to a given node currently known
-        // Default is a large number
-        let mut g_scores = HashMap::<Coord, i32>::new();
-        for row in self.data.iter() {
-            for tile in row.iter() {
-                g_scores.insert(tile.position, LARGE_NUMBER);
-            }
+
+        // Give up if even reaching every unvisited junction can't beat the best hike
+        let bound: u32 = (0..self.trails.len())
+            .filter(|j| visited & (1 << j) == 0)
+            .map(|j| longest_into[j])
+            .sum();
+        if best.as_ref().is_some_and(|(b, _)| length + bound <= *b) {
+            return;
         }
-        g_scores.insert(self.start, 0);
-
-        while !open_set.is_empty() {
-            // Get the node with the lowest f-score
-            let current = open_set.pop();
-
-            // If we're at the end, mark the path that was used to arrive there
-            if current == self.end {
-                let mut path_node = current;
-                loop {
-                    self.visit(&path_node);
-                    if let Some(parent) = came_from.get(&path_node) {
-                        path_node = *parent;
-                    } else {
-                        return;
-                    }
-                }
-            }
 
-            // Expand on neighbors
-            'traversal: for neighbor in current.neighbors() {
-                // Skip neighbors that are out of bounds
-                if self.out_of_bounds(&neighbor) {
-                    continue 'traversal;
-                }
-                // Skip neighbors that are Forest (obstacles)
-                if self.tile(&neighbor).tile_type == TileType::Forest {
-                    continue 'traversal;
-                }
-                // Skip neighbors that slope against current position
-                if self.tile(&neighbor).tile_type == TileType::SlopeUp && neighbor.i > current.i {
-                    continue 'traversal;
-                }
-                if self.tile(&neighbor).tile_type == TileType::SlopeDown && neighbor.i < current.i {
-                    continue 'traversal;
-                }
-                if self.tile(&neighbor).tile_type == TileType::SlopeLeft && neighbor.j > current.j {
-                    continue 'traversal;
-                }
-                if self.tile(&neighbor).tile_type == TileType::SlopeRight && neighbor.j < current.j {
-                    continue 'traversal;
-                }
-                // Scoring
-                let tentative_g_score = g_scores.get(&current).unwrap() + 1;
-                let neighbor_g_score = *g_scores.get(&neighbor).unwrap();
-                if tentative_g_score < neighbor_g_score {
-                    came_from.insert(neighbor, current);
-                    g_scores.insert(neighbor, tentative_g_score);
-                    let h = neighbor.manhattan(&self.end);
-                    f_scores.insert(neighbor, tentative_g_score + h);
-                    if !open_set.contains(&neighbor) {
-                        open_set.add_coord(neighbor, &f_scores);
-                    }
-                }
+        for (i, trail) in self.trails[junction].iter().enumerate() {
+            if visited & (1 << trail.to) == 0 {
+                path.push((junction, i));
+                self.search(
+                    trail.to,
+                    visited | (1 << trail.to),
+                    length + trail.length(),
+                    longest_into,
+                    path,
+                    best,
+                );
+                path.pop();
             }
         }
     }
+}
 
-    fn score(&self) -> u32 {
-        self.data
-            .iter()
-            .map(|row| {
-                row.iter()
-                    .map(|t| if t.visited { 1 } else { 0 })
-                    .sum::<u32>()
-            })
-            .sum()
+impl fmt::Display for Grid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.data.rows() {
+            let row_str: String = row.iter().map(|t| t.as_char()).collect();
+            writeln!(f, "{}", row_str)?;
+        }
+        Ok(())
     }
 }
 
-#[aocd(2023, 23, "src/day23/test.txt")]
-pub fn solution1() {
-    let input_data = input!();
-    let mut grid = Grid::from_string(input_data);
-    grid.a_star();
-    grid.display();
+pub fn solution1(input: &str) -> Solution {
+    let mut grid = Grid::from_string(input)?;
+    bench::parsed();
+    let steps = grid.hike(true)?;
+    Ok(steps.into())
 }
 
-#[aocd(2023, 23)]
-pub fn solution2() {
-    let input_data = input!();
+pub fn solution2(input: &str) -> Solution {
+    let mut grid = Grid::from_string(input)?;
+    bench::parsed();
+    let steps = grid.hike(false)?;
+    Ok(steps.into())
 }
 
-// #[cfg(tests)]
-// mod tests {
-//     use super::*;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::answer::Answer;
+
+    const EXAMPLE: &str = "\
+#.#####################
+#.......#########...###
+#######.#########.#.###
+###.....#.>.>.###.#.###
+###v#####.#v#.###.#.###
+###.>...#.#.#.....#...#
+###v###.#.#.#########.#
+###...#.#.#.......#...#
+#####.#.#.#######.#.###
+#.....#.#.#.......#...#
+#.#####.#.#.#########v#
+#.#...#...#...###...>.#
+#.#.#v#######v###.###v#
+#...#.>.#...>.>.#.###.#
+#####v#.#.###v#.#.###v#
+#.....#...#...#.#.#...#
+#.#########.###.#.#.###
+#...###...#...#...#.###
+###.###.#.###v#####v###
+#...#...#.#.>.>.#.>.###
+#.###.###.#.###.#.#v###
+#.....###...###...#...#
+#####################.#";
+
+    #[test]
+    fn example_part1() {
+        assert_eq!(solution1(EXAMPLE), Ok(Answer::Int(94)));
+    }
+
+    #[test]
+    fn example_part2() {
+        assert_eq!(solution2(EXAMPLE), Ok(Answer::Int(154)));
+    }
 
-//     #[test]
-//     fn test_slope_up() {
-//         let input_data = String::from(".##");
-//         let mut grid = Grid::from_string("")
-//     }
-// }
\ No newline at end of file
+    #[test]
+    fn malformed_input() {
+        let error = solution1("#.#\n#x#\n#.#").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!((error.line, error.column, error.text.as_str()), (2, 2, "x"));
+        let error = solution2("#.#\n#.#\n###").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!((error.line, error.reason.as_str()), (3, "expected a path"));
+    }
+
+    #[test]
+    fn no_hike() {
+        let no_hike = Err(SolveError::unsolved(
+            DAY,
+            "there is no hike from start to end",
+        ));
+        assert_eq!(solution2("#.#\n###\n#.#"), no_hike);
+        assert_eq!(solution1("#.#\n#^#\n#.#"), no_hike);
+        assert_eq!(solution2("#.#\n#^#\n#.#"), Ok(Answer::Int(2)));
+    }
+
+    #[test]
+    fn junction_graph() {
+        let grid = Grid::from_string(EXAMPLE).unwrap();
+        assert_eq!(grid.junctions().len(), 9);
+
+        // Without slopes every corridor can be walked both ways
+        let graph = grid.compress(false);
+        let trails: usize = graph.trails.iter().map(|t| t.len()).sum();
+        assert_eq!(trails, 24);
+        assert_eq!(graph.trails[graph.start][0].length(), 15);
+
+        // With slopes each corridor between forks is one-way
+        let graph = grid.compress(true);
+        let trails: usize = graph.trails.iter().map(|t| t.len()).sum();
+        assert_eq!(trails, 12);
+    }
+}
@@ -1,53 +1,157 @@
-use aocd::*;
+use crate::answer::{Answer, Solution, SolveError};
+use crate::bench;
+use crate::parse::{self, ParseError};
+use std::fmt;
+use std::ops::{Add, Mul, Sub};
+use std::str::FromStr;
 
-#[derive(Debug, Clone, Copy)]
-struct Vector {
-    x: f64,
-    y: f64,
-    z: f64
-}
-
-impl Vector {
-    fn from_str(s: &str) -> Vector {
-        let vector: Vec<f64> = s
-            .split(", ")
-            .map(|x| {x.trim().parse::<f64>().unwrap()})
-            .collect();
-        Vector {
-            x: vector[0],
-            y: vector[1],
-            z: vector[2]
+const DAY: u8 = 24;
+
+/// Vector of integers, wide enough for the products of cross products of the input.
+/// The input is parsed straight into these, so that part 2 is exact.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExactVector {
+    pub x: i128,
+    pub y: i128,
+    pub z: i128,
+}
+
+impl ExactVector {
+    const ZERO: ExactVector = ExactVector { x: 0, y: 0, z: 0 };
+
+    fn dot(self, other: ExactVector) -> i128 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    fn cross(self, other: ExactVector) -> ExactVector {
+        ExactVector {
+            x: self.y * other.z - self.z * other.y,
+            y: self.z * other.x - self.x * other.z,
+            z: self.x * other.y - self.y * other.x,
+        }
+    }
+
+    /// The vector divided by `d`, if every coordinate is a multiple of it
+    fn exact_div(self, d: i128) -> Option<ExactVector> {
+        if d != 0 && [self.x, self.y, self.z].iter().all(|c| c % d == 0) {
+            Some(ExactVector {
+                x: self.x / d,
+                y: self.y / d,
+                z: self.z / d,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Time `t` at which the vector is `t` times `velocity`, if there is one
+    fn time_along(self, velocity: ExactVector) -> Option<i128> {
+        if velocity == ExactVector::ZERO {
+            return (self == ExactVector::ZERO).then_some(0);
+        }
+        let d = [velocity.x, velocity.y, velocity.z]
+            .into_iter()
+            .find(|c| *c != 0)
+            .unwrap();
+        let n = [
+            (velocity.x, self.x),
+            (velocity.y, self.y),
+            (velocity.z, self.z),
+        ]
+        .into_iter()
+        .find(|(v, _)| *v != 0)
+        .unwrap()
+        .1;
+        let t = n / d;
+        (n % d == 0 && velocity * t == self).then_some(t)
+    }
+}
+
+impl Add for ExactVector {
+    type Output = ExactVector;
+
+    fn add(self, other: ExactVector) -> ExactVector {
+        ExactVector {
+            x: self.x + other.x,
+            y: self.y + other.y,
+            z: self.z + other.z,
+        }
+    }
+}
+
+impl Sub for ExactVector {
+    type Output = ExactVector;
+
+    fn sub(self, other: ExactVector) -> ExactVector {
+        ExactVector {
+            x: self.x - other.x,
+            y: self.y - other.y,
+            z: self.z - other.z,
         }
     }
+}
 
-    fn as_string(&self) -> String {
-        let s = format!("{}, {}, {}", self.x, self.y, self.z);
-        s
+impl Mul<i128> for ExactVector {
+    type Output = ExactVector;
+
+    fn mul(self, t: i128) -> ExactVector {
+        ExactVector {
+            x: self.x * t,
+            y: self.y * t,
+            z: self.z * t,
+        }
+    }
+}
+
+impl fmt::Display for ExactVector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}, {}, {}", self.x, self.y, self.z)
     }
 }
 
 #[derive(Debug, Clone, Copy)]
 struct Hailstone {
-    position: Vector,
-    velocity: Vector,
+    position: ExactVector,
+    velocity: ExactVector,
 }
 
-impl Hailstone {
-    fn from_str(s: &str) -> Hailstone {
-        let mut vectors: Vec<Vector> = s
-            .split('@')
-            .map(|x| Vector::from_str(x))
-            .collect();
-        let velocity = vectors.pop().unwrap();
-        let position = vectors.pop().unwrap();
-        Hailstone {
-            position,
-            velocity
-        }
+/// Parse `x, y, z`, counting columns from the start of `s`
+fn parse_vector(s: &str) -> Result<ExactVector, ParseError> {
+    let coordinates = parse::fields(s, ',')
+        .map(|(column, field)| {
+            let number = field.trim_start();
+            let column = column + field.len() - number.len();
+            parse::number(DAY, column, number.trim_end())
+        })
+        .collect::<Result<Vec<i128>, ParseError>>()?;
+    match coordinates[..] {
+        [x, y, z] => Ok(ExactVector { x, y, z }),
+        _ => Err(ParseError::new(DAY, 1, s, "expected three coordinates")),
+    }
+}
+
+impl FromStr for Hailstone {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (position, velocity) = s.split_once('@').ok_or_else(|| {
+            ParseError::new(
+                DAY,
+                1,
+                s,
+                "expected a position and a velocity separated by '@'",
+            )
+        })?;
+        Ok(Hailstone {
+            position: parse_vector(position)?,
+            velocity: parse_vector(velocity).map_err(|e| e.shifted(position.len() + 1))?,
+        })
     }
+}
 
+impl Hailstone {
     fn a(&self) -> f64 {
-        self.velocity.y / self.velocity.x
+        self.velocity.y as f64 / self.velocity.x as f64
     }
 
     fn b(&self) -> f64 {
@@ -55,21 +159,16 @@ impl Hailstone {
     }
 
     fn c(&self) -> f64 {
-        -1. * self.position.x * self.a() + self.position.y
+        -(self.position.x as f64) * self.a() + self.position.y as f64
     }
 
     fn t_from_x(&self, x: f64) -> f64 {
-        (x - self.position.x) / self.velocity.x
-    }
-
-    fn as_string(&self) -> String {
-        let s = format!("{} @ {}", self.position.as_string().as_str(), self.velocity.as_string().as_str());
-        s
+        (x - self.position.x as f64) / self.velocity.x as f64
     }
 
     fn collision(&self, other: &Hailstone) -> Collision {
-        // println!("Hailstone A: {}", other.as_string());
-        // println!("Hailstone B: {}", self.as_string());
+        // println!("Hailstone A: {}", other);
+        // println!("Hailstone B: {}", self);
         let epsilon = 1.0e-9;
         let denominator = self.a() * other.b() - other.a() * self.b();
         if denominator.abs() < epsilon {
@@ -80,7 +179,7 @@ impl Hailstone {
             let y = (other.a() * self.c() - self.a() * other.c()) / denominator;
             if self.t_from_x(x) < epsilon || other.t_from_x(x) < epsilon {
                 // println!("Have collided in the past at ({}, {})", x, y);
-                Collision::Past(x, y)
+                Collision::Past
             } else {
                 // println!("{} {}", self.t_from_x(x), other.t_from_x(x));
                 // println!("Will collide in the future at ({}, {})", x, y);
@@ -90,41 +189,204 @@ impl Hailstone {
     }
 }
 
+impl fmt::Display for Hailstone {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} @ {}", self.position, self.velocity)
+    }
+}
+
 enum Collision {
     Future(f64, f64),
-    Past(f64, f64),
-    None
+    Past,
+    None,
 }
 
-#[aocd(2023, 24)]
-pub fn solution1() {
-    let input_data = input!();
-    let hailstones: Vec<Hailstone> = input_data
-        .lines()
-        .map(Hailstone::from_str)
-        .collect();
+/// Count the pairs of hailstones whose paths cross inside the test area, in the future
+fn count_collisions(hailstones: &[Hailstone], test_area: (f64, f64)) -> usize {
     let n = hailstones.len();
     let mut collisions = 0;
-    let x_range = (200000000000000.0, 400000000000000.0);
-    let y_range = x_range;
+    let x_range = test_area;
+    let y_range = test_area;
     for i in 0..n {
         let a = &hailstones[i];
-        for j in 0..i {
-            let b = &hailstones[j];
+        for b in hailstones.iter().take(i) {
             match a.collision(b) {
                 Collision::Future(x, y) => {
                     if x >= x_range.0 && x <= x_range.1 && y >= y_range.0 && y <= y_range.1 {
                         collisions += 1;
                     }
                 }
-                Collision::Past(_, _) | Collision::None => {}
+                Collision::Past | Collision::None => {}
             };
         }
     }
-    submit!(1, collisions);
+    collisions
 }
 
-#[aocd(2023, 24)]
-pub fn solution2() {
-    let input_data = input!();
-}
\ No newline at end of file
+/// Where and how fast to throw the rock so that it hits every hailstone
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rock {
+    pub position: ExactVector,
+    pub velocity: ExactVector,
+}
+
+impl Rock {
+    /// Time at which the rock hits the hailstone, if it ever does
+    fn hit_time(&self, hailstone: &Hailstone) -> Option<i128> {
+        let offset = hailstone.position - self.position;
+        let closing = self.velocity - hailstone.velocity;
+        offset.time_along(closing).filter(|t| *t >= 0)
+    }
+
+    pub fn coordinate_sum(&self) -> i128 {
+        self.position.x + self.position.y + self.position.z
+    }
+}
+
+impl fmt::Display for Rock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} @ {}", self.position, self.velocity)
+    }
+}
+
+/// Find the rock throw from three hailstones `a`, `b` and `c`, in exact integer arithmetic.
+/// Seen from hailstone `a`, the rock's path goes through the origin, so it lies in the
+/// plane through the origin and the path of `b`, and in the one through the path of `c`.
+/// Each pair of hailstones gives the linear equation `(p × v) · w = 0` on the rock's
+/// direction `w`, and the rock meets `b` and `c` where their paths cross the other plane.
+fn throw_from(a: &Hailstone, b: &Hailstone, c: &Hailstone) -> Option<Rock> {
+    // Move into the frame of hailstone a
+    let origin = a.position;
+    let drift = a.velocity;
+    let (pb, vb) = (b.position - origin, b.velocity - drift);
+    let (pc, vc) = (c.position - origin, c.velocity - drift);
+
+    // Normals of the planes through the origin and the paths of b and c
+    let nb = pb.cross(vb);
+    let nc = pc.cross(vc);
+    if nb.cross(nc) == ExactVector::ZERO {
+        return None;
+    }
+
+    // Times at which b and c cross the other plane, which is where the rock meets them
+    let (denominator_b, denominator_c) = (vb.dot(nc), vc.dot(nb));
+    if denominator_b == 0 || denominator_c == 0 {
+        return None;
+    }
+    let (tb, tc) = (-pb.dot(nc), -pc.dot(nb));
+    if tb % denominator_b != 0 || tc % denominator_c != 0 {
+        return None;
+    }
+    let (tb, tc) = (tb / denominator_b, tc / denominator_c);
+    if tb == tc {
+        return None;
+    }
+
+    // The rock goes from one meeting point to the other in the time between them
+    let (qb, qc) = (pb + vb * tb, pc + vc * tc);
+    let velocity = (qc - qb).exact_div(tc - tb)?;
+    let position = qb - velocity * tb;
+
+    // Back out of the frame of hailstone a
+    Some(Rock {
+        position: position + origin,
+        velocity: velocity + drift,
+    })
+}
+
+/// Find a rock that hits every hailstone. Any three hailstones in general position
+/// determine it, and every other hailstone is checked exactly against the result.
+fn throw_rock(hailstones: &[Hailstone]) -> Option<Rock> {
+    let n = hailstones.len();
+    for i in 0..n {
+        for j in i + 1..n {
+            for k in j + 1..n {
+                let rock = throw_from(&hailstones[i], &hailstones[j], &hailstones[k]);
+                if let Some(rock) =
+                    rock.filter(|r| hailstones.iter().all(|h| r.hit_time(h).is_some()))
+                {
+                    return Some(rock);
+                }
+            }
+        }
+    }
+    None
+}
+
+pub fn solution1(input: &str) -> Solution {
+    let hailstones: Vec<Hailstone> = parse::lines(input, Hailstone::from_str)?;
+    bench::parsed();
+    Ok(count_collisions(&hailstones, (200000000000000.0, 400000000000000.0)).into())
+}
+
+pub fn solution2(input: &str) -> Solution {
+    let hailstones: Vec<Hailstone> = parse::lines(input, Hailstone::from_str)?;
+    bench::parsed();
+    let rock = throw_rock(&hailstones)
+        .ok_or_else(|| SolveError::unsolved(DAY, "no rock throw hits every hailstone"))?;
+    Ok(Answer::from(rock.coordinate_sum()).explained(format!("the rock is thrown from {rock}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+19, 13, 30 @ -2,  1, -2
+18, 19, 22 @ -1, -1, -2
+20, 25, 34 @ -2, -2, -4
+12, 31, 28 @ -1, -2, -1
+20, 19, 15 @  1, -5, -3";
+
+    #[test]
+    fn example_part1() {
+        let hailstones = parse::lines(EXAMPLE, Hailstone::from_str).unwrap();
+        assert_eq!(count_collisions(&hailstones, (7.0, 27.0)), 2);
+    }
+
+    #[test]
+    fn example_part2() {
+        let hailstones = parse::lines(EXAMPLE, Hailstone::from_str).unwrap();
+        let rock = throw_rock(&hailstones).unwrap();
+        assert_eq!(rock.to_string(), "24, 13, 10 @ -3, 1, 2");
+        let answer = solution2(EXAMPLE).unwrap();
+        assert_eq!(answer.to_string(), "47");
+        assert_eq!(
+            answer.explanation(),
+            Some("the rock is thrown from 24, 13, 10 @ -3, 1, 2")
+        );
+
+        // Each hailstone is hit at the time given in the puzzle
+        let times: Vec<Option<i128>> = hailstones.iter().map(|h| rock.hit_time(h)).collect();
+        assert_eq!(times, vec![Some(5), Some(3), Some(4), Some(6), Some(1)]);
+
+        // Hailstones on parallel paths never pin down a throw
+        let parallel = "0, 0, 0 @ 1, 0, 0\n0, 1, 0 @ 1, 0, 0\n0, 2, 0 @ 1, 0, 0";
+        assert_eq!(
+            solution2(parallel),
+            Err(SolveError::unsolved(
+                DAY,
+                "no rock throw hits every hailstone"
+            ))
+        );
+    }
+
+    #[test]
+    fn malformed_input() {
+        let error = solution1("19, 13, 30 @ -2,  1, -2\n18, x, 22 @ -1, -1, -2")
+            .unwrap_err()
+            .into_parse_error()
+            .unwrap();
+        assert_eq!((error.line, error.column, error.text.as_str()), (2, 5, "x"));
+        let error = solution2("19, 13, 30 @ -2,  1")
+            .unwrap_err()
+            .into_parse_error()
+            .unwrap();
+        assert_eq!((error.line, error.column), (1, 13));
+        let error = solution2("19, 13, 30")
+            .unwrap_err()
+            .into_parse_error()
+            .unwrap();
+        assert_eq!((error.line, error.column), (1, 1));
+    }
+}
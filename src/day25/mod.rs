@@ -1,11 +1,300 @@
-use aocd::*;
+use crate::answer::{Answer, Solution, SolveError};
+use crate::bench;
+use crate::parse::{self, ParseError};
+use std::collections::{BinaryHeap, HashMap};
 
-#[aocd(2023, 25)]
-pub fn solution1() {
-    let input_data = input!();
+const DAY: u8 = 25;
+
+/// The wiring diagram as an undirected graph, with the components interned as indices
+#[derive(Debug, Default)]
+pub struct Wiring {
+    names: Vec<String>,
+    indices: HashMap<String, usize>,
+    wires: Vec<(usize, usize)>,
+}
+
+/// The smallest set of wires whose removal splits the components into two groups
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cut {
+    /// Components on one side of the cut; the rest are on the other
+    pub side: Vec<usize>,
+    /// The wires to cut, as pairs of component indices
+    pub wires: Vec<(usize, usize)>,
+}
+
+impl Wiring {
+    pub fn from_string(s: &str) -> Result<Wiring, ParseError> {
+        let mut wiring = Wiring::default();
+        for (i, line) in s.lines().enumerate() {
+            let (component, connected) = parse_line(line).map_err(|e| e.on_line(i + 1))?;
+            let component = wiring.intern(component);
+            for other in connected {
+                let other = wiring.intern(other);
+                wiring.wires.push((component, other));
+            }
+        }
+        Ok(wiring)
+    }
+
+    fn intern(&mut self, name: &str) -> usize {
+        if let Some(&index) = self.indices.get(name) {
+            return index;
+        }
+        let index = self.names.len();
+        self.names.push(name.to_string());
+        self.indices.insert(name.to_string(), index);
+        index
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn name(&self, component: usize) -> &str {
+        &self.names[component]
+    }
+
+    /// Global minimum cut by the Stoer-Wagner algorithm, or `None` with fewer than two
+    /// components. Each phase adds the components in order of how strongly they are wired
+    /// to those already added. The last one added is cut off from the rest by exactly the
+    /// wires that connect it, and is then merged into the one added before it.
+    pub fn min_cut(&self) -> Option<Cut> {
+        let n = self.len();
+        if n < 2 {
+            return None;
+        }
+        let mut weights: Vec<HashMap<usize, usize>> = vec![HashMap::new(); n];
+        for &(a, b) in self.wires.iter().filter(|(a, b)| a != b) {
+            *weights[a].entry(b).or_default() += 1;
+            *weights[b].entry(a).or_default() += 1;
+        }
+        let mut members: Vec<Vec<usize>> = (0..n).map(|c| vec![c]).collect();
+        let mut merged = vec![false; n];
+        let mut best: Option<(usize, Vec<usize>)> = None;
+
+        for _ in 1..n {
+            // Every component starts out unconnected to the added ones, which also
+            // covers diagrams in several pieces. Stale heap entries are skipped.
+            let mut connection = vec![0; n];
+            let mut added = merged.clone();
+            let mut heap: BinaryHeap<(usize, usize)> =
+                (0..n).filter(|c| !merged[*c]).map(|c| (0, c)).collect();
+            let (mut previous, mut last, mut cut_weight) = (n, n, 0);
+            while let Some((weight, component)) = heap.pop() {
+                if added[component] || weight != connection[component] {
+                    continue;
+                }
+                added[component] = true;
+                (previous, last, cut_weight) = (last, component, weight);
+                for (&other, &w) in &weights[component] {
+                    if !added[other] {
+                        connection[other] += w;
+                        heap.push((connection[other], other));
+                    }
+                }
+            }
+
+            if best.as_ref().is_none_or(|(weight, _)| cut_weight < *weight) {
+                best = Some((cut_weight, members[last].clone()));
+            }
+
+            // Merge the last component into the one before it
+            let moved = std::mem::take(&mut members[last]);
+            members[previous].extend(moved);
+            merged[last] = true;
+            for (other, w) in std::mem::take(&mut weights[last]) {
+                weights[other].remove(&last);
+                if other != previous {
+                    *weights[previous].entry(other).or_default() += w;
+                    *weights[other].entry(previous).or_default() += w;
+                }
+            }
+        }
+
+        let (_, mut side) = best?;
+        side.sort_unstable();
+        let mut on_side = vec![false; n];
+        for &c in &side {
+            on_side[c] = true;
+        }
+        let wires = self
+            .wires
+            .iter()
+            .filter(|(a, b)| on_side[*a] != on_side[*b])
+            .copied()
+            .collect();
+        Some(Cut { side, wires })
+    }
+
+    /// Graphviz rendering of the diagram, with the given wires drawn dashed and in red
+    pub fn to_dot(&self, cut: &[(usize, usize)]) -> String {
+        let mut dot = String::from("graph wiring {\n");
+        for wire in &self.wires {
+            let (a, b) = (self.name(wire.0), self.name(wire.1));
+            if cut.contains(wire) {
+                dot += &format!("    \"{a}\" -- \"{b}\" [color=red, style=dashed];\n");
+            } else {
+                dot += &format!("    \"{a}\" -- \"{b}\";\n");
+            }
+        }
+        dot + "}\n"
+    }
 }
 
-#[aocd(2023, 25)]
-pub fn solution2() {
-    let input_data = input!();
-}
\ No newline at end of file
+impl Cut {
+    /// Number of components on each side of the cut
+    pub fn sizes(&self, wiring: &Wiring) -> (usize, usize) {
+        (self.side.len(), wiring.len() - self.side.len())
+    }
+}
+
+/// Parse `name: other other ...` into the component and those it is wired to
+fn parse_line(line: &str) -> Result<(&str, Vec<&str>), ParseError> {
+    let (component, connected) = line.split_once(':').ok_or_else(|| {
+        ParseError::new(
+            DAY,
+            1,
+            line,
+            "expected a component, ':' and what it is wired to",
+        )
+    })?;
+    if component.is_empty() || !component.chars().all(char::is_alphanumeric) {
+        return Err(ParseError::new(
+            DAY,
+            1,
+            component,
+            "expected a component name",
+        ));
+    }
+    let offset = component.len() + 1;
+    let connected: Vec<&str> = parse::fields(connected, ' ')
+        .filter(|(_, name)| !name.is_empty())
+        .map(|(column, name)| {
+            if name.chars().all(char::is_alphanumeric) {
+                Ok(name)
+            } else {
+                Err(ParseError::new(
+                    DAY,
+                    column + offset,
+                    name,
+                    "expected a component name",
+                ))
+            }
+        })
+        .collect::<Result<_, _>>()?;
+    if connected.is_empty() {
+        return Err(ParseError::new(
+            DAY,
+            offset + 1,
+            "",
+            "expected the components it is wired to",
+        ));
+    }
+    Ok((component, connected))
+}
+
+pub fn solution1(input: &str) -> Solution {
+    let wiring = Wiring::from_string(input)?;
+    bench::parsed();
+
+    let cut = wiring
+        .min_cut()
+        .ok_or_else(|| SolveError::unsolved(DAY, "there are fewer than two components"))?;
+    if cut.wires.len() != 3 {
+        return Err(SolveError::unsolved(
+            DAY,
+            format!("the smallest cut has {} wires, not 3", cut.wires.len()),
+        ));
+    }
+    let (a, b) = cut.sizes(&wiring);
+    let wires: Vec<String> = cut
+        .wires
+        .iter()
+        .map(|(x, y)| format!("{}/{}", wiring.name(*x), wiring.name(*y)))
+        .collect();
+    Ok(Answer::from(a * b).explained(format!(
+        "cutting {} leaves groups of {a} and {b}",
+        wires.join(", ")
+    )))
+}
+
+pub fn solution2(_input: &str) -> Solution {
+    Err(SolveError::unsolved(
+        DAY,
+        "there is no part 2 puzzle on day 25",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+jqt: rhn xhk nvd
+rsh: frs pzl lsr
+xhk: hfx
+cmg: qnr nvd lhk bvb
+rhn: xhk bvb hfx
+bvb: xhk hfx
+pzl: lsr hfx nvd
+qnr: nvd
+ntq: jqt hfx bvb xhk
+nvd: lhk
+lsr: lhk
+rzs: qnr cmg lsr rsh
+frs: qnr lhk lsr";
+
+    #[test]
+    fn example_part1() {
+        let answer = solution1(EXAMPLE).unwrap();
+        assert_eq!(answer.to_string(), "54");
+        assert_eq!(
+            answer.explanation(),
+            Some("cutting jqt/nvd, cmg/bvb, pzl/hfx leaves groups of 6 and 9")
+        );
+    }
+
+    #[test]
+    fn cut_wires() {
+        let wiring = Wiring::from_string(EXAMPLE).unwrap();
+        let cut = wiring.min_cut().unwrap();
+        let mut wires: Vec<[&str; 2]> = cut
+            .wires
+            .iter()
+            .map(|(a, b)| {
+                let mut wire = [wiring.name(*a), wiring.name(*b)];
+                wire.sort_unstable();
+                wire
+            })
+            .collect();
+        wires.sort_unstable();
+        assert_eq!(wires, vec![["bvb", "cmg"], ["hfx", "pzl"], ["jqt", "nvd"]]);
+        let (a, b) = cut.sizes(&wiring);
+        assert_eq!((a.min(b), a.max(b)), (6, 9));
+
+        let dot = wiring.to_dot(&cut.wires);
+        assert_eq!(dot.matches("color=red").count(), 3);
+        assert!(dot.contains("\"jqt\" -- \"nvd\" [color=red, style=dashed];"));
+    }
+
+    #[test]
+    fn malformed_input() {
+        let error = solution1("jqt: rhn\nrsh frs").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!((error.line, error.column), (2, 1));
+        let error = solution1("jqt: rhn x-y").unwrap_err();
+        let error = error.into_parse_error().unwrap();
+        assert_eq!((error.column, error.text.as_str()), (10, "x-y"));
+        assert_eq!(
+            solution1("a: b"),
+            Err(SolveError::unsolved(
+                DAY,
+                "the smallest cut has 1 wires, not 3"
+            ))
+        );
+    }
+}
@@ -0,0 +1,288 @@
+use std::fmt;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// Point on a 2D grid, as a `(row, column)` pair that may lie off the grid.
+/// Rows grow downwards, so `Direction::Up` decreases `i`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Coord {
+    pub i: i64,
+    pub j: i64,
+}
+
+impl Coord {
+    pub const ORIGIN: Coord = Coord { i: 0, j: 0 };
+
+    pub const fn new(i: i64, j: i64) -> Coord {
+        Coord { i, j }
+    }
+
+    /// Coordinate of a grid index
+    pub fn from_index(index: (usize, usize)) -> Coord {
+        Coord::new(index.0 as i64, index.1 as i64)
+    }
+
+    /// Grid index of the coordinate, if neither component is negative.
+    /// Use `Grid::position_of` to check the upper bounds as well.
+    pub fn to_index(self) -> Option<(usize, usize)> {
+        if self.i >= 0 && self.j >= 0 {
+            Some((self.i as usize, self.j as usize))
+        } else {
+            None
+        }
+    }
+
+    pub fn manhattan(self, other: Coord) -> i64 {
+        (self.i - other.i).abs() + (self.j - other.j).abs()
+    }
+
+    /// Neighboring coordinate in the given direction
+    pub fn step(self, direction: Direction) -> Coord {
+        self + direction.offset()
+    }
+
+    /// The four orthogonal neighbors, in the order of `Direction::ALL`
+    pub fn neighbors(self) -> [Coord; 4] {
+        Direction::ALL.map(|d| self.step(d))
+    }
+}
+
+impl Add for Coord {
+    type Output = Coord;
+
+    fn add(self, other: Coord) -> Coord {
+        Coord::new(self.i + other.i, self.j + other.j)
+    }
+}
+
+impl Sub for Coord {
+    type Output = Coord;
+
+    fn sub(self, other: Coord) -> Coord {
+        Coord::new(self.i - other.i, self.j - other.j)
+    }
+}
+
+impl AddAssign for Coord {
+    fn add_assign(&mut self, other: Coord) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Coord {
+    fn sub_assign(&mut self, other: Coord) {
+        *self = *self - other;
+    }
+}
+
+impl Neg for Coord {
+    type Output = Coord;
+
+    fn neg(self) -> Coord {
+        Coord::new(-self.i, -self.j)
+    }
+}
+
+impl Mul<i64> for Coord {
+    type Output = Coord;
+
+    fn mul(self, n: i64) -> Coord {
+        Coord::new(self.i * n, self.j * n)
+    }
+}
+
+impl fmt::Display for Coord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}, {})", self.i, self.j)
+    }
+}
+
+/// Point in 3D space
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Coord3 {
+    pub x: i64,
+    pub y: i64,
+    pub z: i64,
+}
+
+impl Coord3 {
+    pub const ORIGIN: Coord3 = Coord3 { x: 0, y: 0, z: 0 };
+
+    pub const fn new(x: i64, y: i64, z: i64) -> Coord3 {
+        Coord3 { x, y, z }
+    }
+
+    pub fn manhattan(self, other: Coord3) -> i64 {
+        (self.x - other.x).abs() + (self.y - other.y).abs() + (self.z - other.z).abs()
+    }
+}
+
+impl Add for Coord3 {
+    type Output = Coord3;
+
+    fn add(self, other: Coord3) -> Coord3 {
+        Coord3::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl Sub for Coord3 {
+    type Output = Coord3;
+
+    fn sub(self, other: Coord3) -> Coord3 {
+        Coord3::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl AddAssign for Coord3 {
+    fn add_assign(&mut self, other: Coord3) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Coord3 {
+    fn sub_assign(&mut self, other: Coord3) {
+        *self = *self - other;
+    }
+}
+
+impl Neg for Coord3 {
+    type Output = Coord3;
+
+    fn neg(self) -> Coord3 {
+        Coord3::new(-self.x, -self.y, -self.z)
+    }
+}
+
+impl Mul<i64> for Coord3 {
+    type Output = Coord3;
+
+    fn mul(self, n: i64) -> Coord3 {
+        Coord3::new(self.x * n, self.y * n, self.z * n)
+    }
+}
+
+impl fmt::Display for Coord3 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}, {}, {})", self.x, self.y, self.z)
+    }
+}
+
+/// One of the four directions on a grid, as seen from above
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ];
+
+    /// Change in coordinate of a single step
+    pub fn offset(self) -> Coord {
+        match self {
+            Direction::Up => Coord::new(-1, 0),
+            Direction::Down => Coord::new(1, 0),
+            Direction::Left => Coord::new(0, -1),
+            Direction::Right => Coord::new(0, 1),
+        }
+    }
+
+    /// Direction after a quarter turn counterclockwise
+    pub fn turn_left(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Left,
+            Direction::Left => Direction::Down,
+            Direction::Down => Direction::Right,
+            Direction::Right => Direction::Up,
+        }
+    }
+
+    /// Direction after a quarter turn clockwise
+    pub fn turn_right(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Right,
+            Direction::Right => Direction::Down,
+            Direction::Down => Direction::Left,
+            Direction::Left => Direction::Up,
+        }
+    }
+
+    pub fn reverse(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        }
+    }
+
+    /// Arrow pointing in the direction, for drawing paths
+    pub fn arrow(self) -> char {
+        match self {
+            Direction::Up => '^',
+            Direction::Down => 'v',
+            Direction::Left => '<',
+            Direction::Right => '>',
+        }
+    }
+
+    /// Direction of an `U`, `D`, `L` or `R` instruction
+    pub fn from_char(c: char) -> Option<Direction> {
+        match c {
+            'U' => Some(Direction::Up),
+            'D' => Some(Direction::Down),
+            'L' => Some(Direction::Left),
+            'R' => Some(Direction::Right),
+            _ => None,
+        }
+    }
+
+    /// Direction of a single step between two neighboring coordinates
+    pub fn between(from: Coord, to: Coord) -> Option<Direction> {
+        Direction::ALL.into_iter().find(|d| from.step(*d) == to)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coord_arithmetic() {
+        let a = Coord::new(1, 2);
+        let b = Coord::new(-3, 5);
+        assert_eq!(a + b, Coord::new(-2, 7));
+        assert_eq!(a - b, Coord::new(4, -3));
+        assert_eq!(-a * 3, Coord::new(-3, -6));
+        assert_eq!(a.manhattan(b), 7);
+        assert_eq!(Coord3::new(1, 2, 3).manhattan(Coord3::ORIGIN), 6);
+    }
+
+    #[test]
+    fn turns() {
+        for d in Direction::ALL {
+            assert_eq!(d.turn_left().turn_right(), d);
+            assert_eq!(d.turn_right().turn_right(), d.reverse());
+            assert_eq!(d.offset() + d.reverse().offset(), Coord::ORIGIN);
+        }
+        assert_eq!(Direction::Up.turn_right(), Direction::Right);
+    }
+
+    #[test]
+    fn steps() {
+        let a = Coord::new(0, 0);
+        assert_eq!(a.step(Direction::Up), Coord::new(-1, 0));
+        assert_eq!(a.step(Direction::Up).to_index(), None);
+        assert_eq!(
+            Direction::between(a, Coord::new(0, 1)),
+            Some(Direction::Right)
+        );
+        assert_eq!(Coord::from_index((2, 3)).to_index(), Some((2, 3)));
+    }
+}
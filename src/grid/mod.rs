@@ -0,0 +1,363 @@
+use crate::geometry::Coord;
+use crate::parse::ParseError;
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+/// Offsets of the four orthogonal neighbors: up, down, left, right
+const OFFSETS_4: [(i64, i64); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+
+/// Offsets of all eight neighbors, row by row
+const OFFSETS_8: [(i64, i64); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// Rectangular grid of tiles, stored row by row in a single vector.
+/// Positions are `(row, column)` pairs, with `(0, 0)` at the top left.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    data: Vec<T>,
+    nrows: usize,
+    ncols: usize,
+}
+
+impl<T> Grid<T> {
+    /// Grid of the given size with every tile set to `value`
+    pub fn new(nrows: usize, ncols: usize, value: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid {
+            data: vec![value; nrows * ncols],
+            nrows,
+            ncols,
+        }
+    }
+
+    /// Build a grid from its rows, which must all have the same length
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Grid<T> {
+        let nrows = rows.len();
+        let ncols = rows.first().map_or(0, |row| row.len());
+        assert!(
+            rows.iter().all(|row| row.len() == ncols),
+            "Grid rows must all have the same length."
+        );
+        Grid {
+            data: rows.into_iter().flatten().collect(),
+            nrows,
+            ncols,
+        }
+    }
+
+    /// Parse a grid with one line of text per row, converting each character into a tile
+    pub fn parse(s: &str, mut f: impl FnMut(char) -> T) -> Grid<T> {
+        let rows: Vec<Vec<T>> = s
+            .lines()
+            .map(|line| line.chars().map(&mut f).collect())
+            .collect();
+        Grid::from_rows(rows)
+    }
+
+    /// Parse a grid like `parse`, reporting characters that `f` rejects, rows of another
+    /// length than the first, and empty input as errors. `expected` describes the tiles
+    /// `f` accepts.
+    pub fn try_parse(
+        s: &str,
+        day: u8,
+        expected: &str,
+        mut f: impl FnMut(char) -> Option<T>,
+    ) -> Result<Grid<T>, ParseError> {
+        let mut rows: Vec<Vec<T>> = Vec::new();
+        for (i, line) in s.lines().enumerate() {
+            let row = line
+                .chars()
+                .enumerate()
+                .map(|(j, c)| {
+                    f(c).ok_or_else(|| {
+                        ParseError::new(day, j + 1, &c.to_string(), expected).on_line(i + 1)
+                    })
+                })
+                .collect::<Result<Vec<T>, ParseError>>()?;
+            let ncols = rows.first().map_or(row.len(), |first| first.len());
+            if row.is_empty() || row.len() != ncols {
+                let reason = match ncols {
+                    0 => "expected a row of tiles".to_string(),
+                    _ => format!("expected a row of {ncols} tiles"),
+                };
+                return Err(ParseError::new(day, 1, line, reason).on_line(i + 1));
+            }
+            rows.push(row);
+        }
+        if rows.is_empty() {
+            return Err(ParseError::new(day, 1, "", "expected a grid").on_line(1));
+        }
+        Ok(Grid::from_rows(rows))
+    }
+
+    pub fn nrows(&self) -> usize {
+        self.nrows
+    }
+
+    pub fn ncols(&self) -> usize {
+        self.ncols
+    }
+
+    /// Checked conversion of a signed position into a position on the grid
+    pub fn position(&self, i: i64, j: i64) -> Option<(usize, usize)> {
+        if i >= 0 && i < self.nrows as i64 && j >= 0 && j < self.ncols as i64 {
+            Some((i as usize, j as usize))
+        } else {
+            None
+        }
+    }
+
+    /// Position of a coordinate, if it is on the grid
+    pub fn position_of(&self, coord: Coord) -> Option<(usize, usize)> {
+        self.position(coord.i, coord.j)
+    }
+
+    /// Tile at a coordinate, if it is on the grid
+    pub fn at(&self, coord: Coord) -> Option<&T> {
+        self.position_of(coord).map(|position| &self[position])
+    }
+
+    /// Position reached by moving from `position` by the given offset, if it is on the grid
+    pub fn offset(&self, position: (usize, usize), di: i64, dj: i64) -> Option<(usize, usize)> {
+        self.position(position.0 as i64 + di, position.1 as i64 + dj)
+    }
+
+    pub fn get(&self, position: (usize, usize)) -> Option<&T> {
+        let (i, j) = position;
+        if i < self.nrows && j < self.ncols {
+            Some(&self.data[i * self.ncols + j])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, position: (usize, usize)) -> Option<&mut T> {
+        let (i, j) = position;
+        if i < self.nrows && j < self.ncols {
+            Some(&mut self.data[i * self.ncols + j])
+        } else {
+            None
+        }
+    }
+
+    /// The up to four orthogonal neighbors of a position that lie on the grid
+    pub fn neighbors4(
+        &self,
+        position: (usize, usize),
+    ) -> impl Iterator<Item = (usize, usize)> + '_ {
+        OFFSETS_4
+            .iter()
+            .filter_map(move |&(di, dj)| self.offset(position, di, dj))
+    }
+
+    /// The up to eight orthogonal and diagonal neighbors of a position that lie on the grid
+    pub fn neighbors8(
+        &self,
+        position: (usize, usize),
+    ) -> impl Iterator<Item = (usize, usize)> + '_ {
+        OFFSETS_8
+            .iter()
+            .filter_map(move |&(di, dj)| self.offset(position, di, dj))
+    }
+
+    /// Every position on the grid, row by row
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let ncols = self.ncols;
+        (0..self.nrows).flat_map(move |i| (0..ncols).map(move |j| (i, j)))
+    }
+
+    /// Every tile with its position, row by row
+    pub fn enumerate(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.positions().zip(self.data.iter())
+    }
+
+    /// Position of the first tile, row by row, that matches the predicate
+    pub fn find(&self, mut predicate: impl FnMut(&T) -> bool) -> Option<(usize, usize)> {
+        self.enumerate()
+            .find(|(_, t)| predicate(t))
+            .map(|(position, _)| position)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.data.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.data.iter_mut()
+    }
+
+    pub fn row(&self, i: usize) -> &[T] {
+        &self.data[i * self.ncols..(i + 1) * self.ncols]
+    }
+
+    pub fn row_mut(&mut self, i: usize) -> &mut [T] {
+        &mut self.data[i * self.ncols..(i + 1) * self.ncols]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // chunks panics on a chunk size of 0, which an empty grid would otherwise give
+        self.data.chunks(self.ncols.max(1))
+    }
+
+    pub fn column(&self, j: usize) -> impl Iterator<Item = &T> {
+        self.data.iter().skip(j).step_by(self.ncols.max(1))
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.ncols).map(|j| self.column(j))
+    }
+
+    /// Convert every tile, keeping the shape of the grid
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            data: self.data.iter().map(f).collect(),
+            nrows: self.nrows,
+            ncols: self.ncols,
+        }
+    }
+
+    /// Grid whose rows are the columns of this one
+    pub fn transpose(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let rows = self.columns().map(|col| col.cloned().collect()).collect();
+        Grid::from_rows(rows)
+    }
+
+    /// Grid turned a quarter clockwise, so the first column becomes the first row, reversed
+    pub fn rotate_clockwise(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let rows = self
+            .columns()
+            .map(|col| {
+                let mut row: Vec<T> = col.cloned().collect();
+                row.reverse();
+                row
+            })
+            .collect();
+        Grid::from_rows(rows)
+    }
+
+    /// Grid turned a quarter counterclockwise, so the last column becomes the first row
+    pub fn rotate_counterclockwise(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let rows = (0..self.ncols)
+            .rev()
+            .map(|j| self.column(j).cloned().collect())
+            .collect();
+        Grid::from_rows(rows)
+    }
+
+    /// Grid surrounded by a border of `value` tiles that is `width` tiles wide
+    pub fn padded(&self, width: usize, value: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let mut grid = Grid::new(self.nrows + 2 * width, self.ncols + 2 * width, value);
+        for ((i, j), t) in self.enumerate() {
+            grid[(i + width, j + width)] = t.clone();
+        }
+        grid
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, position: (usize, usize)) -> &T {
+        let (i, j) = position;
+        assert!(i < self.nrows && j < self.ncols, "Position out of bounds.");
+        &self.data[i * self.ncols + j]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, position: (usize, usize)) -> &mut T {
+        let (i, j) = position;
+        assert!(i < self.nrows && j < self.ncols, "Position out of bounds.");
+        &mut self.data[i * self.ncols + j]
+    }
+}
+
+/// One line per row, with each tile written out next to the previous one
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.rows() {
+            for t in row {
+                write!(f, "{t}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> Grid<char> {
+        Grid::parse("abc\ndef", |c| c)
+    }
+
+    #[test]
+    fn parse_and_index() {
+        let grid = example();
+        assert_eq!((grid.nrows(), grid.ncols()), (2, 3));
+        assert_eq!(grid[(1, 2)], 'f');
+        assert_eq!(grid.get((2, 0)), None);
+        assert_eq!(grid.position(-1, 0), None);
+        assert_eq!(grid.find(|c| *c == 'e'), Some((1, 1)));
+    }
+
+    #[test]
+    fn parse_errors() {
+        let digit = |c: char| c.to_digit(10);
+        let grid = Grid::try_parse("12\n34", 0, "expected a digit", digit).unwrap();
+        assert_eq!(grid[(1, 0)], 3);
+        let error = Grid::try_parse("12\n3x", 0, "expected a digit", digit).unwrap_err();
+        assert_eq!((error.line, error.column, error.text.as_str()), (2, 2, "x"));
+        let error = Grid::try_parse("12\n345", 0, "expected a digit", digit).unwrap_err();
+        assert_eq!(
+            (error.line, error.reason.as_str()),
+            (2, "expected a row of 2 tiles")
+        );
+        let error = Grid::try_parse("", 0, "expected a digit", digit).unwrap_err();
+        assert_eq!((error.line, error.reason.as_str()), (1, "expected a grid"));
+    }
+
+    #[test]
+    fn neighbors() {
+        let grid = example();
+        let corner: Vec<_> = grid.neighbors4((0, 0)).collect();
+        assert_eq!(corner, vec![(1, 0), (0, 1)]);
+        assert_eq!(grid.neighbors8((0, 1)).count(), 5);
+    }
+
+    #[test]
+    fn transformations() {
+        let grid = example();
+        assert_eq!(grid.transpose().to_string(), "ad\nbe\ncf\n");
+        assert_eq!(grid.rotate_clockwise().to_string(), "da\neb\nfc\n");
+        assert_eq!(grid.rotate_counterclockwise().to_string(), "cf\nbe\nad\n");
+        assert_eq!(
+            grid.padded(1, '.').to_string(),
+            ".....\n.abc.\n.def.\n.....\n"
+        );
+    }
+}
@@ -0,0 +1,110 @@
+use std::env;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+/// Environment variable that points at the input cache directory
+pub const INPUT_DIR_VAR: &str = "AOC_INPUT_DIR";
+
+/// Cache directory used when neither the command line nor the environment names one
+pub const DEFAULT_INPUT_DIR: &str = "input";
+
+/// Where to read a day's puzzle input from. Nothing here touches the network.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// A single input file
+    File(PathBuf),
+    /// Whatever is piped into standard input
+    Stdin,
+    /// A directory holding one `dayNN.txt` file per day
+    Cache(PathBuf),
+}
+
+impl Source {
+    /// Read from the cache directory, see [`input_dir`]
+    pub fn cache(dir: Option<PathBuf>) -> Source {
+        Source::Cache(input_dir(dir))
+    }
+
+    /// Read the input for the given day
+    pub fn load(&self, day: u8) -> Result<String, String> {
+        let text = match self {
+            Source::File(path) => read_file(path)?,
+            Source::Stdin => {
+                let mut text = String::new();
+                io::stdin()
+                    .read_to_string(&mut text)
+                    .map_err(|e| format!("Unable to read input from stdin: {e}"))?;
+                text
+            }
+            Source::Cache(dir) => {
+                let path = cache_path(dir, day);
+                if !path.exists() {
+                    return Err(format!(
+                        "No input for day {day:02}: {} does not exist.\n\
+                         Save the puzzle input there, choose another directory with \
+                         --input-dir or {INPUT_DIR_VAR}, or pass --input <path> or --stdin.",
+                        path.display()
+                    ));
+                }
+                read_file(&path)?
+            }
+        };
+
+        let text = normalize(&text);
+        if text.is_empty() {
+            return Err(format!("The input for day {day:02} is empty"));
+        }
+        Ok(text)
+    }
+}
+
+/// The cache directory given on the command line, else the one in `AOC_INPUT_DIR`,
+/// else `input/` in the working directory
+pub fn input_dir(dir: Option<PathBuf>) -> PathBuf {
+    dir.or_else(|| env::var_os(INPUT_DIR_VAR).map(PathBuf::from))
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_INPUT_DIR))
+}
+
+/// Path of a day's input inside a cache directory, e.g. `input/day07.txt`
+pub fn cache_path(dir: &Path, day: u8) -> PathBuf {
+    dir.join(format!("day{day:02}.txt"))
+}
+
+fn read_file(path: &Path) -> Result<String, String> {
+    fs::read_to_string(path)
+        .map_err(|e| format!("Unable to read input file '{}': {e}", path.display()))
+}
+
+/// Use Unix line endings and drop the trailing newline, which the solutions do not expect
+fn normalize(text: &str) -> String {
+    text.replace("\r\n", "\n")
+        .trim_end_matches('\n')
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("a\r\nb\r\n\r\n"), "a\nb");
+        assert_eq!(normalize("a\nb"), "a\nb");
+    }
+
+    #[test]
+    fn test_cache_path() {
+        assert_eq!(
+            cache_path(Path::new("input"), 7),
+            PathBuf::from("input/day07.txt")
+        );
+    }
+
+    #[test]
+    fn test_missing_input() {
+        let source = Source::Cache(PathBuf::from("does/not/exist"));
+        let error = source.load(3).unwrap_err();
+        assert!(error.contains("does/not/exist/day03.txt"));
+    }
+}
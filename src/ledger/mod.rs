@@ -0,0 +1,239 @@
+use crate::answer::Answer;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Environment variable that points at the ledger file
+pub const LEDGER_VAR: &str = "AOC_LEDGER";
+
+/// Ledger file used when neither the command line nor the environment names one
+pub const DEFAULT_LEDGER: &str = "answers.json";
+
+/// What is known about an answer that was given for a puzzle part
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Verdict {
+    Correct,
+    Wrong,
+    /// Sent to the website, but the response has not been recorded
+    Submitted,
+}
+
+impl Verdict {
+    pub fn parse(s: &str) -> Option<Verdict> {
+        match s {
+            "correct" => Some(Verdict::Correct),
+            "wrong" => Some(Verdict::Wrong),
+            "submitted" => Some(Verdict::Submitted),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Verdict::Correct => write!(f, "correct"),
+            Verdict::Wrong => write!(f, "wrong"),
+            Verdict::Submitted => write!(f, "submitted"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    pub day: u8,
+    pub part: u8,
+    pub answer: String,
+    pub verdict: Verdict,
+}
+
+/// Result of checking an answer against the ledger
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Check {
+    Correct,
+    /// Either recorded as wrong, or different from the recorded correct answer
+    Wrong {
+        expected: Option<String>,
+    },
+    /// Submitted, but the response has not been recorded yet
+    Pending,
+    Unknown,
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Check::Correct => write!(f, "correct"),
+            Check::Wrong {
+                expected: Some(expected),
+            } => write!(f, "wrong, expected {expected}"),
+            Check::Wrong { expected: None } => write!(f, "known wrong"),
+            Check::Pending => write!(f, "submitted, verdict not recorded"),
+            Check::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// Local record of every answer given for each day and part, stored as JSON
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ledger {
+    entries: Vec<Entry>,
+}
+
+/// The ledger path given on the command line, else the one in `AOC_LEDGER`,
+/// else `answers.json` in the working directory
+pub fn ledger_path(path: Option<PathBuf>) -> PathBuf {
+    path.or_else(|| env::var_os(LEDGER_VAR).map(PathBuf::from))
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_LEDGER))
+}
+
+impl Ledger {
+    /// Read the ledger from disk. A file that does not exist yet is an empty ledger.
+    pub fn load(path: &Path) -> Result<Ledger, String> {
+        if !path.exists() {
+            return Ok(Ledger::default());
+        }
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Unable to read ledger '{}': {e}", path.display()))?;
+        serde_json::from_str(&text).map_err(|e| format!("Invalid ledger '{}': {e}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let text = serde_json::to_string_pretty(self).unwrap();
+        fs::write(path, text + "\n")
+            .map_err(|e| format!("Unable to write ledger '{}': {e}", path.display()))
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// Record the verdict for an answer, replacing whatever was known about it before.
+    /// There is only one correct answer, so recording one marks the others as wrong.
+    pub fn record(&mut self, day: u8, part: u8, answer: &Answer, verdict: Verdict) {
+        let answer = answer.to_string();
+        if verdict == Verdict::Correct {
+            for entry in self
+                .entries
+                .iter_mut()
+                .filter(|e| e.day == day && e.part == part && e.answer != answer)
+            {
+                entry.verdict = Verdict::Wrong;
+            }
+        }
+        match self
+            .entries
+            .iter_mut()
+            .find(|e| e.day == day && e.part == part && e.answer == answer)
+        {
+            Some(entry) => entry.verdict = verdict,
+            None => self.entries.push(Entry {
+                day,
+                part,
+                answer,
+                verdict,
+            }),
+        }
+        // Keep the file in a stable order so it diffs nicely
+        self.entries.sort_by_key(|e| (e.day, e.part));
+    }
+
+    /// Check an answer against what is known, without going online
+    pub fn check(&self, day: u8, part: u8, answer: &Answer) -> Check {
+        let answer = answer.to_string();
+        let entries: Vec<&Entry> = self
+            .entries
+            .iter()
+            .filter(|e| e.day == day && e.part == part)
+            .collect();
+
+        // Once the correct answer is known, every other answer is wrong
+        if let Some(correct) = entries.iter().find(|e| e.verdict == Verdict::Correct) {
+            return if correct.answer == answer {
+                Check::Correct
+            } else {
+                Check::Wrong {
+                    expected: Some(correct.answer.clone()),
+                }
+            };
+        }
+        match entries.iter().find(|e| e.answer == answer) {
+            Some(entry) if entry.verdict == Verdict::Wrong => Check::Wrong { expected: None },
+            Some(_) => Check::Pending,
+            None => Check::Unknown,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        let mut ledger = Ledger::default();
+        assert_eq!(ledger.check(7, 1, &Answer::Int(5)), Check::Unknown);
+
+        ledger.record(7, 1, &Answer::Int(5), Verdict::Wrong);
+        ledger.record(7, 1, &Answer::Int(6), Verdict::Submitted);
+        assert_eq!(
+            ledger.check(7, 1, &Answer::Int(5)),
+            Check::Wrong { expected: None }
+        );
+        assert_eq!(ledger.check(7, 1, &Answer::Int(6)), Check::Pending);
+        assert_eq!(ledger.check(7, 1, &Answer::Int(7)), Check::Unknown);
+        assert_eq!(ledger.check(7, 2, &Answer::Int(5)), Check::Unknown);
+
+        ledger.record(7, 1, &Answer::Int(6), Verdict::Correct);
+        assert_eq!(ledger.check(7, 1, &Answer::Int(6)), Check::Correct);
+        assert_eq!(
+            ledger.check(7, 1, &Answer::Int(8)),
+            Check::Wrong {
+                expected: Some("6".to_string())
+            }
+        );
+        assert_eq!(ledger.entries().len(), 2);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut ledger = Ledger::default();
+        ledger.record(3, 2, &Answer::from("abc"), Verdict::Correct);
+        ledger.record(1, 1, &Answer::Int(42), Verdict::Wrong);
+        let text = serde_json::to_string(&ledger).unwrap();
+        assert_eq!(serde_json::from_str::<Ledger>(&text).unwrap(), ledger);
+        assert_eq!(ledger.entries()[0].day, 1);
+    }
+
+    #[test]
+    fn test_correct_demotes_others() {
+        let mut ledger = Ledger::default();
+        ledger.record(9, 2, &Answer::Int(1), Verdict::Correct);
+        ledger.record(9, 2, &Answer::Int(2), Verdict::Submitted);
+        ledger.record(9, 1, &Answer::Int(3), Verdict::Correct);
+        ledger.record(9, 2, &Answer::Int(2), Verdict::Correct);
+
+        let verdicts: Vec<(u8, &str, Verdict)> = ledger
+            .entries()
+            .iter()
+            .map(|e| (e.part, e.answer.as_str(), e.verdict))
+            .collect();
+        assert_eq!(
+            verdicts,
+            vec![
+                (1, "3", Verdict::Correct),
+                (2, "1", Verdict::Wrong),
+                (2, "2", Verdict::Correct)
+            ]
+        );
+        assert_eq!(
+            ledger.check(9, 2, &Answer::Int(1)),
+            Check::Wrong {
+                expected: Some("2".to_string())
+            }
+        );
+    }
+}
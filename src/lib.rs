@@ -1,3 +1,5 @@
+pub mod answer;
+pub mod bench;
 pub mod day01;
 pub mod day02;
 pub mod day03;
@@ -22,4 +24,13 @@ pub mod day21;
 pub mod day22;
 pub mod day23;
 pub mod day24;
-pub mod day25;
\ No newline at end of file
+pub mod day25;
+pub mod geometry;
+pub mod grid;
+pub mod input;
+pub mod ledger;
+pub mod math;
+pub mod parse;
+pub mod polygon;
+pub mod runner;
+pub mod search;
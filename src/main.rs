@@ -1,5 +1,54 @@
-use adventofcode::*;
+use adventofcode::runner::{self, Command};
+use std::{env, process::ExitCode};
 
-fn main() {
-    day24::solution1();
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.is_empty() || args.iter().any(|a| a == "--help" || a == "-h") {
+        println!("{}", runner::USAGE);
+        return ExitCode::SUCCESS;
+    }
+
+    let command = match Command::parse(args) {
+        Ok(command) => command,
+        Err(message) => {
+            eprintln!("{message}\n\n{}", runner::USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    match command {
+        Command::List => {
+            runner::list();
+            ExitCode::SUCCESS
+        }
+        Command::All {
+            part,
+            input_dir,
+            action,
+            ledger,
+            report,
+        } => match runner::run_all(part, input_dir, action, ledger, report) {
+            Ok(true) => ExitCode::SUCCESS,
+            Ok(false) => ExitCode::FAILURE,
+            Err(message) => {
+                eprintln!("{message}");
+                ExitCode::FAILURE
+            }
+        },
+        Command::Day {
+            day,
+            part,
+            source,
+            action,
+            ledger,
+            report,
+        } => match runner::run_day(day, part, &source, action, ledger, report) {
+            Ok(true) => ExitCode::SUCCESS,
+            Ok(false) => ExitCode::FAILURE,
+            Err(message) => {
+                eprintln!("{message}");
+                ExitCode::FAILURE
+            }
+        },
+    }
 }
@@ -0,0 +1,191 @@
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+/// Greatest common divisor, always non-negative
+pub fn gcd(a: i128, b: i128) -> i128 {
+    if b == 0 {
+        a.abs()
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Least common multiple, always non-negative
+pub fn lcm(a: i128, b: i128) -> i128 {
+    if a == 0 || b == 0 {
+        0
+    } else {
+        (a / gcd(a, b) * b).abs()
+    }
+}
+
+/// Coefficients `(g, x, y)` with `a * x + b * y = g = gcd(a, b)`
+pub fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        (a, 1, 0)
+    } else {
+        let (g, x, y) = extended_gcd(b, a % b);
+        (g, y, x - (a / b) * y)
+    }
+}
+
+/// Combine congruences `t = residue (mod modulus)` into a single one, with the moduli
+/// not necessarily coprime. Returns the smallest non-negative residue and the lcm of the
+/// moduli, or `None` if the congruences contradict each other.
+pub fn crt(congruences: impl IntoIterator<Item = (i128, i128)>) -> Option<(i128, i128)> {
+    let mut combined = (0, 1);
+    for (residue, modulus) in congruences {
+        let (r1, m1) = combined;
+        let r2 = residue.rem_euclid(modulus);
+        let (g, p, _) = extended_gcd(m1, modulus);
+        if (r2 - r1) % g != 0 {
+            return None;
+        }
+        // Solve r1 + m1 * k = r2 (mod modulus) for k
+        let m = m1 / g * modulus;
+        let k = ((r2 - r1) / g % (modulus / g) * p).rem_euclid(modulus / g);
+        combined = ((r1 + m1 * k).rem_euclid(m), m);
+    }
+    Some(combined)
+}
+
+/// Exact fraction, kept in lowest terms with a positive denominator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rational {
+    numerator: i128,
+    denominator: i128,
+}
+
+impl Rational {
+    pub const ZERO: Rational = Rational::integer(0);
+    pub const ONE: Rational = Rational::integer(1);
+
+    pub fn new(numerator: i128, denominator: i128) -> Rational {
+        assert!(denominator != 0, "Rational with a zero denominator.");
+        let g = gcd(numerator, denominator) * denominator.signum();
+        Rational {
+            numerator: numerator / g,
+            denominator: denominator / g,
+        }
+    }
+
+    pub const fn integer(n: i128) -> Rational {
+        Rational {
+            numerator: n,
+            denominator: 1,
+        }
+    }
+
+    pub fn numerator(self) -> i128 {
+        self.numerator
+    }
+
+    pub fn denominator(self) -> i128 {
+        self.denominator
+    }
+
+    /// The value as an integer, if it is one
+    pub fn to_integer(self) -> Option<i128> {
+        (self.denominator == 1).then_some(self.numerator)
+    }
+}
+
+impl From<i64> for Rational {
+    fn from(n: i64) -> Rational {
+        Rational::integer(n as i128)
+    }
+}
+
+impl Add for Rational {
+    type Output = Rational;
+
+    fn add(self, other: Rational) -> Rational {
+        Rational::new(
+            self.numerator * other.denominator + other.numerator * self.denominator,
+            self.denominator * other.denominator,
+        )
+    }
+}
+
+impl Sub for Rational {
+    type Output = Rational;
+
+    fn sub(self, other: Rational) -> Rational {
+        self + -other
+    }
+}
+
+impl Mul for Rational {
+    type Output = Rational;
+
+    fn mul(self, other: Rational) -> Rational {
+        Rational::new(
+            self.numerator * other.numerator,
+            self.denominator * other.denominator,
+        )
+    }
+}
+
+impl Div for Rational {
+    type Output = Rational;
+
+    fn div(self, other: Rational) -> Rational {
+        Rational::new(
+            self.numerator * other.denominator,
+            self.denominator * other.numerator,
+        )
+    }
+}
+
+impl Neg for Rational {
+    type Output = Rational;
+
+    fn neg(self) -> Rational {
+        Rational {
+            numerator: -self.numerator,
+            denominator: self.denominator,
+        }
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.denominator == 1 {
+            write!(f, "{}", self.numerator)
+        } else {
+            write!(f, "{}/{}", self.numerator, self.denominator)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn divisors() {
+        assert_eq!(gcd(12, -18), 6);
+        assert_eq!(lcm(4, 6), 12);
+        let (g, x, y) = extended_gcd(240, 46);
+        assert_eq!((g, 240 * x + 46 * y), (2, 2));
+    }
+
+    #[test]
+    fn chinese_remainders() {
+        assert_eq!(crt([(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        assert_eq!(crt([(1, 4), (3, 6)]), Some((9, 12)));
+        assert_eq!(crt([(1, 4), (2, 6)]), None);
+        assert_eq!(crt([]), Some((0, 1)));
+    }
+
+    #[test]
+    fn rationals() {
+        let half = Rational::new(2, -4);
+        assert_eq!((half.numerator(), half.denominator()), (-1, 2));
+        assert_eq!(half + Rational::ONE, Rational::new(1, 2));
+        assert_eq!(half * half - Rational::new(1, 4), Rational::ZERO);
+        assert_eq!((half / Rational::new(1, 6)).to_integer(), Some(-3));
+        assert_eq!(Rational::new(7, 3).to_string(), "7/3");
+        assert_eq!(Rational::from(5).to_integer(), Some(5));
+    }
+}
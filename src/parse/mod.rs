@@ -0,0 +1,116 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// Where and why a puzzle input could not be parsed. Lines and columns count from 1.
+/// Parsers of a single line leave `line` at 0 for `lines` to fill in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub day: u8,
+    pub line: usize,
+    pub column: usize,
+    /// The text that could not be parsed
+    pub text: String,
+    /// What was expected instead
+    pub reason: String,
+}
+
+impl ParseError {
+    pub fn new(day: u8, column: usize, text: &str, reason: impl Into<String>) -> ParseError {
+        ParseError {
+            day,
+            line: 0,
+            column,
+            text: text.to_string(),
+            reason: reason.into(),
+        }
+    }
+
+    /// The same error, placed on the given line of the input
+    pub fn on_line(self, line: usize) -> ParseError {
+        ParseError { line, ..self }
+    }
+
+    /// The same error, moved right by `offset` columns. Used when a field of a line
+    /// is parsed on its own, so its columns count from the start of the field.
+    pub fn shifted(self, offset: usize) -> ParseError {
+        ParseError {
+            column: self.column + offset,
+            ..self
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Day {:02} input, line {}, column {}: {}",
+            self.day, self.line, self.column, self.reason
+        )?;
+        if self.text.is_empty() {
+            write!(f, ", found nothing")
+        } else {
+            write!(f, ", found '{}'", self.text)
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// Fields of a line split on `separator`, each with the column it starts at
+pub fn fields(line: &str, separator: char) -> impl Iterator<Item = (usize, &str)> {
+    line.split(separator).scan(1, |column, field| {
+        let start = *column;
+        *column += field.chars().count() + 1;
+        Some((start, field))
+    })
+}
+
+/// Parse a number, or an error pointing at the text that isn't one
+pub fn number<T: FromStr>(day: u8, column: usize, text: &str) -> Result<T, ParseError> {
+    text.parse()
+        .map_err(|_| ParseError::new(day, column, text, "expected a number"))
+}
+
+/// Parse every line of the input, placing any error on the line it came from
+pub fn lines<'a, T>(
+    input: &'a str,
+    mut parse_line: impl FnMut(&'a str) -> Result<T, ParseError>,
+) -> Result<Vec<T>, ParseError> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| parse_line(line).map_err(|e| e.on_line(i + 1)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn field_columns() {
+        let fields: Vec<(usize, &str)> = fields("ab,,cde,f", ',').collect();
+        assert_eq!(fields, vec![(1, "ab"), (4, ""), (5, "cde"), (9, "f")]);
+    }
+
+    #[test]
+    fn errors_point_at_their_line() {
+        let parsed = lines("1 2\n3 x\n5 6", |line| {
+            fields(line, ' ')
+                .map(|(column, field)| number::<u32>(9, column, field))
+                .collect::<Result<Vec<u32>, ParseError>>()
+        });
+        let error = parsed.unwrap_err();
+        assert_eq!((error.line, error.column), (2, 3));
+        assert_eq!(
+            error.to_string(),
+            "Day 09 input, line 2, column 3: expected a number, found 'x'"
+        );
+        assert_eq!(
+            lines("1\n2", |line| number::<u32>(9, 1, line)),
+            Ok(vec![1, 2])
+        );
+    }
+}
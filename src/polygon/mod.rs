@@ -0,0 +1,97 @@
+use crate::geometry::{Coord, Direction};
+use crate::math;
+
+/// Closed polygon through lattice points. The last vertex connects back to the first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Polygon {
+    vertices: Vec<Coord>,
+}
+
+impl Polygon {
+    pub fn from_vertices(vertices: Vec<Coord>) -> Polygon {
+        Polygon { vertices }
+    }
+
+    /// Polygon traced by moving `length` tiles in each direction, starting at the origin.
+    /// The path is expected to end where it started.
+    pub fn from_moves(moves: impl IntoIterator<Item = (Direction, i64)>) -> Polygon {
+        let mut position = Coord::ORIGIN;
+        let mut vertices = vec![position];
+        for (direction, length) in moves {
+            position += direction.offset() * length;
+            vertices.push(position);
+        }
+        // The closing vertex is the start again
+        if vertices.len() > 1 && vertices.last() == vertices.first() {
+            vertices.pop();
+        }
+        Polygon { vertices }
+    }
+
+    pub fn vertices(&self) -> &[Coord] {
+        &self.vertices
+    }
+
+    /// Consecutive pairs of vertices, including the edge from the last vertex back to the first
+    fn edges(&self) -> impl Iterator<Item = (Coord, Coord)> + '_ {
+        self.vertices
+            .iter()
+            .zip(self.vertices.iter().cycle().skip(1))
+            .map(|(a, b)| (*a, *b))
+    }
+
+    /// Twice the enclosed area, by the shoelace formula. Doubling keeps it an integer.
+    pub fn double_area(&self) -> i64 {
+        self.edges()
+            .map(|(a, b)| a.i * b.j - b.i * a.j)
+            .sum::<i64>()
+            .abs()
+    }
+
+    /// Number of lattice points on the edges
+    pub fn boundary_points(&self) -> i64 {
+        self.edges()
+            .map(|(a, b)| math::gcd((b.i - a.i) as i128, (b.j - a.j) as i128) as i64)
+            .sum()
+    }
+
+    /// Number of lattice points strictly inside, by Pick's theorem: A = I + B/2 - 1
+    pub fn interior_points(&self) -> i64 {
+        (self.double_area() - self.boundary_points()) / 2 + 1
+    }
+
+    /// Number of lattice points inside or on the edges. When the vertices are tile
+    /// centers, this is the number of tiles the polygon covers.
+    pub fn lattice_area(&self) -> i64 {
+        self.interior_points() + self.boundary_points()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn square() {
+        let square = Polygon::from_moves([
+            (Direction::Right, 2),
+            (Direction::Down, 2),
+            (Direction::Left, 2),
+            (Direction::Up, 2),
+        ]);
+        assert_eq!(square.vertices().len(), 4);
+        assert_eq!(square.double_area(), 8);
+        assert_eq!(square.boundary_points(), 8);
+        assert_eq!(square.interior_points(), 1);
+        assert_eq!(square.lattice_area(), 9);
+    }
+
+    #[test]
+    fn diagonal_edges() {
+        let triangle =
+            Polygon::from_vertices(vec![Coord::new(0, 0), Coord::new(0, 4), Coord::new(4, 0)]);
+        assert_eq!(triangle.double_area(), 16);
+        assert_eq!(triangle.boundary_points(), 12);
+        assert_eq!(triangle.interior_points(), 3);
+    }
+}
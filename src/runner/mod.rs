@@ -0,0 +1,572 @@
+use crate::answer::Solution;
+use crate::bench::{self, Report};
+use crate::input::Source;
+use crate::ledger::{self, Check, Ledger, Verdict};
+use crate::*;
+use aocd::Aocd;
+use std::path::{Path, PathBuf};
+
+pub const YEAR: u16 = 2023;
+
+/// Entry point of a single puzzle part; takes the puzzle input text and returns the answer
+pub type Solver = fn(&str) -> Solution;
+
+/// Solvers for each day, indexed by day - 1. Parts that have not been solved yet are `None`.
+pub const SOLVERS: [[Option<Solver>; 2]; 25] = [
+    [Some(day01::solution1), Some(day01::solution2)],
+    [Some(day02::solution1), Some(day02::solution2)],
+    [Some(day03::solution1), Some(day03::solution2)],
+    [Some(day04::solution1), Some(day04::solution2)],
+    [Some(day05::solution1), Some(day05::solution2)],
+    [Some(day06::solution1), Some(day06::solution2)],
+    [None, Some(day07::solution2)],
+    [Some(day08::solution1), Some(day08::solution2)],
+    [Some(day09::solution1), Some(day09::solution2)],
+    [Some(day10::solution1), Some(day10::solution2)],
+    [Some(day11::solution1), Some(day11::solution2)],
+    [Some(day12::solution1), Some(day12::solution2)],
+    [Some(day13::solution1), Some(day13::solution2)],
+    [Some(day14::solution1), Some(day14::solution2)],
+    [Some(day15::solution1), Some(day15::solution2)],
+    [Some(day16::solution1), Some(day16::solution2)],
+    [Some(day17::solution1), Some(day17::solution2)],
+    [Some(day18::solution1), Some(day18::solution2)],
+    [Some(day19::solution1), Some(day19::solution2)],
+    [Some(day20::solution1), Some(day20::solution2)],
+    [Some(day21::solution1), Some(day21::solution2)],
+    [Some(day22::solution1), Some(day22::solution2)],
+    [Some(day23::solution1), Some(day23::solution2)],
+    [Some(day24::solution1), Some(day24::solution2)],
+    [Some(day25::solution1), None],
+];
+
+pub fn solver(day: u8, part: u8) -> Option<Solver> {
+    SOLVERS[day as usize - 1][part as usize - 1]
+}
+
+/// What to do with each answer once it has been computed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Print,
+    /// Compare against the ledger, without going online
+    Check,
+    /// Store in the ledger with the given verdict
+    Record(Verdict),
+    /// Send to Advent of Code, unless the answer is already in the ledger
+    Submit,
+    /// Time the given number of runs of each solver and write a report
+    Bench(usize),
+}
+
+/// What the command line asked us to run
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    List,
+    All {
+        part: Option<u8>,
+        input_dir: Option<PathBuf>,
+        action: Action,
+        ledger: Option<PathBuf>,
+        report: Option<PathBuf>,
+    },
+    Day {
+        day: u8,
+        part: Option<u8>,
+        source: Source,
+        action: Action,
+        ledger: Option<PathBuf>,
+        report: Option<PathBuf>,
+    },
+}
+
+pub const USAGE: &str = "\
+Usage: adventofcode [--year 2023] --day <1-25> [--part <1|2>] [<input>] [<answers>]
+       adventofcode [--year 2023] --all [--part <1|2>] [--input-dir <dir>] [--check | --bench <runs>]
+       adventofcode --list
+
+Options:
+  --year <year>       Puzzle year (only 2023 is available)
+  --day <day>         Run the given day
+  --part <part>       Run only the given part of the day
+  --all               Run every implemented part of every day that has an input
+  --list              List which days and parts are implemented
+  --help              Show this message
+
+Input (one of):
+  --input <path>      Read the puzzle input from the given file
+  --stdin             Read the puzzle input from standard input
+  --input-dir <dir>   Read dayNN.txt from the given directory. Defaults to $AOC_INPUT_DIR,
+                      or ./input if that is not set
+
+Answers (one of, all but --submit work offline):
+  --check             Check the answers against the ledger: correct, wrong or unknown
+  --record <verdict>  Record the answer in the ledger as correct, wrong or submitted.
+                      Needs --part
+  --submit            Submit the answers to Advent of Code, unless the ledger already
+                      has them, and record them as submitted
+  --ledger <path>     Ledger file. Defaults to $AOC_LEDGER, or ./answers.json if that
+                      is not set
+  --bench <runs>      Time each part over the given number of runs instead, reporting
+                      the parse, solve and total times
+  --report <path>     Benchmark report, written as JSON. Defaults to $AOC_BENCH_REPORT,
+                      or ./bench.json if that is not set";
+
+fn parse_number(
+    flag: &str,
+    value: Option<String>,
+    range: std::ops::RangeInclusive<u16>,
+) -> Result<u16, String> {
+    let value = value.ok_or(format!("{flag} expects a value"))?;
+    match value.parse::<u16>() {
+        Ok(n) if range.contains(&n) => Ok(n),
+        _ => Err(format!(
+            "{flag} expects a number from {} to {}, got '{value}'",
+            range.start(),
+            range.end()
+        )),
+    }
+}
+
+impl Command {
+    /// Parse the command line arguments (excluding the program name)
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Command, String> {
+        let mut args = args.into_iter();
+        let mut list = false;
+        let mut all = false;
+        let mut day: Option<u8> = None;
+        let mut part: Option<u8> = None;
+        let mut sources: Vec<Source> = Vec::new();
+        let mut input_dir: Option<PathBuf> = None;
+        let mut actions: Vec<Action> = Vec::new();
+        let mut ledger: Option<PathBuf> = None;
+        let mut report: Option<PathBuf> = None;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--year" => {
+                    let year = parse_number("--year", args.next(), 2015..=9999)?;
+                    if year != YEAR {
+                        return Err(format!("Only {YEAR} puzzles are available, not {year}"));
+                    }
+                }
+                "--day" => day = Some(parse_number("--day", args.next(), 1..=25)? as u8),
+                "--part" => part = Some(parse_number("--part", args.next(), 1..=2)? as u8),
+                "--input" => {
+                    let path = args.next().ok_or("--input expects a path")?;
+                    sources.push(Source::File(PathBuf::from(path)));
+                }
+                "--stdin" => sources.push(Source::Stdin),
+                "--input-dir" => {
+                    let dir = PathBuf::from(args.next().ok_or("--input-dir expects a path")?);
+                    input_dir = Some(dir.clone());
+                    sources.push(Source::Cache(dir));
+                }
+                "--check" => actions.push(Action::Check),
+                "--record" => {
+                    let value = args.next().ok_or("--record expects a verdict")?;
+                    let verdict = Verdict::parse(&value).ok_or(format!(
+                        "--record expects correct, wrong or submitted, got '{value}'"
+                    ))?;
+                    actions.push(Action::Record(verdict));
+                }
+                "--submit" => actions.push(Action::Submit),
+                "--bench" => {
+                    let runs = parse_number("--bench", args.next(), 1..=10000)?;
+                    actions.push(Action::Bench(runs as usize));
+                }
+                "--report" => {
+                    report = Some(PathBuf::from(args.next().ok_or("--report expects a path")?))
+                }
+                "--ledger" => {
+                    ledger = Some(PathBuf::from(args.next().ok_or("--ledger expects a path")?))
+                }
+                "--all" => all = true,
+                "--list" => list = true,
+                _ => return Err(format!("Unknown argument '{arg}'")),
+            }
+        }
+
+        if sources.len() > 1 {
+            return Err("Only one of --input, --stdin and --input-dir can be given".to_string());
+        }
+
+        if actions.len() > 1 {
+            return Err(
+                "Only one of --check, --record, --submit and --bench can be given".to_string(),
+            );
+        }
+        let action = actions.pop().unwrap_or(Action::Print);
+        if matches!(action, Action::Record(_)) && part.is_none() {
+            return Err("--record needs --part".to_string());
+        }
+        if report.is_some() && !matches!(action, Action::Bench(_)) {
+            return Err("--report needs --bench".to_string());
+        }
+
+        match (list, all, day) {
+            (true, false, None) => Ok(Command::List),
+            (false, true, None) if input_dir.is_none() && !sources.is_empty() => {
+                Err("--all reads from the input directory; use --input-dir".to_string())
+            }
+            (false, true, None)
+                if !matches!(action, Action::Print | Action::Check | Action::Bench(_)) =>
+            {
+                Err("--record and --submit cannot be combined with --all".to_string())
+            }
+            (false, true, None) => Ok(Command::All {
+                part,
+                input_dir,
+                action,
+                ledger,
+                report,
+            }),
+            (false, false, Some(day)) => Ok(Command::Day {
+                day,
+                part,
+                source: sources.pop().unwrap_or_else(|| Source::cache(None)),
+                action,
+                ledger,
+                report,
+            }),
+            (false, false, None) => Err("Expected one of --day, --all or --list".to_string()),
+            _ => Err("--day, --all and --list cannot be combined".to_string()),
+        }
+    }
+}
+
+/// Print a table of which parts are implemented and which are stubs
+pub fn list() {
+    println!("Day  Part 1  Part 2");
+    for (i, parts) in SOLVERS.iter().enumerate() {
+        let status: Vec<&str> = parts
+            .iter()
+            .map(|p| if p.is_some() { "done" } else { "stub" })
+            .collect();
+        println!("{:>3}  {:<6}  {}", i + 1, status[0], status[1]);
+    }
+}
+
+fn selected_parts(part: Option<u8>) -> Vec<u8> {
+    match part {
+        Some(part) => vec![part],
+        None => vec![1, 2],
+    }
+}
+
+/// Run the solvers and act on their answers. Returns false if any answer is known to be wrong,
+/// or if a solver could not find one.
+fn run_solvers(
+    day: u8,
+    solvers: Vec<(u8, Solver)>,
+    input_text: &str,
+    action: Action,
+    ledger: &mut Ledger,
+    report: &mut Report,
+) -> bool {
+    let mut all_ok = true;
+    for (part, solver) in solvers {
+        if let Action::Bench(runs) = action {
+            match bench::measure(day, part, solver, input_text, runs) {
+                Ok(measurement) => {
+                    println!("{measurement}");
+                    report.add(measurement);
+                }
+                Err(error) => {
+                    eprintln!("Day {day:02} part {part}: {error}");
+                    all_ok = false;
+                }
+            }
+            continue;
+        }
+        let answer = match solver(input_text) {
+            Ok(answer) => answer,
+            Err(error) => {
+                eprintln!("Day {day:02} part {part}: {error}");
+                all_ok = false;
+                continue;
+            }
+        };
+        match action {
+            Action::Print => println!("Day {day:02} part {part}: {answer}"),
+            Action::Check => {
+                let check = ledger.check(day, part, &answer);
+                println!("Day {day:02} part {part}: {answer} ({check})");
+                all_ok &= !matches!(check, Check::Wrong { .. });
+            }
+            Action::Record(verdict) => {
+                ledger.record(day, part, &answer, verdict);
+                println!("Day {day:02} part {part}: {answer} (recorded as {verdict})");
+            }
+            Action::Submit => {
+                let check = ledger.check(day, part, &answer);
+                println!("Day {day:02} part {part}: {answer} ({check})");
+                if check == Check::Unknown {
+                    Aocd::new(YEAR, day, None).submit(part, &answer);
+                    ledger.record(day, part, &answer, Verdict::Submitted);
+                    println!("Record the response with --part {part} --record <correct|wrong>");
+                } else if check == Check::Pending {
+                    eprintln!(
+                        "Day {day:02} part {part}: already submitted, record the response with \
+                         --part {part} --record <correct|wrong> before submitting again"
+                    );
+                } else {
+                    eprintln!(
+                        "Day {day:02} part {part}: the verdict is already known, not submitting"
+                    );
+                    all_ok &= check == Check::Correct;
+                }
+            }
+            Action::Bench(_) => unreachable!("Benchmarks are handled above."),
+        }
+        if let Some(explanation) = answer.explanation() {
+            println!("Day {day:02} part {part}: {explanation}");
+        }
+    }
+    all_ok
+}
+
+/// The ledger is only read for actions that need it, and only written when it changes
+fn open_ledger(action: Action, path: &Path) -> Result<Ledger, String> {
+    match action {
+        Action::Print | Action::Bench(_) => Ok(Ledger::default()),
+        _ => Ledger::load(path),
+    }
+}
+
+fn close_ledger(action: Action, path: &Path, ledger: &Ledger) -> Result<(), String> {
+    match action {
+        Action::Record(_) | Action::Submit => ledger.save(path),
+        _ => Ok(()),
+    }
+}
+
+/// The benchmark report is only written when there is something in it
+fn close_report(path: Option<PathBuf>, report: &Report) -> Result<(), String> {
+    if report.measurements().is_empty() {
+        return Ok(());
+    }
+    let path = bench::report_path(path);
+    report.save(&path)?;
+    match report.commit() {
+        Some(commit) => println!(
+            "Benchmark report for commit {commit} written to {}",
+            path.display()
+        ),
+        None => println!("Benchmark report written to {}", path.display()),
+    }
+    Ok(())
+}
+
+/// Run the selected parts of a day. Returns false if any of them is missing or wrong.
+pub fn run_day(
+    day: u8,
+    part: Option<u8>,
+    source: &Source,
+    action: Action,
+    ledger_path: Option<PathBuf>,
+    report_path: Option<PathBuf>,
+) -> Result<bool, String> {
+    let ledger_path = ledger::ledger_path(ledger_path);
+    let mut ledger = open_ledger(action, &ledger_path)?;
+    let mut report = Report::new();
+    let mut all_present = true;
+    let mut solvers: Vec<(u8, Solver)> = Vec::new();
+    for part in selected_parts(part) {
+        if let Some(solver) = solver(day, part) {
+            solvers.push((part, solver));
+        } else {
+            eprintln!("Day {day:02} part {part} is not implemented");
+            all_present = false;
+        }
+    }
+
+    // Only read the input if there is something to run
+    let mut all_ok = true;
+    if !solvers.is_empty() {
+        let input_text = source.load(day)?;
+        all_ok = run_solvers(day, solvers, &input_text, action, &mut ledger, &mut report);
+    }
+    close_ledger(action, &ledger_path, &ledger)?;
+    close_report(report_path, &report)?;
+    Ok(all_present && all_ok)
+}
+
+/// Run every implemented part of every day from the input directory, skipping the stubs.
+/// Days without an input file are skipped as well; returns false if there were any,
+/// or if any answer is known to be wrong.
+pub fn run_all(
+    part: Option<u8>,
+    input_dir: Option<PathBuf>,
+    action: Action,
+    ledger_path: Option<PathBuf>,
+    report_path: Option<PathBuf>,
+) -> Result<bool, String> {
+    let dir = input::input_dir(input_dir);
+    let ledger_path = ledger::ledger_path(ledger_path);
+    let mut ledger = open_ledger(action, &ledger_path)?;
+    let mut report = Report::new();
+    let mut all_present = true;
+    let mut all_ok = true;
+    for day in 1..=25 {
+        let solvers: Vec<(u8, Solver)> = selected_parts(part)
+            .into_iter()
+            .filter_map(|p| solver(day, p).map(|s| (p, s)))
+            .collect();
+        if solvers.is_empty() {
+            continue;
+        }
+        if !input::cache_path(&dir, day).exists() {
+            eprintln!("Day {day:02} skipped: no input in {}", dir.display());
+            all_present = false;
+            continue;
+        }
+        let input_text = Source::Cache(dir.clone()).load(day)?;
+        all_ok &= run_solvers(day, solvers, &input_text, action, &mut ledger, &mut report);
+    }
+    close_ledger(action, &ledger_path, &ledger)?;
+    close_report(report_path, &report)?;
+    Ok(all_present && all_ok)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<Command, String> {
+        Command::parse(args.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn test_accepted() {
+        assert_eq!(parse("--list"), Ok(Command::List));
+        assert_eq!(
+            parse("--day 5"),
+            Ok(Command::Day {
+                day: 5,
+                part: None,
+                source: Source::cache(None),
+                action: Action::Print,
+                ledger: None,
+                report: None,
+            })
+        );
+        assert_eq!(
+            parse("--year 2023 --day 25 --part 2 --input day25.txt --check --ledger a.json"),
+            Ok(Command::Day {
+                day: 25,
+                part: Some(2),
+                source: Source::File(PathBuf::from("day25.txt")),
+                action: Action::Check,
+                ledger: Some(PathBuf::from("a.json")),
+                report: None,
+            })
+        );
+        assert_eq!(
+            parse("--stdin --record submitted --part 1 --day 1"),
+            Ok(Command::Day {
+                day: 1,
+                part: Some(1),
+                source: Source::Stdin,
+                action: Action::Record(Verdict::Submitted),
+                ledger: None,
+                report: None,
+            })
+        );
+        assert_eq!(
+            parse("--day 3 --input-dir inputs --submit"),
+            Ok(Command::Day {
+                day: 3,
+                part: None,
+                source: Source::Cache(PathBuf::from("inputs")),
+                action: Action::Submit,
+                ledger: None,
+                report: None,
+            })
+        );
+        assert_eq!(
+            parse("--all --part 1 --input-dir inputs --bench 10 --report b.json"),
+            Ok(Command::All {
+                part: Some(1),
+                input_dir: Some(PathBuf::from("inputs")),
+                action: Action::Bench(10),
+                ledger: None,
+                report: Some(PathBuf::from("b.json")),
+            })
+        );
+        assert_eq!(
+            parse("--all --check"),
+            Ok(Command::All {
+                part: None,
+                input_dir: None,
+                action: Action::Check,
+                ledger: None,
+                report: None,
+            })
+        );
+    }
+
+    #[test]
+    fn test_rejected() {
+        for (args, message) in [
+            ("", "Expected one of --day, --all or --list"),
+            (
+                "--day 1 --all",
+                "--day, --all and --list cannot be combined",
+            ),
+            ("--all --list", "--day, --all and --list cannot be combined"),
+            (
+                "--list --day 1",
+                "--day, --all and --list cannot be combined",
+            ),
+            (
+                "--day 1 --input a.txt --stdin",
+                "Only one of --input, --stdin and --input-dir can be given",
+            ),
+            (
+                "--day 1 --check --submit",
+                "Only one of --check, --record, --submit and --bench can be given",
+            ),
+            ("--day 1 --record correct", "--record needs --part"),
+            ("--day 1 --report b.json", "--report needs --bench"),
+            (
+                "--all --input a.txt",
+                "--all reads from the input directory; use --input-dir",
+            ),
+            (
+                "--all --part 1 --record wrong",
+                "--record and --submit cannot be combined with --all",
+            ),
+            (
+                "--all --submit",
+                "--record and --submit cannot be combined with --all",
+            ),
+            ("--day 0", "--day expects a number from 1 to 25, got '0'"),
+            ("--day 26", "--day expects a number from 1 to 25, got '26'"),
+            (
+                "--day one",
+                "--day expects a number from 1 to 25, got 'one'",
+            ),
+            (
+                "--day 1 --part 3",
+                "--part expects a number from 1 to 2, got '3'",
+            ),
+            (
+                "--day 1 --bench 0",
+                "--bench expects a number from 1 to 10000, got '0'",
+            ),
+            ("--day", "--day expects a value"),
+            ("--day 1 --input", "--input expects a path"),
+            (
+                "--year 2022 --day 1",
+                "Only 2023 puzzles are available, not 2022",
+            ),
+            (
+                "--day 1 --part 1 --record maybe",
+                "--record expects correct, wrong or submitted, got 'maybe'",
+            ),
+            ("--day 1 --verbose", "Unknown argument '--verbose'"),
+        ] {
+            assert_eq!(parse(args), Err(message.to_string()), "{args}");
+        }
+    }
+}
@@ -0,0 +1,242 @@
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+/// A discovered state, with the cheapest known way of reaching it
+struct Node<S, C> {
+    state: S,
+    parent: Option<usize>,
+    cost: C,
+    closed: bool,
+}
+
+/// Follow the parents of a node back to the start, returning the states from start to node
+fn reconstruct_path<S: Clone, C>(nodes: &[Node<S, C>], mut index: usize) -> Vec<S> {
+    let mut path = vec![nodes[index].state.clone()];
+    while let Some(parent) = nodes[index].parent {
+        path.push(nodes[parent].state.clone());
+        index = parent;
+    }
+    path.reverse();
+    path
+}
+
+/// Breadth-first search for the fewest steps from `start` to a goal state.
+/// Returns the number of steps and the path, including both the start and the goal.
+pub fn bfs<S, I>(
+    start: S,
+    mut successors: impl FnMut(&S) -> I,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> Option<(usize, Vec<S>)>
+where
+    S: Hash + Eq + Clone,
+    I: IntoIterator<Item = S>,
+{
+    let mut indices = HashMap::from([(start.clone(), 0)]);
+    let mut nodes = vec![Node {
+        state: start,
+        parent: None,
+        cost: 0,
+        closed: true,
+    }];
+    let mut frontier = VecDeque::from([0]);
+
+    while let Some(index) = frontier.pop_front() {
+        if is_goal(&nodes[index].state) {
+            return Some((nodes[index].cost, reconstruct_path(&nodes, index)));
+        }
+        let cost = nodes[index].cost + 1;
+        for next in successors(&nodes[index].state) {
+            if let Entry::Vacant(entry) = indices.entry(next) {
+                let next_index = nodes.len();
+                nodes.push(Node {
+                    state: entry.key().clone(),
+                    parent: Some(index),
+                    cost,
+                    closed: true,
+                });
+                entry.insert(next_index);
+                frontier.push_back(next_index);
+            }
+        }
+    }
+    None
+}
+
+/// Breadth-first flood fill from `start`, giving the fewest steps to every reachable state
+pub fn bfs_distances<S, I>(start: S, mut successors: impl FnMut(&S) -> I) -> HashMap<S, usize>
+where
+    S: Hash + Eq + Clone,
+    I: IntoIterator<Item = S>,
+{
+    let mut distances = HashMap::from([(start.clone(), 0)]);
+    let mut frontier = VecDeque::from([(start, 0)]);
+    while let Some((state, distance)) = frontier.pop_front() {
+        for next in successors(&state) {
+            if let Entry::Vacant(entry) = distances.entry(next) {
+                frontier.push_back((entry.key().clone(), distance + 1));
+                entry.insert(distance + 1);
+            }
+        }
+    }
+    distances
+}
+
+/// Dijkstra's algorithm for the cheapest path from `start` to a goal state.
+/// The successor closure gives every neighboring state with the cost of stepping to it.
+pub fn dijkstra<S, C, I>(
+    start: S,
+    successors: impl FnMut(&S) -> I,
+    is_goal: impl FnMut(&S) -> bool,
+) -> Option<(C, Vec<S>)>
+where
+    S: Hash + Eq + Clone,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (S, C)>,
+{
+    a_star(start, successors, |_| C::default(), is_goal)
+}
+
+/// A* search for the cheapest path from `start` to a goal state. The heuristic must never
+/// overestimate the remaining cost, and must not drop by more than the cost of a single step,
+/// or the path found may not be the cheapest.
+pub fn a_star<S, C, I>(
+    start: S,
+    mut successors: impl FnMut(&S) -> I,
+    mut heuristic: impl FnMut(&S) -> C,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> Option<(C, Vec<S>)>
+where
+    S: Hash + Eq + Clone,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (S, C)>,
+{
+    let mut indices = HashMap::from([(start.clone(), 0)]);
+    let mut frontier = BinaryHeap::from([Reverse((heuristic(&start), 0))]);
+    let mut nodes = vec![Node {
+        state: start,
+        parent: None,
+        cost: C::default(),
+        closed: false,
+    }];
+
+    while let Some(Reverse((_, index))) = frontier.pop() {
+        // A node may be queued several times, only expand it the first time
+        if nodes[index].closed {
+            continue;
+        }
+        nodes[index].closed = true;
+
+        let cost = nodes[index].cost;
+        if is_goal(&nodes[index].state) {
+            return Some((cost, reconstruct_path(&nodes, index)));
+        }
+
+        for (next, step) in successors(&nodes[index].state) {
+            let next_cost = cost + step;
+            let next_index = match indices.entry(next) {
+                Entry::Vacant(entry) => {
+                    let next_index = nodes.len();
+                    nodes.push(Node {
+                        state: entry.key().clone(),
+                        parent: Some(index),
+                        cost: next_cost,
+                        closed: false,
+                    });
+                    entry.insert(next_index);
+                    next_index
+                }
+                Entry::Occupied(entry) => {
+                    let next_index = *entry.get();
+                    let node = &mut nodes[next_index];
+                    if node.closed || next_cost >= node.cost {
+                        continue;
+                    }
+                    node.parent = Some(index);
+                    node.cost = next_cost;
+                    next_index
+                }
+            };
+            let estimate = next_cost + heuristic(&nodes[next_index].state);
+            frontier.push(Reverse((estimate, next_index)));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::Coord;
+    use crate::grid::Grid;
+
+    const MAZE: &str = "\
+..#....
+.##.##.
+...#...
+.#...#.";
+
+    fn maze() -> Grid<bool> {
+        Grid::parse(MAZE, |c| c == '#')
+    }
+
+    fn open_neighbors(grid: &Grid<bool>, coord: Coord) -> Vec<Coord> {
+        coord
+            .neighbors()
+            .into_iter()
+            .filter(|c| grid.at(*c) == Some(&false))
+            .collect()
+    }
+
+    #[test]
+    fn bfs_path() {
+        let grid = maze();
+        let end = Coord::new(3, 6);
+        let (steps, path) =
+            bfs(Coord::ORIGIN, |c| open_neighbors(&grid, *c), |c| *c == end).unwrap();
+        assert_eq!(steps, 11);
+        assert_eq!(path.len(), 12);
+        assert_eq!(path.first(), Some(&Coord::ORIGIN));
+        assert_eq!(path.last(), Some(&end));
+        assert!(path.windows(2).all(|w| w[0].manhattan(w[1]) == 1));
+    }
+
+    #[test]
+    fn bfs_unreachable() {
+        let grid = maze();
+        let wall = Coord::new(0, 2);
+        assert_eq!(
+            bfs(Coord::ORIGIN, |c| open_neighbors(&grid, *c), |c| *c == wall),
+            None
+        );
+        let distances = bfs_distances(Coord::ORIGIN, |c| open_neighbors(&grid, *c));
+        assert_eq!(distances.get(&Coord::new(3, 6)), Some(&11));
+        assert_eq!(distances.get(&wall), None);
+    }
+
+    #[test]
+    fn weighted_paths() {
+        // Stepping onto a tile costs its digit
+        let grid = Grid::parse("131\n919\n111", |c| c.to_digit(10).unwrap() as u64);
+        let successors = |c: &Coord| {
+            c.neighbors()
+                .into_iter()
+                .filter_map(|n| grid.at(n).map(|cost| (n, *cost)))
+                .collect::<Vec<_>>()
+        };
+        let end = Coord::new(2, 2);
+        let (cost, path) = dijkstra(Coord::ORIGIN, successors, |c| *c == end).unwrap();
+        assert_eq!(cost, 6);
+        assert_eq!(path.len(), 5);
+        let (cost, _) = a_star(
+            Coord::ORIGIN,
+            successors,
+            |c| c.manhattan(end) as u64,
+            |c| *c == end,
+        )
+        .unwrap();
+        assert_eq!(cost, 6);
+    }
+}